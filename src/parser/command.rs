@@ -1,9 +1,50 @@
-use std::{collections::HashMap, sync::LazyLock, vec};
-
+mod compare_object;
+mod content;
+mod convert_csv;
+mod convert_from_json;
+mod convert_to_json;
+mod format;
+mod get_command;
+mod get_date;
+mod get_member;
+mod get_random;
+mod invoke_expression;
+mod join_string;
+mod measure_object;
+mod merge_hashtable;
+mod new_object;
+mod object_properties;
+mod sort_object;
+mod variable;
+
+use std::{collections::HashMap, rc::Rc, sync::LazyLock, vec};
+
+use compare_object::compare_object;
+use content::{add_content, get_content, set_content};
+use convert_csv::{convert_from_csv, convert_to_csv};
+use convert_from_json::convert_from_json;
+pub(crate) use convert_from_json::{DEFAULT_DEPTH as JSON_DEPTH, parse_json};
+use convert_to_json::convert_to_json;
+use format::{format_list, format_table};
+use get_command::get_command;
+use get_date::get_date;
+use get_member::get_member;
+pub(crate) use get_random::Random;
+use get_random::get_random;
+use invoke_expression::invoke_expression;
+use join_string::join_string;
+use measure_object::measure_object;
+use merge_hashtable::merge_hashtable;
+use new_object::new_object;
+use sort_object::sort_object;
 use thiserror_no_std::Error;
+use variable::{get_variable, remove_variable, set_variable};
 
-use super::{SessionScope, StreamMessage, Val, value::ScriptBlock};
-use crate::{PowerShellSession, ScriptResult, parser::ParserError};
+use super::{
+    SessionScope, StreamMessage, Val,
+    value::{Params, ScriptBlock},
+};
+use crate::{NEWLINE, PowerShellSession, PsValue, ScriptResult, parser::ParserError};
 
 #[derive(Error, Debug, PartialEq, Clone)]
 pub enum CommandError {
@@ -23,6 +64,8 @@ impl From<ParserError> for CommandError {
 use crate::parser::ParserResult;
 pub type CallablePredType =
     Box<dyn Fn(Vec<CommandElem>, &mut PowerShellSession) -> ParserResult<CommandOutput>>;
+pub(crate) type UserCmdletType =
+    Rc<dyn Fn(Vec<CommandArg>, &mut PowerShellSession) -> Result<PsValue, String>>;
 
 #[derive(Debug, Clone)]
 pub struct CommandOutput {
@@ -72,6 +115,8 @@ pub struct Command {
     command_inner: CommandInner,
     args: Vec<CommandElem>,
     scope: SessionScope,
+    // invoked by the command reference, e.g. & (gcm iex)
+    reference: bool,
 }
 
 impl Command {
@@ -80,6 +125,7 @@ impl Command {
             command_inner: CommandInner::ScriptBlock(script_block),
             args: Vec::new(),
             scope: SessionScope::Current,
+            reference: false,
         }
     }
 
@@ -88,6 +134,14 @@ impl Command {
             command_inner: CommandInner::Cmdlet(cmdlet.to_string()),
             args: Vec::new(),
             scope: SessionScope::Current,
+            reference: false,
+        }
+    }
+
+    pub(crate) fn command_reference(cmdlet: &str) -> Self {
+        Self {
+            reference: true,
+            ..Self::cmdlet(cmdlet)
         }
     }
 
@@ -96,6 +150,7 @@ impl Command {
             command_inner: CommandInner::Path(path.to_string()),
             args: Vec::new(),
             scope: SessionScope::Current,
+            reference: false,
         }
     }
 
@@ -123,6 +178,7 @@ impl Command {
 impl std::fmt::Display for Command {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         let mut command = match &self.command_inner {
+            CommandInner::Cmdlet(name) if self.reference => format!("gcm {name}"),
             CommandInner::Cmdlet(name) => name.clone(),
             CommandInner::Path(path) => path.clone(),
             CommandInner::ScriptBlock(sb) => sb.deobfuscated_string(),
@@ -142,7 +198,7 @@ impl std::fmt::Display for Command {
 }
 
 pub(crate) type FunctionPredType =
-    fn(&mut Vec<CommandElem>, &mut PowerShellSession) -> ParserResult<CommandOutput>;
+    fn(&mut [CommandElem], &mut PowerShellSession) -> ParserResult<CommandOutput>;
 
 impl Command {
     const COMMAND_MAP: LazyLock<HashMap<&'static str, FunctionPredType>> = LazyLock::new(|| {
@@ -152,10 +208,45 @@ impl Command {
             ("write-host", write_host as FunctionPredType),
             ("write-error", write_error as FunctionPredType),
             ("write-verbose", write_verbose as FunctionPredType),
+            ("write-debug", write_debug as FunctionPredType),
+            ("write-information", write_information as FunctionPredType),
             ("where-object", where_object as FunctionPredType),
             ("get-location", get_location as FunctionPredType),
             ("powershell", powershell as FunctionPredType),
             ("foreach-object", foreach_object as FunctionPredType),
+            ("merge-hashtable", merge_hashtable as FunctionPredType),
+            ("join-path", join_path as FunctionPredType),
+            ("split-path", split_path as FunctionPredType),
+            ("new-object", new_object as FunctionPredType),
+            ("out-null", out_null as FunctionPredType),
+            ("out-string", out_string as FunctionPredType),
+            ("tee-object", tee_object as FunctionPredType),
+            ("format-table", format_table as FunctionPredType),
+            ("format-list", format_list as FunctionPredType),
+            ("get-command", get_command as FunctionPredType),
+            ("gcm", get_command as FunctionPredType),
+            ("get-member", get_member as FunctionPredType),
+            ("gm", get_member as FunctionPredType),
+            ("measure-object", measure_object as FunctionPredType),
+            ("measure", measure_object as FunctionPredType),
+            ("join-string", join_string as FunctionPredType),
+            ("get-date", get_date as FunctionPredType),
+            ("get-random", get_random as FunctionPredType),
+            ("sort-object", sort_object as FunctionPredType),
+            ("select-string", select_string as FunctionPredType),
+            ("convertto-json", convert_to_json as FunctionPredType),
+            ("convertto-csv", convert_to_csv as FunctionPredType),
+            ("convertfrom-csv", convert_from_csv as FunctionPredType),
+            ("convertfrom-json", convert_from_json as FunctionPredType),
+            ("set-content", set_content as FunctionPredType),
+            ("add-content", add_content as FunctionPredType),
+            ("get-content", get_content as FunctionPredType),
+            ("compare-object", compare_object as FunctionPredType),
+            ("set-variable", set_variable as FunctionPredType),
+            ("get-variable", get_variable as FunctionPredType),
+            ("remove-variable", remove_variable as FunctionPredType),
+            ("invoke-expression", invoke_expression as FunctionPredType),
+            ("iex", invoke_expression as FunctionPredType),
         ])
     });
 
@@ -164,13 +255,27 @@ impl Command {
     }
 
     fn impl_execute(&mut self, ps: &mut PowerShellSession) -> ParserResult<CommandOutput> {
+        // only script blocks and functions see the pipeline input as $input
+        let pipeline_input = ps.pipeline_input.take();
         match &mut self.command_inner {
-            CommandInner::ScriptBlock(sb) => sb.run(self.args.clone(), ps, None),
+            CommandInner::ScriptBlock(sb) => {
+                ps.pipeline_input = pipeline_input;
+                sb.run(self.args.clone(), ps, None)
+            }
             CommandInner::Cmdlet(name) => {
                 if let Some(fun) = ps.variables.get_function(&name.to_ascii_lowercase()) {
+                    ps.pipeline_input = pipeline_input;
                     fun(self.args.clone(), ps)
+                } else if let Some(cmdlet) = ps.cmdlets.get(&name.to_ascii_lowercase()).cloned() {
+                    let args = self.args.iter().cloned().map(CommandArg::from).collect();
+                    let val = cmdlet(args, ps).map_err(CommandError::ExecutionError)?;
+                    Ok(Val::from(val).into())
                 } else if let Some(cmdlet) = Self::get(&name.to_ascii_lowercase()) {
-                    cmdlet(&mut self.args, ps)
+                    // built-in cmdlets only know if the first argument is piped
+                    ps.piped_cmdlet_arg = pipeline_input.is_some();
+                    let res = cmdlet(&mut self.args, ps);
+                    ps.piped_cmdlet_arg = false;
+                    res
                 } else {
                     Err(ParserError::from(CommandError::NotFound(name.clone())))?
                 }
@@ -181,8 +286,39 @@ impl Command {
         }
     }
 
+    // parameters of the script block or the function run by the command
+    pub(crate) fn params(&self, ps: &PowerShellSession) -> Option<Params> {
+        match &self.command_inner {
+            CommandInner::ScriptBlock(sb) => Some(sb.params.clone()),
+            CommandInner::Cmdlet(name) => ps
+                .variables
+                .function_script_block(&name.to_ascii_lowercase())
+                .map(|sb| sb.params.clone()),
+            CommandInner::Path(_) => None,
+        }
+    }
+
+    // built-in cmdlets run in the caller's scope, so e.g. Set-Variable can
+    // define variables there. Functions and user cmdlets may shadow them
+    pub(crate) fn is_builtin(&self, ps: &mut PowerShellSession) -> bool {
+        let CommandInner::Cmdlet(name) = &self.command_inner else {
+            return false;
+        };
+        let name = name.to_ascii_lowercase();
+        Self::get(&name).is_some()
+            && ps.variables.get_function(&name).is_none()
+            && !ps.cmdlets.contains_key(&name)
+    }
+
+    pub(crate) fn is_invoke_expression(&self) -> bool {
+        matches!(
+            &self.command_inner,
+            CommandInner::Cmdlet(name) if ["iex", "invoke-expression"].contains(&name.to_ascii_lowercase().as_str())
+        )
+    }
+
     pub(crate) fn execute(&mut self, ps: &mut PowerShellSession) -> ParserResult<CommandOutput> {
-        let new_scope = matches!(self.scope, SessionScope::New);
+        let new_scope = matches!(self.scope, SessionScope::New) && !self.is_builtin(ps);
 
         if new_scope {
             ps.push_scope_session();
@@ -219,9 +355,29 @@ impl CommandElem {
     }
 }
 
+/// Command element passed to the cmdlet registered with
+/// `PowerShellSession::register_cmdlet`.
+///
+/// Parameter names are lowercase and keep the leading dash, e.g. `-uri`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CommandArg {
+    Parameter(String),
+    Argument(PsValue),
+}
+
+impl From<CommandElem> for CommandArg {
+    fn from(elem: CommandElem) -> Self {
+        match elem {
+            CommandElem::Parameter(s) => CommandArg::Parameter(s),
+            CommandElem::Argument(v) => CommandArg::Argument(v.into()),
+            CommandElem::ArgList(s) => CommandArg::Argument(PsValue::String(s)),
+        }
+    }
+}
+
 // Where-Object cmdlet implementation
 fn where_object(
-    args: &mut Vec<CommandElem>,
+    args: &mut [CommandElem],
     ps: &mut PowerShellSession,
 ) -> ParserResult<CommandOutput> {
     log::debug!("args: {:?}", args);
@@ -277,7 +433,7 @@ fn where_object(
 
 // Foreach-Object cmdlet implementation
 fn foreach_object(
-    args: &mut Vec<CommandElem>,
+    args: &mut [CommandElem],
     ps: &mut PowerShellSession,
 ) -> ParserResult<CommandOutput> {
     log::debug!("args: {:?}", args);
@@ -295,10 +451,11 @@ fn foreach_object(
         .into());
     };
 
-    let CommandElem::Argument(Val::ScriptBlock(sb)) = &args[1] else {
-        return Err(
-            CommandError::IncorrectArgs("Second argument must be a script block".into()).into(),
-        );
+    // % Name is a shortcut for % { $_.Name }
+    let sb = if let CommandElem::Argument(Val::ScriptBlock(sb)) = &args[1] {
+        sb
+    } else {
+        &ScriptBlock::from_command_elements(&args[1..])
     };
 
     let transformed_elements = if let Val::Array(elements) = argument {
@@ -331,7 +488,7 @@ fn foreach_object(
 }
 
 fn get_location(
-    _args: &mut Vec<CommandElem>,
+    _args: &mut [CommandElem],
     _: &mut PowerShellSession,
 ) -> ParserResult<CommandOutput> {
     let Ok(dir) = std::env::current_dir() else {
@@ -340,142 +497,442 @@ fn get_location(
         ))?;
     };
 
+    Ok(Val::String(dir.display().to_string().into()).into())
+}
+
+// Out-Null cmdlet implementation. Upstream pipeline is already evaluated, only
+// its output is discarded
+fn out_null(_args: &mut [CommandElem], _: &mut PowerShellSession) -> ParserResult<CommandOutput> {
     Ok(CommandOutput {
-        val: Val::String(dir.display().to_string().into()),
-        deobfuscated: Some(format!("Get-Location \"{}\"", dir.display())),
+        val: Val::NonDisplayed(Box::new(Val::Null)),
+        deobfuscated: None,
     })
 }
-// Helper function to extract message from command arguments
-fn extract_message(args: &[CommandElem]) -> String {
-    let mut output = Vec::new();
-    let mut skip = 0;
-    for i in args.iter() {
-        if skip > 0 {
-            skip -= 1;
+
+// Out-String cmdlet implementation. Converts the input to its display text,
+// which, like in PowerShell, ends with a new line
+fn out_string(args: &mut [CommandElem], _: &mut PowerShellSession) -> ParserResult<CommandOutput> {
+    let mut input = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg {
+            // width only wraps long lines of the formatted tables, so it's
+            // just checked
+            CommandElem::Parameter(p) if p == "-width" => {
+                let Some(CommandElem::Argument(val)) = iter.next() else {
+                    return Err(CommandError::IncorrectArgs("Out-String".into()).into());
+                };
+                val.cast_to_int()?;
+            }
+            CommandElem::Parameter(p) if p == "-inputobject" => {
+                let Some(CommandElem::Argument(val)) = iter.next() else {
+                    return Err(CommandError::IncorrectArgs("Out-String".into()).into());
+                };
+                input.push(val.clone());
+            }
+            CommandElem::Argument(val) => input.push(val.clone()),
+            _ => return Err(CommandError::IncorrectArgs("Out-String".into()).into()),
+        }
+    }
+
+    let mut text = input
+        .iter()
+        .map(Val::display)
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>()
+        .join(NEWLINE);
+    if !text.is_empty() {
+        text.push_str(NEWLINE);
+    }
+
+    Ok(Val::String(text.into()).into())
+}
+
+// Tee-Object cmdlet implementation. The input is passed down the pipeline and
+// its copy is stored in the variable. Files are not written, so -FilePath is
+// only recorded
+fn tee_object(args: &mut [CommandElem], ps: &mut PowerShellSession) -> ParserResult<CommandOutput> {
+    let mut input = None;
+    let mut variable = None;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg {
+            CommandElem::Parameter(p) if p == "-append" => {}
+            CommandElem::Parameter(p) => {
+                let Some(CommandElem::Argument(val)) = iter.next() else {
+                    return Err(CommandError::IncorrectArgs("Tee-Object".into()).into());
+                };
+                match p.as_str() {
+                    "-variable" => variable = Some(val.cast_to_string()),
+                    "-inputobject" => input = Some(val.clone()),
+                    "-filepath" | "-literalpath" => {}
+                    _ => return Err(CommandError::IncorrectArgs("Tee-Object".into()).into()),
+                }
+            }
+            CommandElem::Argument(val) if input.is_none() => input = Some(val.clone()),
+            _ => return Err(CommandError::IncorrectArgs("Tee-Object".into()).into()),
+        }
+    }
+
+    let input = input.unwrap_or_default();
+    if let Some(name) = variable {
+        ps.variables
+            .set_local(&name, input.clone())
+            .map_err(ParserError::from)?;
+        ps.add_deobfuscated_statement(format!(
+            "${} = {}",
+            name.to_ascii_lowercase(),
+            input.cast_to_script()
+        ));
+    }
+    Ok(input.into())
+}
+
+// Select-String cmdlet implementation. Every input object is a line, the
+// matching ones are returned as MatchInfo-like hashtables
+fn select_string(
+    args: &mut [CommandElem],
+    _: &mut PowerShellSession,
+) -> ParserResult<CommandOutput> {
+    let mut input = None;
+    let mut patterns = None;
+    let mut simple_match = false;
+    let mut case_sensitive = false;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg {
+            CommandElem::Parameter(p) => match p.as_str() {
+                "-simplematch" => simple_match = true,
+                "-casesensitive" => case_sensitive = true,
+                "-pattern" | "-inputobject" => {
+                    let Some(CommandElem::Argument(val)) = iter.next() else {
+                        return Err(CommandError::IncorrectArgs("Select-String".into()).into());
+                    };
+                    if p == "-pattern" {
+                        patterns = Some(val.clone());
+                    } else {
+                        input = Some(val.clone());
+                    }
+                }
+                _ => return Err(CommandError::IncorrectArgs("Select-String".into()).into()),
+            },
+            // piped value is always the first argument
+            CommandElem::Argument(val) if input.is_none() => input = Some(val.clone()),
+            CommandElem::Argument(val) if patterns.is_none() => patterns = Some(val.clone()),
+            _ => return Err(CommandError::IncorrectArgs("Select-String".into()).into()),
+        }
+    }
+
+    let Some(patterns) = patterns else {
+        return Err(CommandError::IncorrectArgs("Select-String".into()).into());
+    };
+    let patterns = match patterns {
+        Val::Array(arr) => arr,
+        val => vec![val],
+    };
+    let regexes = patterns
+        .iter()
+        .map(|pattern| {
+            let pattern = pattern.cast_to_string();
+            let pattern = if simple_match {
+                regex::escape(&pattern)
+            } else {
+                pattern
+            };
+            regex::RegexBuilder::new(&pattern)
+                .case_insensitive(!case_sensitive)
+                .build()
+                .map_err(|e| CommandError::ExecutionError(e.to_string()))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let lines = match input {
+        None | Some(Val::Null) => vec![],
+        Some(Val::Array(arr)) => arr,
+        Some(val) => vec![val],
+    };
+
+    let mut matches = vec![];
+    for (i, line) in lines.iter().enumerate() {
+        let line = line.cast_to_string();
+        // the first pattern that matches the line is reported
+        let Some(found) = regexes.iter().find_map(|re| re.find(&line)) else {
             continue;
+        };
+        let found = Val::Array(vec![Val::String(found.as_str().into())]);
+        matches.push(Val::HashTable(HashMap::from([
+            ("line".to_string(), Val::String(line.as_str().into())),
+            ("linenumber".to_string(), Val::Int(i as i64 + 1)),
+            ("matches".to_string(), found),
+        ])));
+    }
+
+    Ok(Val::Array(matches).into())
+}
+
+fn is_path_separator(c: char) -> bool {
+    c == '\\' || c == '/'
+}
+
+// Join-Path cmdlet implementation
+fn join_path(args: &mut [CommandElem], ps: &mut PowerShellSession) -> ParserResult<CommandOutput> {
+    let mut paths = vec![];
+    let mut child_paths = vec![];
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg {
+            CommandElem::Parameter(p) if p == "-path" => match iter.next() {
+                Some(CommandElem::Argument(val)) => paths.push(val.clone()),
+                _ => return Err(CommandError::IncorrectArgs("Join-Path".into()).into()),
+            },
+            CommandElem::Parameter(p) if p == "-childpath" || p == "-additionalchildpath" => {
+                match iter.next() {
+                    Some(CommandElem::Argument(val)) => child_paths.push(val.clone()),
+                    _ => return Err(CommandError::IncorrectArgs("Join-Path".into()).into()),
+                }
+            }
+            CommandElem::Argument(val) if paths.is_empty() => paths.push(val.clone()),
+            CommandElem::Argument(val) => child_paths.push(val.clone()),
+            _ => return Err(CommandError::IncorrectArgs("Join-Path".into()).into()),
         }
-        match i {
-            CommandElem::Parameter(s) => {
-                if s.to_ascii_lowercase().as_str() == "-foregroundcolor" {
-                    skip = 1
-                } else {
-                    output.push(s.clone());
+    }
+
+    // path can be an array, then every element is joined with child paths
+    let Some(path) = paths.pop() else {
+        return Err(CommandError::IncorrectArgs("Join-Path".into()).into());
+    };
+    let child_paths = child_paths
+        .into_iter()
+        .flat_map(|v| v.cast_to_array())
+        .map(|v| v.cast_to_string())
+        .collect::<Vec<_>>();
+
+    let separator = ps.path_separator;
+    let mut joined = path
+        .cast_to_array()
+        .into_iter()
+        .map(|parent| {
+            let mut res = parent.cast_to_string();
+            for child in &child_paths {
+                let child = child.trim_start_matches(is_path_separator);
+                if !res.ends_with(is_path_separator) {
+                    res.push(separator);
                 }
+                res.push_str(child);
             }
+            Val::String(
+                res.replace(is_path_separator, &separator.to_string())
+                    .into(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let val = if joined.len() == 1 {
+        joined.remove(0)
+    } else {
+        Val::Array(joined)
+    };
+
+    Ok(CommandOutput {
+        val,
+        deobfuscated: None,
+    })
+}
+
+// Split-Path cmdlet implementation
+fn split_path(args: &mut [CommandElem], _: &mut PowerShellSession) -> ParserResult<CommandOutput> {
+    #[derive(PartialEq)]
+    enum Part {
+        Parent,
+        Leaf,
+        Extension,
+        Qualifier,
+    }
+
+    let mut part = Part::Parent;
+    let mut path = None;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg {
+            CommandElem::Parameter(p) => match p.as_str() {
+                "-parent" => part = Part::Parent,
+                "-leaf" => part = Part::Leaf,
+                "-extension" => part = Part::Extension,
+                "-qualifier" => part = Part::Qualifier,
+                "-path" | "-literalpath" => match iter.next() {
+                    Some(CommandElem::Argument(val)) => path = Some(val.cast_to_string()),
+                    _ => return Err(CommandError::IncorrectArgs("Split-Path".into()).into()),
+                },
+                _ => return Err(CommandError::IncorrectArgs("Split-Path".into()).into()),
+            },
+            CommandElem::Argument(val) if path.is_none() => path = Some(val.cast_to_string()),
+            _ => return Err(CommandError::IncorrectArgs("Split-Path".into()).into()),
+        }
+    }
+
+    let Some(path) = path else {
+        return Err(CommandError::IncorrectArgs("Split-Path".into()).into());
+    };
+
+    let trimmed = path.trim_end_matches(is_path_separator);
+    let (parent, leaf) = match trimmed.rfind(is_path_separator) {
+        // keep separator of the root, e.g. "C:\"
+        Some(0) => (&trimmed[..1], &trimmed[1..]),
+        Some(pos) if trimmed[..pos].ends_with(':') => (&trimmed[..=pos], &trimmed[pos + 1..]),
+        Some(pos) => (&trimmed[..pos], &trimmed[pos + 1..]),
+        None => ("", trimmed),
+    };
+
+    let res = match part {
+        Part::Parent => parent.to_string(),
+        Part::Leaf => leaf.to_string(),
+        Part::Extension => leaf
+            .rfind('.')
+            .map(|pos| leaf[pos..].to_string())
+            .unwrap_or_default(),
+        Part::Qualifier => {
+            let Some(pos) = path.find(':') else {
+                return Err(CommandError::ExecutionError(format!(
+                    "Cannot parse path because path '{}' does not have a qualifier specified.",
+                    path
+                ))
+                .into());
+            };
+            path[..=pos].to_string()
+        }
+    };
+
+    Ok(CommandOutput {
+        val: Val::String(res.into()),
+        deobfuscated: None,
+    })
+}
+// Helper function to extract message from command arguments. Parameters which
+// take a value consume the following argument, so it isn't a part of the
+// message, while switch parameters stand alone
+fn extract_message(args: &[CommandElem]) -> String {
+    let mut output = Vec::new();
+    let mut separator = " ".to_string();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg {
+            CommandElem::Parameter(s) => match s.to_ascii_lowercase().as_str() {
+                "-object" | "-message" | "-inputobject" | "-messagedata" => {
+                    if let Some(CommandElem::Argument(val)) = iter.next() {
+                        output.push(val.display());
+                    }
+                }
+                "-separator" => {
+                    if let Some(CommandElem::Argument(val)) = iter.next() {
+                        separator = val.cast_to_string();
+                    }
+                }
+                "-foregroundcolor" | "-backgroundcolor" | "-category" | "-errorid"
+                | "-targetobject" | "-recommendedaction" | "-tags" | "-erroraction"
+                | "-warningaction" | "-informationaction" => {
+                    iter.next();
+                }
+                "-nonewline" | "-noenumerate" | "-verbose" | "-debug" => {}
+                _ => output.push(s.clone()),
+            },
             CommandElem::Argument(val) => {
                 output.push(val.display());
             }
             CommandElem::ArgList(_) => {}
         }
     }
-    output.join(" ")
+    output.join(&separator)
 }
 // Write-Host cmdlet implementation (goes directly to console, not capturable)
-fn write_host(
-    args: &mut Vec<CommandElem>,
-    ps: &mut PowerShellSession,
-) -> ParserResult<CommandOutput> {
+fn write_host(args: &mut [CommandElem], ps: &mut PowerShellSession) -> ParserResult<CommandOutput> {
     let message = extract_message(args);
-    let deobfuscated = format!(
-        "Write-Host {}",
-        args.iter()
-            .map(|p| p.display())
-            .collect::<Vec<_>>()
-            .join(" ")
-    );
-
-    ps.add_output_statement(StreamMessage::success(message));
+    ps.add_output_statement(StreamMessage::information(message));
     Ok(CommandOutput {
         val: Val::Null,
-        deobfuscated: Some(deobfuscated),
+        deobfuscated: None,
     })
 }
 // Write-Output cmdlet implementation
 fn write_output(
-    args: &mut Vec<CommandElem>,
+    args: &mut [CommandElem],
     _: &mut PowerShellSession,
 ) -> ParserResult<CommandOutput> {
     let message = extract_message(args);
-    let deobfuscated = format!(
-        "Write-Output {}",
-        args.iter()
-            .map(|p| p.display())
-            .collect::<Vec<_>>()
-            .join(" ")
-    );
-
     Ok(CommandOutput {
-        val: Val::String(message.clone().into()),
-        deobfuscated: Some(deobfuscated),
+        val: Val::String(message.into()),
+        deobfuscated: None,
     })
 }
 
 // Write-Warning cmdlet implementation (mimics PowerShell's Write-Warning)
 fn write_warning(
-    args: &mut Vec<CommandElem>,
-    _: &mut PowerShellSession,
+    args: &mut [CommandElem],
+    ps: &mut PowerShellSession,
 ) -> ParserResult<CommandOutput> {
     let message = extract_message(args);
-    let deobfuscated = format!(
-        "Write-Warning {}",
-        args.iter()
-            .map(|p| p.display())
-            .collect::<Vec<_>>()
-            .join(" ")
-    );
-
+    ps.add_output_statement(StreamMessage::warning(message));
     Ok(CommandOutput {
-        val: Val::String(message.clone().into()),
-        deobfuscated: Some(deobfuscated),
+        val: Val::Null,
+        deobfuscated: None,
     })
 }
 
 // Write-Error cmdlet implementation
 fn write_error(
-    args: &mut Vec<CommandElem>,
-    _: &mut PowerShellSession,
+    args: &mut [CommandElem],
+    ps: &mut PowerShellSession,
 ) -> ParserResult<CommandOutput> {
     let message = extract_message(args);
-    let deobfuscated = format!(
-        "Write-Error {}",
-        args.iter()
-            .map(|p| p.display())
-            .collect::<Vec<_>>()
-            .join(" ")
-    );
-
+    ps.add_output_statement(StreamMessage::error(message));
     Ok(CommandOutput {
-        val: Val::String(message.clone().into()),
-        deobfuscated: Some(deobfuscated),
+        val: Val::Null,
+        deobfuscated: None,
     })
 }
 
 // Write-Verbose cmdlet implementation
 fn write_verbose(
-    args: &mut Vec<CommandElem>,
-    _: &mut PowerShellSession,
+    args: &mut [CommandElem],
+    ps: &mut PowerShellSession,
 ) -> ParserResult<CommandOutput> {
     let message = extract_message(args);
-    let deobfuscated = format!(
-        "Write-Verbose {}",
-        args.iter()
-            .map(|p| p.display())
-            .collect::<Vec<_>>()
-            .join(" ")
-    );
+    ps.add_output_statement(StreamMessage::verbose(message));
     Ok(CommandOutput {
-        val: Val::String(message.clone().into()),
-        deobfuscated: Some(deobfuscated),
+        val: Val::Null,
+        deobfuscated: None,
     })
 }
 
-// Powershell cmdlet implementation. It don't actually invoke a new PowerShell
-// process, only deobfuscates the command.
-fn powershell(
-    args: &mut Vec<CommandElem>,
+// Write-Debug cmdlet implementation
+fn write_debug(
+    args: &mut [CommandElem],
     ps: &mut PowerShellSession,
 ) -> ParserResult<CommandOutput> {
-    fn deobfuscate_command(args: &mut Vec<CommandElem>, ps: &mut PowerShellSession) {
+    let message = extract_message(args);
+    ps.add_output_statement(StreamMessage::debug(message));
+    Ok(CommandOutput {
+        val: Val::Null,
+        deobfuscated: None,
+    })
+}
+
+// Write-Information cmdlet implementation
+fn write_information(
+    args: &mut [CommandElem],
+    ps: &mut PowerShellSession,
+) -> ParserResult<CommandOutput> {
+    let message = extract_message(args);
+    ps.add_output_statement(StreamMessage::information(message));
+    Ok(CommandOutput {
+        val: Val::Null,
+        deobfuscated: None,
+    })
+}
+
+// Powershell cmdlet implementation. It don't actually invoke a new PowerShell
+// process, only deobfuscates the command.
+fn powershell(args: &mut [CommandElem], ps: &mut PowerShellSession) -> ParserResult<CommandOutput> {
+    fn deobfuscate_command(args: &mut [CommandElem], ps: &mut PowerShellSession) {
         use base64::prelude::*;
         let mut index_to_decode = vec![];
         let mut args = args.iter_mut().map(Some).collect::<Vec<_>>();
@@ -587,6 +1044,161 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_out_null() {
+        let mut p = PowerShellSession::new();
+        let input = r#"Write-Output "x" | Out-Null"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(s.result(), PsValue::Null);
+        assert_eq!(s.output(), "");
+        assert_eq!(s.errors().len(), 0);
+
+        // upstream pipeline is evaluated
+        let input = r#"Write-Host "side effect" | Out-Null; "y""#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(s.result(), PsValue::String("y".to_string()));
+        assert_eq!(s.output(), ["side effect", "y"].join(NEWLINE));
+    }
+
+    #[test]
+    fn test_out_string() {
+        let mut p = PowerShellSession::new();
+        let s = p.parse_input(r#"1, "a" | Out-String -Width 80"#).unwrap();
+        assert_eq!(s.result(), PsValue::String(["1", "a", ""].join(NEWLINE)));
+
+        let input =
+            r#"$s = Get-Date -Date "2020-01-02" -Format "yyyy" | Out-String; $s.Trim() + "!""#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(s.result(), PsValue::String("2020!".to_string()));
+        assert_eq!(
+            s.deobfuscated(),
+            [format!("$s = \"2020{NEWLINE}\""), "\"2020!\"".into()].join(NEWLINE)
+        );
+
+        let s = p.parse_input("$null | Out-String").unwrap();
+        assert_eq!(s.result(), PsValue::String(String::new()));
+    }
+
+    #[test]
+    fn test_tee_object() {
+        let mut p = PowerShellSession::new();
+        let input = r#"$r = "ab", "cd" | Tee-Object -Variable parts | ForEach-Object { $_.ToUpper() }
+$parts -join "" ; $r -join """#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(s.output(), ["abcd", "ABCD"].join(NEWLINE));
+        assert_eq!(
+            s.deobfuscated(),
+            [
+                r#"$parts = @("ab","cd")"#,
+                r#"$r = @("AB","CD")"#,
+                r#""abcd""#,
+                r#""ABCD""#
+            ]
+            .join(NEWLINE)
+        );
+
+        let input = r#"7 | Tee-Object -FilePath "C:\out.txt" -Append"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(s.result(), PsValue::Int(7));
+        assert_eq!(s.errors().len(), 0);
+    }
+
+    #[test]
+    fn test_select_string() {
+        let mut p = PowerShellSession::new();
+        let input = r#"$log = "INFO start", "ERROR disk full", "info done", "Error: net"
+$m = $log | Select-String -Pattern "^error"
+$m.Count; $m[1].LineNumber; $m[0].Line; $m[1].Matches[0]"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(
+            s.output(),
+            ["2", "4", "ERROR disk full", "Error"].join(NEWLINE)
+        );
+
+        let input = r#"("INFO start", "ERROR disk" | Select-String "error" -CaseSensitive).Count"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(s.result(), PsValue::Int(0));
+
+        let input = r#"("a.c", "abc" | Select-String -SimpleMatch "a.c")[0].Line"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(s.result(), PsValue::String("a.c".to_string()));
+    }
+
+    #[test]
+    fn test_join_path() {
+        let mut p = PowerShellSession::new();
+        let input = r#"$dir = "C:\Windows\"; $p = Join-Path $dir "\System32"; $p"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(
+            s.result(),
+            PsValue::String(r#"C:\Windows\System32"#.to_string())
+        );
+        assert_eq!(
+            s.deobfuscated(),
+            [
+                r#"$dir = "C:\Windows\""#,
+                r#"$p = "C:\Windows\System32""#,
+                r#""C:\Windows\System32""#
+            ]
+            .join(NEWLINE)
+        );
+
+        let input = r#"Join-Path -Path C:/Users -ChildPath "a/b.txt""#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(
+            s.result(),
+            PsValue::String(r#"C:\Users\a\b.txt"#.to_string())
+        );
+
+        let input = r#"Join-Path ("a", "b") "c""#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(
+            s.result(),
+            PsValue::Array(vec![
+                PsValue::String(r#"a\c"#.to_string()),
+                PsValue::String(r#"b\c"#.to_string())
+            ])
+        );
+
+        let mut p = PowerShellSession::new().with_path_separator('/');
+        let input = r#"Join-Path "/usr" "bin" -AdditionalChildPath "ls""#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(s.result(), PsValue::String("/usr/bin/ls".to_string()));
+    }
+
+    #[test]
+    fn test_split_path() {
+        let mut p = PowerShellSession::new();
+        let path = r#""C:\Windows\System32\cmd.exe""#;
+        for (switch, expected) in [
+            ("", r#"C:\Windows\System32"#),
+            ("-Parent", r#"C:\Windows\System32"#),
+            ("-Leaf", "cmd.exe"),
+            ("-Extension", ".exe"),
+            ("-Qualifier", "C:"),
+        ] {
+            let s = p
+                .parse_input(&format!("Split-Path {path} {switch}"))
+                .unwrap();
+            assert_eq!(s.result(), PsValue::String(expected.to_string()));
+        }
+
+        let s = p.parse_input(r#"Split-Path -Path "C:\Windows\""#).unwrap();
+        assert_eq!(s.result(), PsValue::String(r#"C:\"#.to_string()));
+
+        let s = p
+            .parse_input(r#"Split-Path "/tmp/a.b/file" -Extension"#)
+            .unwrap();
+        assert_eq!(s.result(), PsValue::String("".to_string()));
+
+        let s = p.parse_input(r#"Split-Path "a/b" -Qualifier"#).unwrap();
+        assert_eq!(
+            s.errors()[0].to_string(),
+            "CommandError: Cannot parse path because path 'a/b' does not have a qualifier \
+             specified."
+        );
+    }
+
     #[test]
     fn test_write_output() {
         // assign not existing value, without forcing evaluation
@@ -647,6 +1259,21 @@ mod tests {
         );
     }
 
+    #[test]
+    fn positional_after_value_param() {
+        let mut p = PowerShellSession::new();
+        let input = r#"Write-Host -ForegroundColor Red "a" -BackgroundColor Blue -NoNewline "b"
+Write-Host -Separator "," "x" "y"
+Write-Output -InputObject "io""#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(s.output(), ["a b", "x,y", "io"].join(NEWLINE));
+
+        let input = r#"function f($Scope, [switch]$Force, $Name) { "$Scope|$Force|$Name" }
+f -Scope Process -Force x"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(s.result(), PsValue::String("Process|True|x".into()));
+    }
+
     #[test]
     fn double_quoted_string() {
         let mut p = PowerShellSession::new();
@@ -0,0 +1,173 @@
+use std::collections::HashMap;
+
+use super::{CommandElem, CommandError, CommandOutput, Val};
+use crate::{PowerShellSession, parser::ParserResult};
+
+// Compare-Object cmdlet implementation. Like in PowerShell, elements of the
+// difference object go first, each one consumes one equal element of the
+// reference object. Unmatched elements of the reference object are at the end
+pub(super) fn compare_object(
+    args: &mut [CommandElem],
+    _: &mut PowerShellSession,
+) -> ParserResult<CommandOutput> {
+    let mut reference = None;
+    let mut difference = None;
+    let mut include_equal = false;
+    let mut exclude_different = false;
+    let mut case_sensitive = false;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg {
+            CommandElem::Parameter(p) => match p.as_str() {
+                "-includeequal" => include_equal = true,
+                "-excludedifferent" => exclude_different = true,
+                "-casesensitive" => case_sensitive = true,
+                "-referenceobject" | "-differenceobject" => {
+                    let Some(CommandElem::Argument(val)) = iter.next() else {
+                        return Err(CommandError::IncorrectArgs("Compare-Object".into()).into());
+                    };
+                    if p == "-referenceobject" {
+                        reference = Some(val.clone());
+                    } else {
+                        difference = Some(val.clone());
+                    }
+                }
+                _ => return Err(CommandError::IncorrectArgs("Compare-Object".into()).into()),
+            },
+            CommandElem::Argument(val) if reference.is_none() => reference = Some(val.clone()),
+            CommandElem::Argument(val) if difference.is_none() => difference = Some(val.clone()),
+            _ => return Err(CommandError::IncorrectArgs("Compare-Object".into()).into()),
+        }
+    }
+
+    let (Some(reference), Some(difference)) = (reference, difference) else {
+        return Err(CommandError::IncorrectArgs("Compare-Object".into()).into());
+    };
+    let deobfuscated = summary(&reference, &difference, include_equal, exclude_different);
+
+    let mut unmatched = reference.cast_to_array();
+    let mut res = vec![];
+    for elem in difference.cast_to_array() {
+        let pos = unmatched
+            .iter()
+            .position(|r| r.eq(elem.clone(), !case_sensitive).unwrap_or_default());
+        match pos {
+            Some(pos) => {
+                unmatched.remove(pos);
+                if include_equal || exclude_different {
+                    res.push(side_indicator(elem, "=="));
+                }
+            }
+            None if !exclude_different => res.push(side_indicator(elem, "=>")),
+            None => {}
+        }
+    }
+    if !exclude_different {
+        res.extend(unmatched.into_iter().map(|elem| side_indicator(elem, "<=")));
+    }
+
+    let val = if res.is_empty() {
+        Val::Null
+    } else {
+        Val::Array(res)
+    };
+    Ok(CommandOutput {
+        val,
+        deobfuscated: Some(deobfuscated),
+    })
+}
+
+fn side_indicator(elem: Val, indicator: &str) -> Val {
+    Val::HashTable(HashMap::from([
+        ("inputobject".to_string(), elem),
+        ("sideindicator".to_string(), Val::String(indicator.into())),
+    ]))
+}
+
+fn summary(
+    reference: &Val,
+    difference: &Val,
+    include_equal: bool,
+    exclude_different: bool,
+) -> String {
+    let mut summary = format!(
+        "Compare-Object -ReferenceObject {} -DifferenceObject {}",
+        reference.cast_to_script(),
+        difference.cast_to_script()
+    );
+    if include_equal {
+        summary.push_str(" -IncludeEqual");
+    }
+    if exclude_different {
+        summary.push_str(" -ExcludeDifferent");
+    }
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PsValue;
+
+    fn side_indicator(elem: PsValue, indicator: &str) -> PsValue {
+        PsValue::HashTable(HashMap::from([
+            ("inputobject".to_string(), elem),
+            (
+                "sideindicator".to_string(),
+                PsValue::String(indicator.into()),
+            ),
+        ]))
+    }
+
+    #[test]
+    fn compare() {
+        let mut p = PowerShellSession::new();
+        let input = r#"$old = "a", "b", "c"; $new = "B", "c", "d"
+Compare-Object -ReferenceObject $old -DifferenceObject $new"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(
+            s.result(),
+            PsValue::Array(vec![
+                side_indicator(PsValue::String("d".into()), "=>"),
+                side_indicator(PsValue::String("a".into()), "<="),
+            ])
+        );
+
+        let input = r#"Compare-Object 1, 2, 2 2, 3 -IncludeEqual"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(
+            s.result(),
+            PsValue::Array(vec![
+                side_indicator(PsValue::Int(2), "=="),
+                side_indicator(PsValue::Int(3), "=>"),
+                side_indicator(PsValue::Int(1), "<="),
+                side_indicator(PsValue::Int(2), "<="),
+            ])
+        );
+
+        let s = p.parse_input(r#"Compare-Object (1, 2) (2, 1)"#).unwrap();
+        assert_eq!(s.result(), PsValue::Null);
+    }
+
+    #[test]
+    fn deobfuscated_summary() {
+        let mut p = PowerShellSession::new();
+        let input =
+            r#"$d = Compare-Object -ReferenceObject @(1,2) -DifferenceObject @(2,3) -IncludeEqual"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(
+            s.deobfuscated(),
+            r#"Compare-Object -ReferenceObject @(1,2) -DifferenceObject @(2,3) -IncludeEqual
+$d = @(@{
+	inputobject = 2
+	sideindicator = "=="
+},@{
+	inputobject = 3
+	sideindicator = "=>"
+},@{
+	inputobject = 1
+	sideindicator = "<="
+})"#
+        );
+    }
+}
@@ -0,0 +1,235 @@
+use super::{CommandElem, CommandError, CommandOutput, Val};
+use crate::{NEWLINE, PowerShellSession, parser::ParserResult};
+
+// Set-Content cmdlet implementation. Files live in the session, so scripts
+// can't touch the real filesystem
+pub(super) fn set_content(
+    args: &mut [CommandElem],
+    ps: &mut PowerShellSession,
+) -> ParserResult<CommandOutput> {
+    let (path, value) = content_args("Set-Content", args, ps.piped_cmdlet_arg)?;
+    ps.files
+        .insert(path.to_ascii_lowercase(), content_text(&value));
+    Ok(content_output("Set-Content", &path, &value))
+}
+
+// Add-Content cmdlet implementation. Like Set-Content, but the lines are
+// appended to the existing file
+pub(super) fn add_content(
+    args: &mut [CommandElem],
+    ps: &mut PowerShellSession,
+) -> ParserResult<CommandOutput> {
+    let (path, value) = content_args("Add-Content", args, ps.piped_cmdlet_arg)?;
+    ps.files
+        .entry(path.to_ascii_lowercase())
+        .or_default()
+        .push_str(&content_text(&value));
+    Ok(content_output("Add-Content", &path, &value))
+}
+
+// Get-Content cmdlet implementation. Returns the lines of the file, or with
+// -Raw the whole text as one string
+pub(super) fn get_content(
+    args: &mut [CommandElem],
+    ps: &mut PowerShellSession,
+) -> ParserResult<CommandOutput> {
+    let mut path = None;
+    let mut raw = false;
+    let mut total_count = None;
+    let mut tail = None;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg {
+            CommandElem::Parameter(p) if p == "-raw" => raw = true,
+            CommandElem::Parameter(p) => {
+                let Some(CommandElem::Argument(val)) = iter.next() else {
+                    return Err(CommandError::IncorrectArgs("Get-Content".into()).into());
+                };
+                match p.as_str() {
+                    "-path" | "-literalpath" => path = Some(val.cast_to_string()),
+                    "-totalcount" | "-head" | "-first" => total_count = Some(val.cast_to_int()?),
+                    "-tail" | "-last" => tail = Some(val.cast_to_int()?),
+                    "-encoding" => {}
+                    _ => return Err(CommandError::IncorrectArgs("Get-Content".into()).into()),
+                }
+            }
+            CommandElem::Argument(val) if path.is_none() => path = Some(val.cast_to_string()),
+            _ => return Err(CommandError::IncorrectArgs("Get-Content".into()).into()),
+        }
+    }
+
+    let Some(path) = path else {
+        return Err(CommandError::IncorrectArgs("Get-Content".into()).into());
+    };
+    let Some(content) = ps.files.get(&path.to_ascii_lowercase()) else {
+        return Err(CommandError::ExecutionError(format!(
+            "Cannot find path '{path}' because it does not exist."
+        ))
+        .into());
+    };
+    if raw {
+        return Ok(Val::String(content.clone().into()).into());
+    }
+
+    let mut lines = content
+        .lines()
+        .map(|line| Val::String(line.into()))
+        .collect::<Vec<_>>();
+    // negative -TotalCount reads all lines, like in PowerShell
+    if let Some(count) = total_count.and_then(|count| usize::try_from(count).ok()) {
+        lines.truncate(count);
+    }
+    if let Some(count) = tail.and_then(|count| usize::try_from(count).ok()) {
+        lines.drain(..lines.len().saturating_sub(count));
+    }
+    let val = match lines.len() {
+        0 => Val::Null,
+        1 => lines.remove(0),
+        _ => Val::Array(lines),
+    };
+    Ok(val.into())
+}
+
+// Parses the arguments of Set-Content and Add-Content, returns the path and the
+// value to write. Like in PowerShell, positional arguments are the path and
+// then the value, only the piped value goes before them
+fn content_args(name: &str, args: &[CommandElem], piped: bool) -> ParserResult<(String, Val)> {
+    let mut path = None;
+    let mut value = None;
+    let mut positional = Vec::new();
+    let mut iter = args.iter();
+    if piped && let Some(CommandElem::Argument(val)) = iter.next() {
+        value = Some(val.clone());
+    }
+    while let Some(arg) = iter.next() {
+        match arg {
+            CommandElem::Parameter(p) => {
+                let Some(CommandElem::Argument(val)) = iter.next() else {
+                    return Err(CommandError::IncorrectArgs(name.into()).into());
+                };
+                match p.as_str() {
+                    "-path" | "-literalpath" => path = Some(val.cast_to_string()),
+                    "-value" => value = Some(val.clone()),
+                    // the text is kept as is, so the encoding is only recorded
+                    "-encoding" => {}
+                    _ => return Err(CommandError::IncorrectArgs(name.into()).into()),
+                }
+            }
+            CommandElem::Argument(val) => positional.push(val.clone()),
+            _ => return Err(CommandError::IncorrectArgs(name.into()).into()),
+        }
+    }
+
+    let mut positional = positional.into_iter();
+    let path = path.or_else(|| positional.next().map(|val| val.cast_to_string()));
+    let value = value.or_else(|| positional.next());
+    let (Some(path), Some(value), None) = (path, value, positional.next()) else {
+        return Err(CommandError::IncorrectArgs(name.into()).into());
+    };
+    Ok((path, value))
+}
+
+// every value is written as a separate line
+fn content_text(value: &Val) -> String {
+    value
+        .cast_to_array()
+        .iter()
+        .map(|val| format!("{}{NEWLINE}", val.cast_to_string()))
+        .collect()
+}
+
+// the write is deobfuscated with the evaluated path and value
+fn content_output(name: &str, path: &str, value: &Val) -> CommandOutput {
+    CommandOutput {
+        val: Val::Null,
+        deobfuscated: Some(format!(
+            "{name} -Path {} -Value {}",
+            Val::String(path.into()).cast_to_script(),
+            value.cast_to_script()
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{NEWLINE, PowerShellSession, PsValue};
+
+    #[test]
+    fn add_content() {
+        let mut p = PowerShellSession::new();
+        let input = r#"Set-Content -Path "C:\log.txt" -Value "first" -Encoding UTF8
+"second" | Add-Content -Path "C:\log.txt"
+Get-Content C:\LOG.txt"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(
+            s.result(),
+            PsValue::Array(vec![
+                PsValue::String("first".into()),
+                PsValue::String("second".into())
+            ])
+        );
+
+        let input = r#"Set-Content -Path "C:\log.txt" -Value 1, 2; Add-Content "C:\log.txt" -Value 3
+(Get-Content "C:\log.txt") -join ",""#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(s.result(), PsValue::String("1,2,3".into()));
+
+        // positional path goes before the value
+        let input =
+            r#"Set-Content "C:\a.txt" "hello"; "x" | Add-Content C:\a.txt; Get-Content C:\a.txt"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(
+            s.result(),
+            PsValue::Array(vec![
+                PsValue::String("hello".into()),
+                PsValue::String("x".into())
+            ])
+        );
+
+        let s = p.parse_input(r#"Get-Content "C:\missing.txt""#).unwrap();
+        assert_eq!(
+            s.errors()[0].to_string(),
+            "CommandError: Cannot find path 'C:\\missing.txt' because it does not exist."
+        );
+    }
+
+    #[test]
+    fn get_content_lines() {
+        let text = ["one", "two", "three", "four"].join(NEWLINE);
+        let mut p = PowerShellSession::new().with_file("C:\\notes.txt", &text);
+
+        let s = p.parse_input(r#"Get-Content C:\notes.txt -Raw"#).unwrap();
+        assert_eq!(s.result(), PsValue::String(text.clone()));
+
+        let s = p
+            .parse_input(r#"(Get-Content C:\notes.txt -TotalCount 2) -join ",""#)
+            .unwrap();
+        assert_eq!(s.result(), PsValue::String("one,two".into()));
+
+        let s = p
+            .parse_input(r#"Get-Content -Path C:\notes.txt -Tail 1"#)
+            .unwrap();
+        assert_eq!(s.result(), PsValue::String("four".into()));
+
+        let s = p
+            .parse_input(r#"(Get-Content C:\notes.txt -Tail 10 -TotalCount 3) -join ",""#)
+            .unwrap();
+        assert_eq!(s.result(), PsValue::String("one,two,three".into()));
+    }
+
+    #[test]
+    fn deobfuscated_write() {
+        let mut p = PowerShellSession::new();
+        let input = r#"$v = "x"; Set-Content -Path a.txt -Value $v; Add-Content a.txt "y""#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(
+            s.deobfuscated(),
+            [
+                r#"$v = "x""#,
+                r#"Set-Content -Path "a.txt" -Value "x""#,
+                r#"Add-Content -Path "a.txt" -Value "y""#,
+            ]
+            .join(NEWLINE)
+        );
+    }
+}
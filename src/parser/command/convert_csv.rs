@@ -0,0 +1,238 @@
+use super::{CommandElem, CommandError, CommandOutput, Val, object_properties::properties};
+use crate::{
+    NEWLINE, PowerShellSession,
+    parser::{ParserResult, value::PsCustomObject},
+};
+
+const DEFAULT_DELIMITER: char = ',';
+
+// ConvertTo-Csv cmdlet implementation. The header is the union of the keys of
+// all input objects, in the order they appear. Fields are quoted as in
+// PowerShell 7, so the output is a valid RFC 4180 document
+pub(super) fn convert_to_csv(
+    args: &mut [CommandElem],
+    _: &mut PowerShellSession,
+) -> ParserResult<CommandOutput> {
+    let (input, delimiter) = csv_args("ConvertTo-Csv", args)?;
+    let rows = match input {
+        None | Some(Val::Null) => return Ok(Val::Null.into()),
+        Some(Val::Array(arr)) => arr,
+        Some(val) => vec![val],
+    };
+
+    let rows = rows.iter().map(properties).collect::<Vec<_>>();
+    let mut header: Vec<&str> = vec![];
+    for (key, _) in rows.iter().flatten() {
+        if !header.contains(&key.as_str()) {
+            header.push(key);
+        }
+    }
+
+    let separator = delimiter.to_string();
+    let mut lines = vec![
+        header
+            .iter()
+            .map(|key| quote(key))
+            .collect::<Vec<_>>()
+            .join(&separator),
+    ];
+    for row in rows.iter() {
+        let fields = header
+            .iter()
+            .map(|key| match row.iter().find(|(k, _)| k == key) {
+                // $null values are written as empty, unquoted fields
+                None | Some((_, Val::Null)) => String::new(),
+                Some((_, val)) => quote(&val.cast_to_string()),
+            })
+            .collect::<Vec<_>>();
+        lines.push(fields.join(&separator));
+    }
+
+    Ok(Val::String(lines.join(NEWLINE).into()).into())
+}
+
+// ConvertFrom-Csv cmdlet implementation. The first record is the header, every
+// next one becomes a hashtable keyed by the header
+pub(super) fn convert_from_csv(
+    args: &mut [CommandElem],
+    _: &mut PowerShellSession,
+) -> ParserResult<CommandOutput> {
+    let (input, delimiter) = csv_args("ConvertFrom-Csv", args)?;
+    let text = match input {
+        None | Some(Val::Null) => String::new(),
+        Some(Val::Array(lines)) => lines
+            .iter()
+            .map(|line| line.cast_to_string())
+            .collect::<Vec<_>>()
+            .join(NEWLINE),
+        Some(val) => val.cast_to_string(),
+    };
+
+    let mut records = parse_records(&text, delimiter).into_iter();
+    let header = records.next().unwrap_or_default();
+    let rows = records
+        .map(|record| {
+            let mut fields = record.into_iter();
+            // missing fields are $null, redundant ones are ignored
+            let row = header
+                .iter()
+                .map(|key| {
+                    let val = fields
+                        .next()
+                        .map_or(Val::Null, |field| Val::String(field.into()));
+                    (key.clone(), val)
+                })
+                .collect();
+            // pscustomobject keeps the order of the header
+            Val::RuntimeObject(Box::new(PsCustomObject::new(row)))
+        })
+        .collect();
+
+    Ok(Val::Array(rows).into())
+}
+
+fn csv_args(name: &str, args: &[CommandElem]) -> ParserResult<(Option<Val>, char)> {
+    let mut input = None;
+    let mut delimiter = DEFAULT_DELIMITER;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg {
+            CommandElem::Parameter(p) => match p.as_str() {
+                // type information is never written, so the switch is only
+                // recorded
+                "-notypeinformation" => {}
+                "-delimiter" | "-inputobject" => {
+                    let Some(CommandElem::Argument(val)) = iter.next() else {
+                        return Err(CommandError::IncorrectArgs(name.into()).into());
+                    };
+                    if p == "-delimiter" {
+                        let Some(c) = val.cast_to_string().chars().next() else {
+                            return Err(CommandError::IncorrectArgs(name.into()).into());
+                        };
+                        delimiter = c;
+                    } else {
+                        input = Some(val.clone());
+                    }
+                }
+                _ => return Err(CommandError::IncorrectArgs(name.into()).into()),
+            },
+            CommandElem::Argument(val) if input.is_none() => input = Some(val.clone()),
+            _ => return Err(CommandError::IncorrectArgs(name.into()).into()),
+        }
+    }
+    Ok((input, delimiter))
+}
+
+fn quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('"', "\"\""))
+}
+
+// splits the text into records and fields following RFC 4180. Quoted fields
+// may contain delimiters, line breaks and doubled quotes
+fn parse_records(text: &str, delimiter: char) -> Vec<Vec<String>> {
+    let mut records = vec![];
+    let mut record = vec![];
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            c if in_quotes => field.push(c),
+            c if c == delimiter => record.push(std::mem::take(&mut field)),
+            '\r' => {}
+            '\n' => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            c => field.push(c),
+        }
+    }
+    record.push(field);
+    records.push(record);
+
+    // empty lines are skipped
+    records.retain(|record| !matches!(record.as_slice(), [field] if field.is_empty()));
+    records
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::{NEWLINE, PowerShellSession, PsValue};
+
+    #[test]
+    fn convert_to_csv() {
+        let mut p = PowerShellSession::new();
+        let input = r#"@(
+    [pscustomobject]@{ Name = 'a "quoted", name'; Size = 1 },
+    [pscustomobject]@{ Name = "b"; Extra = $true }
+) | ConvertTo-Csv -NoTypeInformation"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(
+            s.result(),
+            PsValue::String(
+                [
+                    r#""Name","Size","Extra""#,
+                    r#""a ""quoted"", name","1","#,
+                    r#""b",,"True""#,
+                ]
+                .join(NEWLINE)
+            )
+        );
+
+        let input = r#"@{ b = 2; a = 1 } | ConvertTo-Csv -Delimiter ';'"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(
+            s.result(),
+            PsValue::String([r#""a";"b""#, r#""1";"2""#].join(NEWLINE))
+        );
+    }
+
+    #[test]
+    fn convert_from_csv() {
+        let mut p = PowerShellSession::new();
+        let input = r#"$lf = [string][char]10
+$csv = 'Name,Url' + $lf + '"evil, inc.","http://a/""b"""' + $lf + '"multi' + $lf + 'line"'
+$rows = ConvertFrom-Csv $csv
+$rows[0].Name + '|' + $rows[0].Url + '|' + $rows[1].Name"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(
+            s.result(),
+            PsValue::String("evil, inc.|http://a/\"b\"|multi\nline".into())
+        );
+
+        let input = r#"ConvertFrom-Csv "Name;Url", "a;b", "", "c" -Delimiter ';'"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(
+            s.result(),
+            PsValue::Array(vec![
+                PsValue::HashTable(HashMap::from([
+                    ("Name".into(), PsValue::String("a".into())),
+                    ("Url".into(), PsValue::String("b".into())),
+                ])),
+                PsValue::HashTable(HashMap::from([
+                    ("Name".into(), PsValue::String("c".into())),
+                    ("Url".into(), PsValue::Null),
+                ])),
+            ])
+        );
+
+        let input = r#"$rows = "x,y", "1,2" | ConvertFrom-Csv; $rows[0].y"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(s.result(), PsValue::String("2".into()));
+
+        // columns keep the order of the header
+        let input = r#""b,a", "1,2" | ConvertFrom-Csv | ConvertTo-Csv -NoTypeInformation"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(
+            s.result(),
+            PsValue::String([r#""b","a""#, r#""1","2""#].join(NEWLINE))
+        );
+    }
+}
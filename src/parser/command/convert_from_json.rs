@@ -0,0 +1,319 @@
+use std::collections::HashMap;
+
+use super::{CommandElem, CommandError, CommandOutput, Val};
+use crate::{NEWLINE, PowerShellSession, parser::ParserResult};
+
+// default -Depth of PowerShell 7
+pub(crate) const DEFAULT_DEPTH: usize = 1024;
+
+// ConvertFrom-Json cmdlet implementation. Objects are converted to hashtables,
+// as if -AsHashtable was always given
+pub(super) fn convert_from_json(
+    args: &mut [CommandElem],
+    _: &mut PowerShellSession,
+) -> ParserResult<CommandOutput> {
+    let mut input = None;
+    let mut depth = DEFAULT_DEPTH;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg {
+            CommandElem::Parameter(p) => match p.as_str() {
+                "-ashashtable" => {}
+                "-depth" | "-inputobject" => {
+                    let Some(CommandElem::Argument(val)) = iter.next() else {
+                        return Err(CommandError::IncorrectArgs("ConvertFrom-Json".into()).into());
+                    };
+                    if p == "-depth" {
+                        depth = usize::try_from(val.cast_to_int()?)
+                            .map_err(|_| CommandError::IncorrectArgs("ConvertFrom-Json".into()))?;
+                    } else {
+                        input = Some(val.clone());
+                    }
+                }
+                _ => return Err(CommandError::IncorrectArgs("ConvertFrom-Json".into()).into()),
+            },
+            CommandElem::Argument(val) if input.is_none() => input = Some(val.clone()),
+            _ => return Err(CommandError::IncorrectArgs("ConvertFrom-Json".into()).into()),
+        }
+    }
+
+    // lines piped from Get-Content make up one document
+    let text = match input {
+        None | Some(Val::Null) => String::new(),
+        Some(Val::Array(lines)) => lines
+            .iter()
+            .map(|line| line.cast_to_string())
+            .collect::<Vec<_>>()
+            .join(NEWLINE),
+        Some(val) => val.cast_to_string(),
+    };
+    if text.trim().is_empty() {
+        return Ok(Val::Null.into());
+    }
+
+    Ok(parse_json(&text, depth)?.into())
+}
+
+// Parses the JSON document. Objects become hashtables with lowercased keys,
+// like the hashtable literals. Integers which don't fit i64 become doubles
+pub(crate) fn parse_json(text: &str, depth: usize) -> Result<Val, CommandError> {
+    let mut parser = JsonParser {
+        text,
+        pos: 0,
+        depth,
+    };
+    let val = parser.value(0)?;
+    parser.skip_whitespace();
+    if parser.peek().is_some() {
+        return Err(parser.error("Unexpected character"));
+    }
+    Ok(val)
+}
+
+struct JsonParser<'a> {
+    text: &'a str,
+    pos: usize,
+    depth: usize,
+}
+
+impl JsonParser<'_> {
+    fn peek(&self) -> Option<char> {
+        self.text[self.pos..].chars().next()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.next();
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), CommandError> {
+        self.skip_whitespace();
+        match self.next() {
+            Some(c) if c == expected => Ok(()),
+            _ => Err(self.error(&format!("Expected '{expected}'"))),
+        }
+    }
+
+    fn error(&self, msg: &str) -> CommandError {
+        CommandError::ExecutionError(format!(
+            "Conversion from JSON failed. {msg} at position {}",
+            self.pos
+        ))
+    }
+
+    fn value(&mut self, level: usize) -> Result<Val, CommandError> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('{' | '[') if level >= self.depth => Err(self.error(&format!(
+                "The maximum depth allowed for deserialization is {}",
+                self.depth
+            ))),
+            Some('{') => self.object(level),
+            Some('[') => self.array(level),
+            Some('"') => Ok(Val::String(self.string()?.into())),
+            Some('-' | '0'..='9') => self.number(),
+            Some(_) => {
+                for (literal, val) in [
+                    ("true", Val::Bool(true)),
+                    ("false", Val::Bool(false)),
+                    ("null", Val::Null),
+                ] {
+                    if self.text[self.pos..].starts_with(literal) {
+                        self.pos += literal.len();
+                        return Ok(val);
+                    }
+                }
+                Err(self.error("Invalid JSON primitive"))
+            }
+            None => Err(self.error("Unexpected end of input")),
+        }
+    }
+
+    fn object(&mut self, level: usize) -> Result<Val, CommandError> {
+        self.expect('{')?;
+        let mut map = HashMap::new();
+        self.skip_whitespace();
+        if self.peek() == Some('}') {
+            self.next();
+            return Ok(Val::HashTable(map));
+        }
+        loop {
+            self.skip_whitespace();
+            let key = self.string()?;
+            self.expect(':')?;
+            map.insert(key.to_ascii_lowercase(), self.value(level + 1)?);
+            self.skip_whitespace();
+            match self.next() {
+                Some(',') => {}
+                Some('}') => return Ok(Val::HashTable(map)),
+                _ => return Err(self.error("Expected ',' or '}'")),
+            }
+        }
+    }
+
+    fn array(&mut self, level: usize) -> Result<Val, CommandError> {
+        self.expect('[')?;
+        let mut items = vec![];
+        self.skip_whitespace();
+        if self.peek() == Some(']') {
+            self.next();
+            return Ok(Val::Array(items));
+        }
+        loop {
+            items.push(self.value(level + 1)?);
+            self.skip_whitespace();
+            match self.next() {
+                Some(',') => {}
+                Some(']') => return Ok(Val::Array(items)),
+                _ => return Err(self.error("Expected ',' or ']'")),
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String, CommandError> {
+        if self.next() != Some('"') {
+            return Err(self.error("Expected string"));
+        }
+        let mut s = String::new();
+        loop {
+            match self.next() {
+                Some('"') => return Ok(s),
+                Some('\\') => {
+                    let c = match self.next() {
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('u') => self.unicode_escape()?,
+                        Some(c @ ('"' | '\\' | '/')) => c,
+                        _ => return Err(self.error("Invalid escape sequence")),
+                    };
+                    s.push(c);
+                }
+                Some(c) => s.push(c),
+                None => return Err(self.error("Unterminated string")),
+            }
+        }
+    }
+
+    // \uXXXX, where characters outside of the BMP are escaped as a surrogate
+    // pair. Unpaired surrogates are replaced
+    fn unicode_escape(&mut self) -> Result<char, CommandError> {
+        let high = self.hex4()?;
+        if !(0xD800..0xDC00).contains(&high) {
+            return Ok(char::from_u32(high).unwrap_or(char::REPLACEMENT_CHARACTER));
+        }
+        if !self.text[self.pos..].starts_with("\\u") {
+            return Ok(char::REPLACEMENT_CHARACTER);
+        }
+        self.pos += 2;
+        let low = self.hex4()?;
+        if !(0xDC00..0xE000).contains(&low) {
+            return Ok(char::REPLACEMENT_CHARACTER);
+        }
+        let code = 0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00);
+        Ok(char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER))
+    }
+
+    fn hex4(&mut self) -> Result<u32, CommandError> {
+        let digits = self.text[self.pos..]
+            .get(..4)
+            .filter(|digits| digits.chars().all(|c| c.is_ascii_hexdigit()))
+            .ok_or_else(|| self.error("Invalid unicode escape"))?;
+        self.pos += 4;
+        Ok(u32::from_str_radix(digits, 16).unwrap_or_default())
+    }
+
+    fn number(&mut self) -> Result<Val, CommandError> {
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|c| c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E'))
+        {
+            self.next();
+        }
+        let literal = &self.text[start..self.pos];
+        if let Ok(i) = literal.parse::<i64>() {
+            return Ok(Val::Int(i));
+        }
+        literal
+            .parse::<f64>()
+            .map(Val::Float)
+            .map_err(|_| self.error("Invalid number"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::{NEWLINE, PowerShellSession, PsValue};
+
+    #[test]
+    fn convert_from_json() {
+        let mut p = PowerShellSession::new();
+        let input = r#"$o = '{"Name": "a\"bé😀", "Ids": [1, 2.5, -3e2], "Nested": {"On": true, "Off": null}}' | ConvertFrom-Json
+$o.name
+$o.ids -join ","
+$o.Nested.On
+$null -eq $o.nested.off
+$o.GetType().Name"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(
+            s.output(),
+            ["a\"bé😀", "1,2.5,-300", "True", "True", "Hashtable"].join(NEWLINE)
+        );
+
+        let s = p.parse_input(r#"ConvertFrom-Json '[]'"#).unwrap();
+        assert_eq!(s.result(), PsValue::Array(vec![]));
+        let s = p
+            .parse_input(r#"ConvertFrom-Json -InputObject '{}' -AsHashtable"#)
+            .unwrap();
+        assert_eq!(s.result(), PsValue::HashTable(HashMap::new()));
+        let s = p.parse_input(r#"ConvertFrom-Json ' '"#).unwrap();
+        assert_eq!(s.result(), PsValue::Null);
+
+        // escaped characters, including a surrogate pair
+        let s = p
+            .parse_input(r#"ConvertFrom-Json '"\u0041\t\/\ud83d\ude00"'"#)
+            .unwrap();
+        assert_eq!(s.result(), PsValue::String("A\t/😀".into()));
+    }
+
+    #[test]
+    fn round_trip_and_errors() {
+        let mut p = PowerShellSession::new();
+        let input = r#"$json = @{ a = 1, "x"; b = @{ c = $false } } | ConvertTo-Json -Compress
+($json | ConvertFrom-Json | ConvertTo-Json -Compress) -eq $json"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(s.result(), PsValue::Bool(true));
+
+        // the lines of a file make up one document
+        let s = p
+            .parse_input(r#"('[', '1,', '2', ']' | ConvertFrom-Json).Count"#)
+            .unwrap();
+        assert_eq!(s.result(), PsValue::Int(2));
+
+        let s = p.parse_input(r#"ConvertFrom-Json '{"a": 1,}'"#).unwrap();
+        assert_eq!(
+            s.errors()[0].to_string(),
+            "CommandError: Conversion from JSON failed. Expected string at position 9"
+        );
+        let s = p
+            .parse_input(r#"ConvertFrom-Json '[[1]]' -Depth 1"#)
+            .unwrap();
+        assert_eq!(
+            s.errors()[0].to_string(),
+            "CommandError: Conversion from JSON failed. The maximum depth allowed for \
+             deserialization is 1 at position 1"
+        );
+    }
+}
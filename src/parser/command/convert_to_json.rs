@@ -0,0 +1,206 @@
+use std::collections::BTreeMap;
+
+use super::{CommandElem, CommandError, CommandOutput, Val};
+use crate::{NEWLINE, PowerShellSession, parser::ParserResult};
+
+const DEFAULT_DEPTH: i64 = 2;
+const INDENT: &str = "  ";
+
+// ConvertTo-Json cmdlet implementation. Output mimics PowerShell 7: two space
+// indentation, or no whitespace at all with -Compress. Objects nested deeper
+// than -Depth are converted to strings
+pub(super) fn convert_to_json(
+    args: &mut [CommandElem],
+    _: &mut PowerShellSession,
+) -> ParserResult<CommandOutput> {
+    let mut input = None;
+    let mut depth = DEFAULT_DEPTH;
+    let mut compress = false;
+    let mut as_array = false;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg {
+            CommandElem::Parameter(p) => match p.as_str() {
+                "-compress" => compress = true,
+                "-asarray" => as_array = true,
+                "-depth" | "-inputobject" => {
+                    let Some(CommandElem::Argument(val)) = iter.next() else {
+                        return Err(CommandError::IncorrectArgs("ConvertTo-Json".into()).into());
+                    };
+                    if p == "-depth" {
+                        depth = val.cast_to_int()?;
+                    } else {
+                        input = Some(val.clone());
+                    }
+                }
+                _ => return Err(CommandError::IncorrectArgs("ConvertTo-Json".into()).into()),
+            },
+            CommandElem::Argument(val) if input.is_none() => input = Some(val.clone()),
+            _ => return Err(CommandError::IncorrectArgs("ConvertTo-Json".into()).into()),
+        }
+    }
+
+    let Some(input) = input else {
+        return Ok(Val::Null.into());
+    };
+    let input = match input {
+        Val::Array(_) => input,
+        val if as_array => Val::Array(vec![val]),
+        val => val,
+    };
+
+    let writer = JsonWriter { depth, compress };
+    Ok(Val::String(writer.write(&input, 0).into()).into())
+}
+
+struct JsonWriter {
+    depth: i64,
+    compress: bool,
+}
+
+impl JsonWriter {
+    fn write(&self, val: &Val, level: usize) -> String {
+        match val {
+            Val::Null => "null".to_string(),
+            Val::Bool(b) => b.to_string(),
+            Val::Int(i) => i.to_string(),
+            Val::Float(f) if f.is_finite() && f.fract() == 0.0 => format!("{f:.1}"),
+            Val::Float(f) if f.is_finite() => f.to_string(),
+            Val::NonDisplayed(inner) => self.write(inner, level),
+            // too deep objects are replaced by their string form
+            Val::Array(_) | Val::HashTable(_) | Val::RuntimeObject(_)
+                if level as i64 > self.depth =>
+            {
+                quote(&val.cast_to_string())
+            }
+            Val::Array(arr) => {
+                let items = arr.iter().map(|v| self.write(v, level + 1)).collect();
+                self.container('[', items, ']', level)
+            }
+            Val::HashTable(h) => {
+                // hashtable is unordered, so sort the keys to get stable output
+                let entries = BTreeMap::from_iter(h.iter())
+                    .into_iter()
+                    .map(|(k, v)| self.entry(k, v, level))
+                    .collect();
+                self.container('{', entries, '}', level)
+            }
+            Val::RuntimeObject(rt) => match rt.properties() {
+                Some(properties) => {
+                    let entries = properties
+                        .iter()
+                        .map(|(k, v)| self.entry(k, v, level))
+                        .collect();
+                    self.container('{', entries, '}', level)
+                }
+                None => quote(&val.cast_to_string()),
+            },
+            _ => quote(&val.cast_to_string()),
+        }
+    }
+
+    fn entry(&self, key: &str, val: &Val, level: usize) -> String {
+        let separator = if self.compress { ":" } else { ": " };
+        format!("{}{separator}{}", quote(key), self.write(val, level + 1))
+    }
+
+    fn container(&self, open: char, items: Vec<String>, close: char, level: usize) -> String {
+        if items.is_empty() {
+            return format!("{open}{close}");
+        }
+        if self.compress {
+            return format!("{open}{}{close}", items.join(","));
+        }
+
+        let inner_indent = INDENT.repeat(level + 1);
+        let items = items
+            .iter()
+            .map(|item| format!("{inner_indent}{item}"))
+            .collect::<Vec<_>>()
+            .join(&format!(",{NEWLINE}"));
+        format!(
+            "{open}{NEWLINE}{items}{NEWLINE}{}{close}",
+            INDENT.repeat(level)
+        )
+    }
+}
+
+fn quote(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len() + 2);
+    escaped.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            '\u{8}' => escaped.push_str("\\b"),
+            '\u{c}' => escaped.push_str("\\f"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{NEWLINE, PowerShellSession, PsValue};
+
+    #[test]
+    fn compress_and_pretty() {
+        let mut p = PowerShellSession::new();
+        let input = r#"$o = [pscustomobject]@{ Name = "a`"b"; Ids = 1, 2; Empty = @() }
+$o | ConvertTo-Json -Compress"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(
+            s.result(),
+            PsValue::String(r#"{"Name":"a\"b","Ids":[1,2],"Empty":[]}"#.into())
+        );
+
+        let input = r#"[pscustomobject]@{ Name = "a"; Ids = 1, 2 } | ConvertTo-Json"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(
+            s.result(),
+            PsValue::String(
+                [
+                    "{",
+                    r#"  "Name": "a","#,
+                    r#"  "Ids": ["#,
+                    "    1,",
+                    "    2",
+                    "  ]",
+                    "}"
+                ]
+                .join(NEWLINE)
+            )
+        );
+    }
+
+    #[test]
+    fn as_array_and_depth() {
+        let mut p = PowerShellSession::new();
+        let s = p
+            .parse_input(r#"ConvertTo-Json 1 -AsArray -Compress"#)
+            .unwrap();
+        assert_eq!(s.result(), PsValue::String("[1]".into()));
+
+        let s = p.parse_input(r#"ConvertTo-Json "x" -AsArray"#).unwrap();
+        assert_eq!(
+            s.result(),
+            PsValue::String(["[", r#"  "x""#, "]"].join(NEWLINE))
+        );
+
+        let s = p.parse_input(r#"ConvertTo-Json 1.5"#).unwrap();
+        assert_eq!(s.result(), PsValue::String("1.5".into()));
+
+        let input = r#"@{ a = @{ b = @{ c = 1 } } } | ConvertTo-Json -Depth 1 -Compress"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(
+            s.result(),
+            PsValue::String(r#"{"a":{"b":"System.Collections.Hashtable"}}"#.into())
+        );
+    }
+}
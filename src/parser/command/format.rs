@@ -0,0 +1,225 @@
+use super::{CommandElem, CommandError, CommandOutput, Val, object_properties::properties};
+use crate::{NEWLINE, PowerShellSession, parser::ParserResult};
+
+// Format-Table cmdlet implementation. Every input object is a row, its
+// properties are the columns. Like in PowerShell, columns are as wide as their
+// longest value and numbers are aligned to the right
+pub(super) fn format_table(
+    args: &mut [CommandElem],
+    _: &mut PowerShellSession,
+) -> ParserResult<CommandOutput> {
+    let Some(table) = Table::from_args("Format-Table", args)? else {
+        return Ok(Val::Null.into());
+    };
+
+    let widths = table
+        .columns
+        .iter()
+        .enumerate()
+        .map(|(i, column)| {
+            table
+                .rows
+                .iter()
+                .map(|row| row[i].as_ref().map_or(0, |val| val.cast_to_string().len()))
+                .fold(column.len(), usize::max)
+        })
+        .collect::<Vec<_>>();
+    let numeric = (0..table.columns.len())
+        .map(|i| {
+            table
+                .rows
+                .iter()
+                .filter_map(|row| row[i].as_ref())
+                .all(|val| matches!(val, Val::Int(_) | Val::Float(_)))
+        })
+        .collect::<Vec<_>>();
+
+    let line = |cells: Vec<String>| {
+        let cells = cells
+            .iter()
+            .enumerate()
+            .map(|(i, cell)| {
+                let width = widths[i];
+                if numeric[i] {
+                    format!("{cell:>width$}")
+                } else {
+                    format!("{cell:<width$}")
+                }
+            })
+            .collect::<Vec<_>>();
+        cells.join(" ").trim_end().to_string()
+    };
+
+    let mut lines = vec![
+        line(table.columns.clone()),
+        line(table.columns.iter().map(|c| "-".repeat(c.len())).collect()),
+    ];
+    for row in table.rows.iter() {
+        let cells = row
+            .iter()
+            .map(|val| val.as_ref().map(Val::cast_to_string).unwrap_or_default())
+            .collect();
+        lines.push(line(cells));
+    }
+    Ok(Val::String(lines.join(NEWLINE).into()).into())
+}
+
+// Format-List cmdlet implementation. Every input object is a block of
+// "Name : Value" lines, blocks are separated by an empty line
+pub(super) fn format_list(
+    args: &mut [CommandElem],
+    _: &mut PowerShellSession,
+) -> ParserResult<CommandOutput> {
+    let Some(table) = Table::from_args("Format-List", args)? else {
+        return Ok(Val::Null.into());
+    };
+
+    let width = table
+        .columns
+        .iter()
+        .map(String::len)
+        .max()
+        .unwrap_or_default();
+    let blocks = table
+        .rows
+        .iter()
+        .map(|row| {
+            table
+                .columns
+                .iter()
+                .zip(row.iter())
+                .map(|(column, val)| {
+                    let val = val.as_ref().map(Val::cast_to_string).unwrap_or_default();
+                    format!("{column:<width$} : {val}").trim_end().to_string()
+                })
+                .collect::<Vec<_>>()
+                .join(NEWLINE)
+        })
+        .collect::<Vec<_>>();
+    Ok(Val::String(blocks.join(&NEWLINE.repeat(2)).into()).into())
+}
+
+struct Table {
+    columns: Vec<String>,
+    // values of the columns, None if the object doesn't have the property
+    rows: Vec<Vec<Option<Val>>>,
+}
+
+impl Table {
+    fn from_args(cmdlet: &str, args: &[CommandElem]) -> ParserResult<Option<Self>> {
+        let mut input = None;
+        let mut selected = None;
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            match arg {
+                // columns are always as wide as needed
+                CommandElem::Parameter(p) if p == "-autosize" || p == "-wrap" => {}
+                CommandElem::Parameter(p) if p == "-property" || p == "-inputobject" => {
+                    let Some(CommandElem::Argument(val)) = iter.next() else {
+                        return Err(CommandError::IncorrectArgs(cmdlet.into()).into());
+                    };
+                    if p == "-property" {
+                        selected = Some(val.clone());
+                    } else {
+                        input = Some(val.clone());
+                    }
+                }
+                CommandElem::Argument(val) if input.is_none() => input = Some(val.clone()),
+                CommandElem::Argument(val) if selected.is_none() => selected = Some(val.clone()),
+                _ => return Err(CommandError::IncorrectArgs(cmdlet.into()).into()),
+            }
+        }
+
+        let objects = match input {
+            None | Some(Val::Null) => return Ok(None),
+            Some(Val::Array(arr)) => arr,
+            Some(val) => vec![val],
+        };
+        let objects = objects.iter().map(properties).collect::<Vec<_>>();
+
+        // selected columns are in the given order, otherwise all properties in
+        // the order they appear
+        let columns = match selected {
+            Some(selected) => selected
+                .cast_to_array()
+                .iter()
+                .map(Val::cast_to_string)
+                .collect::<Vec<_>>(),
+            None => {
+                let mut columns: Vec<String> = vec![];
+                for (key, _) in objects.iter().flatten() {
+                    if !columns.iter().any(|c| c.eq_ignore_ascii_case(key)) {
+                        columns.push(key.clone());
+                    }
+                }
+                columns
+            }
+        };
+
+        let rows = objects
+            .iter()
+            .map(|object| {
+                columns
+                    .iter()
+                    .map(|column| {
+                        object
+                            .iter()
+                            .find(|(key, _)| key.eq_ignore_ascii_case(column))
+                            .map(|(_, val)| val.clone())
+                    })
+                    .collect()
+            })
+            .collect();
+        Ok(Some(Self { columns, rows }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{NEWLINE, PowerShellSession, PsValue};
+
+    #[test]
+    fn format_table() {
+        let mut p = PowerShellSession::new();
+        let input = r#"$files = [pscustomobject]@{ Name = "a.txt"; Size = 1 },
+    [pscustomobject]@{ Name = "long_name.ps1"; Size = 1024; Hidden = $true }
+$files | Format-Table"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(
+            s.result(),
+            PsValue::String(
+                [
+                    "Name          Size Hidden",
+                    "----          ---- ------",
+                    "a.txt            1",
+                    "long_name.ps1 1024 True",
+                ]
+                .join(NEWLINE)
+            )
+        );
+
+        let input = r#"@{ name = "x"; id = 7 }, @{ name = "yy"; id = 10 } | Format-Table -Property Id, Name"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(
+            s.output(),
+            ["Id Name", "-- ----", " 7 x", "10 yy"].join(NEWLINE)
+        );
+    }
+
+    #[test]
+    fn format_list() {
+        let mut p = PowerShellSession::new();
+        let input = r#"[pscustomobject]@{ Name = "a"; LongKey = 1 }, [pscustomobject]@{ Name = "b" } | Format-List"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(
+            s.result(),
+            PsValue::String(
+                ["Name    : a", "LongKey : 1", "", "Name    : b", "LongKey :"].join(NEWLINE)
+            )
+        );
+
+        let input = r#"Format-List -InputObject @{ a = 1; b = 2 } -Property b"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(s.result(), PsValue::String("b : 2".into()));
+    }
+}
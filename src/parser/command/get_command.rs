@@ -0,0 +1,263 @@
+use super::{CommandElem, CommandError, CommandOutput, Val};
+use crate::{
+    PowerShellSession,
+    parser::{
+        ParserResult,
+        predicates::ComparisonPred,
+        value::{CommandInfo, CommandType},
+    },
+};
+
+// cmdlets implemented by the parser, with the modules they come from
+const CMDLETS: [(&str, &str); 39] = [
+    ("Add-Content", "Microsoft.PowerShell.Management"),
+    ("Compare-Object", "Microsoft.PowerShell.Utility"),
+    ("ConvertFrom-Csv", "Microsoft.PowerShell.Utility"),
+    ("ConvertFrom-Json", "Microsoft.PowerShell.Utility"),
+    ("ConvertTo-Csv", "Microsoft.PowerShell.Utility"),
+    ("ConvertTo-Json", "Microsoft.PowerShell.Utility"),
+    ("ForEach-Object", "Microsoft.PowerShell.Core"),
+    ("Format-List", "Microsoft.PowerShell.Utility"),
+    ("Format-Table", "Microsoft.PowerShell.Utility"),
+    ("Get-Command", "Microsoft.PowerShell.Core"),
+    ("Get-Content", "Microsoft.PowerShell.Management"),
+    ("Get-Date", "Microsoft.PowerShell.Utility"),
+    ("Get-Location", "Microsoft.PowerShell.Management"),
+    ("Get-Member", "Microsoft.PowerShell.Utility"),
+    ("Get-Random", "Microsoft.PowerShell.Utility"),
+    ("Get-Variable", "Microsoft.PowerShell.Utility"),
+    ("Invoke-Expression", "Microsoft.PowerShell.Utility"),
+    ("Join-Path", "Microsoft.PowerShell.Management"),
+    ("Join-String", "Microsoft.PowerShell.Utility"),
+    ("Measure-Object", "Microsoft.PowerShell.Utility"),
+    ("Merge-HashTable", ""),
+    ("New-Object", "Microsoft.PowerShell.Utility"),
+    ("Out-Null", "Microsoft.PowerShell.Core"),
+    ("Out-String", "Microsoft.PowerShell.Utility"),
+    ("Remove-Variable", "Microsoft.PowerShell.Utility"),
+    ("Select-String", "Microsoft.PowerShell.Utility"),
+    ("Set-Content", "Microsoft.PowerShell.Management"),
+    ("Set-Variable", "Microsoft.PowerShell.Utility"),
+    ("Sort-Object", "Microsoft.PowerShell.Utility"),
+    ("Split-Path", "Microsoft.PowerShell.Management"),
+    ("Tee-Object", "Microsoft.PowerShell.Utility"),
+    ("Where-Object", "Microsoft.PowerShell.Core"),
+    ("Write-Debug", "Microsoft.PowerShell.Utility"),
+    ("Write-Error", "Microsoft.PowerShell.Utility"),
+    ("Write-Host", "Microsoft.PowerShell.Utility"),
+    ("Write-Information", "Microsoft.PowerShell.Utility"),
+    ("Write-Output", "Microsoft.PowerShell.Utility"),
+    ("Write-Verbose", "Microsoft.PowerShell.Utility"),
+    ("Write-Warning", "Microsoft.PowerShell.Utility"),
+];
+
+const ALIASES: [(&str, &str); 8] = [
+    ("%", "ForEach-Object"),
+    ("?", "Where-Object"),
+    ("foreach", "ForEach-Object"),
+    ("gcm", "Get-Command"),
+    ("gm", "Get-Member"),
+    ("iex", "Invoke-Expression"),
+    ("measure", "Measure-Object"),
+    ("where", "Where-Object"),
+];
+
+// Get-Command cmdlet implementation. Lists the modeled aliases, functions of
+// the script and cmdlets, including the ones registered in the session. The
+// returned command references can be invoked, e.g. & (Get-Command iex)
+pub(super) fn get_command(
+    args: &mut [CommandElem],
+    ps: &mut PowerShellSession,
+) -> ParserResult<CommandOutput> {
+    let mut names = vec![];
+    let mut command_types = vec![];
+    let mut modules = vec![];
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg {
+            CommandElem::Parameter(p) if p == "-name" || p == "-commandtype" || p == "-module" => {
+                let Some(CommandElem::Argument(val)) = iter.next() else {
+                    return Err(CommandError::IncorrectArgs("Get-Command".into()).into());
+                };
+                let values = val
+                    .cast_to_array()
+                    .iter()
+                    .map(Val::cast_to_string)
+                    .collect::<Vec<_>>();
+                match p.as_str() {
+                    "-name" => names.extend(values),
+                    "-commandtype" => command_types.extend(values),
+                    _ => modules.extend(values),
+                }
+            }
+            CommandElem::Argument(val) => {
+                names.extend(val.cast_to_array().iter().map(Val::cast_to_string))
+            }
+            _ => return Err(CommandError::IncorrectArgs("Get-Command".into()).into()),
+        }
+    }
+
+    let like = ComparisonPred::get("-like").unwrap();
+    let matches = |patterns: &[String], value: &str| {
+        patterns.is_empty()
+            || patterns.iter().any(|pattern| {
+                like(
+                    Val::String(value.into()),
+                    Val::String(pattern.as_str().into()),
+                )
+            })
+    };
+
+    let mut commands = all_commands(ps);
+    commands.retain(|command| {
+        matches(&names, &command.name)
+            && matches(&modules, &command.source)
+            && (command_types.is_empty()
+                || command_types.iter().any(|t| {
+                    t.eq_ignore_ascii_case("all")
+                        || t.eq_ignore_ascii_case(&command.command_type.to_string())
+                }))
+    });
+    commands
+        .sort_by_cached_key(|command| (command.command_type, command.name.to_ascii_lowercase()));
+
+    // name without wildcards has to match some command
+    if let Some(name) = names.iter().find(|name| {
+        !name.contains(['*', '?']) && !commands.iter().any(|c| c.name.eq_ignore_ascii_case(name))
+    }) && command_types.is_empty()
+        && modules.is_empty()
+    {
+        return Err(CommandError::NotFound(name.clone()).into());
+    }
+
+    let mut commands = commands
+        .into_iter()
+        .map(|command| Val::RuntimeObject(Box::new(command)))
+        .collect::<Vec<_>>();
+    let val = match commands.len() {
+        0 => Val::Null,
+        1 => commands.remove(0),
+        _ => Val::Array(commands),
+    };
+    Ok(val.into())
+}
+
+fn all_commands(ps: &PowerShellSession) -> Vec<CommandInfo> {
+    let aliases = ALIASES.iter().map(|(name, target)| CommandInfo {
+        name: name.to_string(),
+        command_type: CommandType::Alias,
+        definition: target.to_string(),
+        source: CMDLETS
+            .iter()
+            .find(|(cmdlet, _)| cmdlet == target)
+            .map(|(_, module)| module.to_string())
+            .unwrap_or_default(),
+    });
+    let functions = ps
+        .variables
+        .functions()
+        .into_iter()
+        .map(|(name, script_block)| CommandInfo {
+            name,
+            command_type: CommandType::Function,
+            definition: script_block.body,
+            source: String::new(),
+        });
+    let cmdlets = CMDLETS.iter().map(|(name, module)| CommandInfo {
+        name: name.to_string(),
+        command_type: CommandType::Cmdlet,
+        definition: name.to_string(),
+        source: module.to_string(),
+    });
+    // cmdlets registered in the session, which are not modeled
+    let registered = ps
+        .cmdlets
+        .keys()
+        .filter(|name| {
+            !CMDLETS
+                .iter()
+                .any(|(cmdlet, _)| cmdlet.eq_ignore_ascii_case(name))
+        })
+        .map(|name| CommandInfo {
+            name: name.clone(),
+            command_type: CommandType::Cmdlet,
+            definition: name.clone(),
+            source: String::new(),
+        });
+    aliases
+        .chain(functions)
+        .chain(cmdlets)
+        .chain(registered)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ALIASES, CMDLETS};
+    use crate::{NEWLINE, PowerShellSession, PsValue, parser::command::Command};
+
+    #[test]
+    fn modeled_commands() {
+        // every built-in command is listed, except for the powershell executable
+        for name in Command::COMMAND_MAP
+            .keys()
+            .filter(|name| **name != "powershell")
+        {
+            assert!(
+                CMDLETS
+                    .iter()
+                    .any(|(cmdlet, _)| cmdlet.eq_ignore_ascii_case(name))
+                    || ALIASES.iter().any(|(alias, _)| alias == name),
+                "{name} is not listed by Get-Command"
+            );
+        }
+
+        // and every listed cmdlet is built-in, aliases refer to listed cmdlets
+        for (name, _) in CMDLETS {
+            assert!(
+                Command::COMMAND_MAP.contains_key(name.to_ascii_lowercase().as_str()),
+                "{name} is listed by Get-Command, but not implemented"
+            );
+        }
+        for (alias, target) in ALIASES {
+            assert!(
+                CMDLETS.iter().any(|(cmdlet, _)| cmdlet == &target),
+                "{alias} refers to {target}, which is not listed by Get-Command"
+            );
+        }
+    }
+
+    #[test]
+    fn get_command() {
+        let mut p = PowerShellSession::new();
+        let input = r#"$c = Get-Command Write-Output
+& $c "hi"
+"$($c.Name) $($c.CommandType) $($c.Source)"
+(Get-Command Write-* | % { $_.Name }) -join ","
+function Write-Log { "log" }
+(gcm -CommandType Alias, Function | % { $_.Name }) -join ","
+(Get-Command -Module Microsoft.PowerShell.Management -Name *-Content | % { $_.Name }) -join ","
+& (gcm ('ie{0}' -f 'x')) "1 + 2""#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(
+            s.output(),
+            [
+                "hi",
+                "Write-Output Cmdlet Microsoft.PowerShell.Utility",
+                "Write-Debug,Write-Error,Write-Host,Write-Information,Write-Output,Write-Verbose,Write-Warning",
+                "%,?,foreach,gcm,gm,iex,measure,where,write-log",
+                "Add-Content,Get-Content,Set-Content",
+                "3",
+            ]
+            .join(NEWLINE)
+        );
+
+        let s = p.parse_input("Get-Command Invoke-WebRequest").unwrap();
+        assert_eq!(
+            s.errors()[0].to_string(),
+            "CommandError: Invoke-WebRequest not found"
+        );
+        let s = p.parse_input("(gcm iex).Definition").unwrap();
+        assert_eq!(s.result(), PsValue::String("Invoke-Expression".into()));
+    }
+}
@@ -0,0 +1,138 @@
+use super::{CommandElem, CommandError, CommandOutput, Val};
+use crate::{
+    PowerShellSession,
+    parser::{ParserResult, value::DateTime},
+};
+
+// Get-Date cmdlet implementation. Returns DateTime object or, with -Format or
+// -UFormat, the formatted string
+pub(super) fn get_date(
+    args: &mut [CommandElem],
+    ps: &mut PowerShellSession,
+) -> ParserResult<CommandOutput> {
+    let mut date = None;
+    let mut format = None;
+    let mut uformat = None;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg {
+            CommandElem::Parameter(p) => {
+                let Some(CommandElem::Argument(val)) = iter.next() else {
+                    return Err(CommandError::IncorrectArgs("Get-Date".into()).into());
+                };
+                match p.as_str() {
+                    "-date" => date = Some(val.clone()),
+                    "-format" => format = Some(val.cast_to_string()),
+                    "-uformat" => uformat = Some(val.cast_to_string()),
+                    _ => return Err(CommandError::IncorrectArgs("Get-Date".into()).into()),
+                }
+            }
+            CommandElem::Argument(val) if date.is_none() => date = Some(val.clone()),
+            _ => return Err(CommandError::IncorrectArgs("Get-Date".into()).into()),
+        }
+    }
+
+    let date = match date {
+        Some(val) => DateTime::try_from_val(&val)?,
+        None => DateTime(ps.current_time()),
+    };
+
+    let val = match (format, uformat) {
+        (Some(_), Some(_)) => {
+            return Err(CommandError::ExecutionError(
+                "Parameter set cannot be resolved using the specified named parameters.".into(),
+            )
+            .into());
+        }
+        (Some(format), None) => Val::String(date.format(&format)?.into()),
+        (None, Some(uformat)) => Val::String(date.uformat(&uformat)?.into()),
+        (None, None) => date.into(),
+    };
+
+    Ok(val.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use crate::{PowerShellSession, PsValue};
+
+    fn session() -> PowerShellSession {
+        let now = NaiveDate::from_ymd_opt(2024, 2, 8)
+            .and_then(|d| d.and_hms_opt(15, 5, 9))
+            .unwrap();
+        PowerShellSession::new().with_current_time(now)
+    }
+
+    #[test]
+    fn get_date() {
+        let mut p = session();
+        let s = p.parse_input("Get-Date").unwrap();
+        assert_eq!(
+            s.result(),
+            PsValue::String("02/08/2024 15:05:09".to_string())
+        );
+
+        let s = p.parse_input("(Get-Date).AddDays(-8).Month").unwrap();
+        assert_eq!(s.result(), PsValue::Int(1));
+
+        let s = p
+            .parse_input("Get-Date -Date '2020-01-02' -Format 'dd.MM.yyyy'")
+            .unwrap();
+        assert_eq!(s.result(), PsValue::String("02.01.2020".to_string()));
+
+        let s = p
+            .parse_input("'2020-01-02 10:00:00' | Get-Date -Format t")
+            .unwrap();
+        assert_eq!(s.result(), PsValue::String("10:00 AM".to_string()));
+    }
+
+    #[test]
+    fn get_date_format() {
+        let mut p = session();
+        let input =
+            r#"$d = Get-Date -Format "yyyyMMdd"; if ($d -gt "20240101") { "new" } else { "old" }"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(s.result(), PsValue::String("new".to_string()));
+        assert_eq!(
+            s.deobfuscated(),
+            ["$d = \"20240208\"", "\"new\""].join(crate::NEWLINE)
+        );
+
+        let s = p.parse_input("Get-Date -UFormat '%Y/%m/%d %A %j'").unwrap();
+        assert_eq!(
+            s.result(),
+            PsValue::String("2024/02/08 Thursday 039".to_string())
+        );
+
+        let s = p.parse_input("Get-Date -UFormat '%Q'").unwrap();
+        assert_eq!(
+            s.errors()[0].to_string(),
+            "MethodError: Invalid UFormat \"%Q\""
+        );
+    }
+
+    #[test]
+    fn current_time_of_session() {
+        let mut p = session();
+        let s = p
+            .parse_input(r#"[datetime]::Now.ToString("s"); [DateTime]::Today.Hour"#)
+            .unwrap();
+        assert_eq!(
+            s.output(),
+            ["2024-02-08T15:05:09", "0"].join(crate::NEWLINE)
+        );
+
+        // dates are not numbers
+        let s = p
+            .parse_input("$d = Get-Date; [double]$d; [char][guid]::NewGuid(); 5")
+            .unwrap();
+        assert_eq!(s.result(), PsValue::Int(5));
+        assert_eq!(s.errors().len(), 2);
+        assert_eq!(
+            s.errors()[0].to_string(),
+            "ValError: Failed to convert value 02/08/2024 15:05:09 to type Float"
+        );
+    }
+}
@@ -0,0 +1,129 @@
+use std::collections::HashMap;
+
+use super::{CommandElem, CommandError, CommandOutput, Val};
+use crate::{PowerShellSession, parser::ParserResult};
+
+// Get-Member cmdlet implementation. Every member is reported as a hashtable
+// with its name, member type and definition. Like in PowerShell, members of
+// the piped array are the members of its elements, while -InputObject reports
+// the members of the array itself
+pub(super) fn get_member(
+    args: &mut [CommandElem],
+    _: &mut PowerShellSession,
+) -> ParserResult<CommandOutput> {
+    let mut objects = vec![];
+    let mut names = None;
+    let mut member_type = None;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg {
+            CommandElem::Parameter(p) if p == "-static" || p == "-force" => {}
+            CommandElem::Parameter(p)
+                if p == "-name" || p == "-membertype" || p == "-inputobject" =>
+            {
+                let Some(CommandElem::Argument(val)) = iter.next() else {
+                    return Err(CommandError::IncorrectArgs("Get-Member".into()).into());
+                };
+                match p.as_str() {
+                    "-name" => names = Some(val.cast_to_array()),
+                    "-membertype" => member_type = Some(val.cast_to_string().to_ascii_lowercase()),
+                    _ => objects.push(val.clone()),
+                }
+            }
+            // piped value is always the first argument
+            CommandElem::Argument(val) if objects.is_empty() => objects = val.cast_to_array(),
+            CommandElem::Argument(val) if names.is_none() => names = Some(val.cast_to_array()),
+            _ => return Err(CommandError::IncorrectArgs("Get-Member".into()).into()),
+        }
+    }
+
+    let mut members = vec![];
+    for member in objects.iter().flat_map(Val::members) {
+        if !members.contains(&member) {
+            members.push(member);
+        }
+    }
+
+    let members = members
+        .into_iter()
+        .filter(|member| {
+            names.as_ref().is_none_or(|names| {
+                names
+                    .iter()
+                    .any(|name| name.cast_to_string().eq_ignore_ascii_case(&member.name))
+            })
+        })
+        .filter(|member| {
+            let member_type_name = member.member_type.to_string().to_ascii_lowercase();
+            member_type.as_ref().is_none_or(|member_type| {
+                *member_type == member_type_name
+                    || (member_type == "properties" && member_type_name.ends_with("property"))
+            })
+        })
+        .map(|member| {
+            Val::HashTable(HashMap::from([
+                ("name".to_string(), Val::String(member.name.into())),
+                (
+                    "membertype".to_string(),
+                    Val::String(member.member_type.to_string().into()),
+                ),
+                (
+                    "definition".to_string(),
+                    Val::String(member.definition.into()),
+                ),
+            ]))
+        })
+        .collect::<Vec<_>>();
+
+    let val = match members.len() {
+        0 => Val::Null,
+        1 => members.into_iter().next().unwrap_or_default(),
+        _ => Val::Array(members),
+    };
+    Ok(val.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{PowerShellSession, PsValue};
+
+    #[test]
+    fn get_member() {
+        let mut p = PowerShellSession::new();
+        let input = r#"$m = "abc" | Get-Member
+$m.Count
+($m | ? { $_.Name -eq "ToUpper" }).Definition
+($m | ? { $_.MemberType -eq "Property" }).Name"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(
+            s.output(),
+            [
+                "22",
+                "string ToUpper(), string ToUpper(cultureinfo culture)",
+                "Length",
+            ]
+            .join(crate::NEWLINE)
+        );
+
+        let input =
+            r#"@{ b = "x"; a = 1 } | Get-Member -MemberType NoteProperty | % { $_.Definition }"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(
+            s.result(),
+            PsValue::Array(vec![
+                PsValue::String("int a=1".into()),
+                PsValue::String("string b=x".into())
+            ])
+        );
+
+        // members of the array itself, and of its elements
+        let input = r#"$arr = 1, 2
+(Get-Member -InputObject $arr -MemberType Property | % { $_.Name }) -join ","
+(1, "a" | Get-Member -Name GetType, Length | % { $_.Name }) -join ",""#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(
+            s.output(),
+            ["Count,Length", "GetType,Length"].join(crate::NEWLINE)
+        );
+    }
+}
@@ -0,0 +1,225 @@
+use std::hash::{BuildHasher, RandomState};
+
+use super::{CommandElem, CommandError, CommandOutput, Val};
+use crate::{
+    PowerShellSession,
+    parser::{ParserResult, ValType},
+};
+
+// SplitMix64 generator. It's good enough for the scripts and, unlike the OS
+// generator, gives reproducible results when seeded
+#[derive(Debug, Clone)]
+pub(crate) struct Random {
+    state: u64,
+}
+
+impl Default for Random {
+    fn default() -> Self {
+        Self::new(RandomState::new().hash_one(0u64))
+    }
+}
+
+impl Random {
+    pub(crate) fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    // random integer in range [min, max)
+    fn next_int(&mut self, min: i64, max: i64) -> i64 {
+        let range = max.abs_diff(min);
+        min.wrapping_add((self.next_u64() % range) as i64)
+    }
+
+    // random float in range [min, max)
+    fn next_float(&mut self, min: f64, max: f64) -> f64 {
+        let unit = (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64;
+        min + unit * (max - min)
+    }
+}
+
+// Get-Random cmdlet implementation. Returns random number or random elements
+// of the input collection
+pub(super) fn get_random(
+    args: &mut [CommandElem],
+    ps: &mut PowerShellSession,
+) -> ParserResult<CommandOutput> {
+    let incorrect_args = || CommandError::IncorrectArgs("Get-Random".into());
+
+    let mut minimum = None;
+    let mut maximum = None;
+    let mut count = None;
+    let mut input = None;
+    let mut iter = args.iter();
+    // piped input is the collection to select from, also a single item
+    if ps.piped_cmdlet_arg
+        && let Some(CommandElem::Argument(val)) = iter.next()
+    {
+        input = Some(val.cast_to_array());
+    }
+    while let Some(arg) = iter.next() {
+        match arg {
+            CommandElem::Parameter(p) => {
+                let Some(CommandElem::Argument(val)) = iter.next() else {
+                    return Err(incorrect_args().into());
+                };
+                match p.as_str() {
+                    "-minimum" => minimum = Some(val.clone()),
+                    "-maximum" => maximum = Some(val.clone()),
+                    "-count" => count = Some(val.cast_to_int()?),
+                    "-inputobject" => input = Some(val.cast_to_array()),
+                    "-setseed" => ps.random = Random::new(val.cast_to_int()? as u64),
+                    _ => return Err(incorrect_args().into()),
+                }
+            }
+            // the maximum can be given as positional argument
+            CommandElem::Argument(val) if maximum.is_none() => maximum = Some(val.clone()),
+            _ => return Err(incorrect_args().into()),
+        }
+    }
+
+    let val = if let Some(mut input) = input {
+        // elements are selected without repetitions
+        let count = count.unwrap_or(1).clamp(0, input.len() as i64) as usize;
+        let mut selected = Vec::with_capacity(count);
+        for _ in 0..count {
+            let i = ps.random.next_int(0, input.len() as i64) as usize;
+            selected.push(input.swap_remove(i));
+        }
+        match selected.len() {
+            0 => Val::Null,
+            1 => selected.remove(0),
+            _ => Val::Array(selected),
+        }
+    } else {
+        let count = count.unwrap_or(1);
+        ps.check_loop_iterations(count.max(0) as u64)?;
+        let numbers = (0..count)
+            .map(|_| random_number(ps, minimum.as_ref(), maximum.as_ref()))
+            .collect::<ParserResult<Vec<_>>>()?;
+        if numbers.len() == 1 {
+            numbers.into_iter().next().unwrap_or_default()
+        } else {
+            Val::Array(numbers)
+        }
+    };
+
+    Ok(val.into())
+}
+
+fn random_number(
+    ps: &mut PowerShellSession,
+    minimum: Option<&Val>,
+    maximum: Option<&Val>,
+) -> ParserResult<Val> {
+    let is_float = |v: Option<&Val>| v.is_some_and(|v| v.ttype() == ValType::Float);
+
+    if is_float(minimum) || is_float(maximum) {
+        let min = minimum.map(Val::cast_to_float).transpose()?.unwrap_or(0.);
+        let max = maximum
+            .map(Val::cast_to_float)
+            .transpose()?
+            .unwrap_or(f64::MAX);
+        check_range(min, max)?;
+        Ok(Val::Float(ps.random.next_float(min, max)))
+    } else {
+        let min = minimum.map(Val::cast_to_int).transpose()?.unwrap_or(0);
+        let max = maximum
+            .map(Val::cast_to_int)
+            .transpose()?
+            .unwrap_or(i32::MAX as i64);
+        check_range(min, max)?;
+        Ok(Val::Int(ps.random.next_int(min, max)))
+    }
+}
+
+fn check_range<T: PartialOrd + std::fmt::Display>(min: T, max: T) -> ParserResult<()> {
+    if min >= max {
+        Err(CommandError::ExecutionError(format!(
+            "The Minimum value ({min}) cannot be greater than or equal to the Maximum value ({max})."
+        )))?
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{PowerShellSession, PsValue};
+
+    #[test]
+    fn seeded_random() {
+        let script = r#"
+$hosts = "a.com", "b.com", "c.com"
+$a = Get-Random -Minimum 1 -Maximum 10
+$b = $hosts | Get-Random
+$c = Get-Random -InputObject $hosts -Count 3
+"$a $b $($c -join ',')""#;
+
+        let mut p1 = PowerShellSession::new().with_random_seed(42);
+        let mut p2 = PowerShellSession::new().with_random_seed(42);
+        let res1 = p1.parse_input(script).unwrap().result();
+        assert_eq!(res1, p2.parse_input(script).unwrap().result());
+
+        let PsValue::String(res) = res1 else {
+            panic!("expected string, got {res1:?}");
+        };
+        let parts = res.split(' ').collect::<Vec<_>>();
+        let a = parts[0].parse::<i64>().unwrap();
+        assert!((1..10).contains(&a));
+        assert!(["a.com", "b.com", "c.com"].contains(&parts[1]));
+        let mut c = parts[2].split(',').collect::<Vec<_>>();
+        c.sort();
+        assert_eq!(c, ["a.com", "b.com", "c.com"]);
+    }
+
+    #[test]
+    fn piped_item() {
+        let mut p = PowerShellSession::new();
+        let s = p.parse_input(r#"$h = "a.com" | Get-Random; $h"#).unwrap();
+        assert_eq!(s.result(), PsValue::String("a.com".into()));
+        assert!(s.errors().is_empty());
+    }
+
+    #[test]
+    fn random_range() {
+        let mut p = PowerShellSession::new();
+        for _ in 0..20 {
+            let PsValue::Int(i) = p.parse_input("Get-Random 3").unwrap().result() else {
+                panic!("expected int");
+            };
+            assert!((0..3).contains(&i));
+
+            let PsValue::Float(f) = p
+                .parse_input("Get-Random -Minimum 0.5 -Maximum 1.5")
+                .unwrap()
+                .result()
+            else {
+                panic!("expected float");
+            };
+            assert!((0.5..1.5).contains(&f));
+        }
+
+        let s = p.parse_input("Get-Random -Count 4 -Maximum 100").unwrap();
+        assert!(matches!(s.result(), PsValue::Array(a) if a.len() == 4));
+
+        let s = p.parse_input("Get-Random -Count 100000000; 5").unwrap();
+        assert_eq!(
+            s.errors()[0].to_string(),
+            "LimitExceeded: Loop exceeded the maximum number of iterations (1000000)"
+        );
+
+        let s = p.parse_input("Get-Random -Minimum 5 -Maximum 5").unwrap();
+        assert_eq!(
+            s.errors()[0].to_string(),
+            "CommandError: The Minimum value (5) cannot be greater than or equal to the Maximum \
+             value (5)."
+        );
+    }
+}
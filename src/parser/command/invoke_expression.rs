@@ -0,0 +1,104 @@
+use super::{CommandElem, CommandError, CommandOutput};
+use crate::{
+    NEWLINE, PowerShellSession, PowerShellStream,
+    parser::{ParserResult, Results},
+};
+
+// Invoke-Expression cmdlet implementation. The string is evaluated in the
+// caller's scope, and its statements are deobfuscated in place of the call
+pub(super) fn invoke_expression(
+    args: &mut [CommandElem],
+    ps: &mut PowerShellSession,
+) -> ParserResult<CommandOutput> {
+    let mut command = None;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg {
+            CommandElem::Parameter(p) if p == "-command" => {
+                let Some(CommandElem::Argument(val)) = iter.next() else {
+                    return Err(CommandError::IncorrectArgs("Invoke-Expression".into()).into());
+                };
+                command = Some(val.cast_to_string());
+            }
+            CommandElem::Argument(val) if command.is_none() => command = Some(val.cast_to_string()),
+            _ => return Err(CommandError::IncorrectArgs("Invoke-Expression".into()).into()),
+        }
+    }
+    let Some(command) = command else {
+        return Err(CommandError::IncorrectArgs("Invoke-Expression".into()).into());
+    };
+
+    ps.enter_dynamic_eval()?;
+    let res = ps.parse_subscript(&command);
+    ps.leave_dynamic_eval();
+    let (
+        val,
+        Results {
+            output,
+            deobfuscated,
+        },
+    ) = res?;
+
+    // the returned value is output and deobfuscated by the calling statement
+    let mut output = output;
+    if output
+        .last()
+        .is_some_and(|msg| msg.stream == PowerShellStream::Success && msg.content == val.display())
+    {
+        output.pop();
+    }
+    output
+        .into_iter()
+        .for_each(|msg| ps.add_output_statement(msg));
+
+    let mut lines = deobfuscated;
+    if lines.last() == Some(&val.cast_to_script()) {
+        lines.pop();
+    }
+    Ok(CommandOutput {
+        val,
+        deobfuscated: (!lines.is_empty()).then(|| lines.join(NEWLINE)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{NEWLINE, PowerShellSession, PsValue};
+
+    #[test]
+    fn invoke_expression() {
+        let mut p = PowerShellSession::new();
+        let input = r#"$code = ('Set-Variable a (3 * 2)', '$a + 1') -join ';'
+$r = Invoke-Expression -Command $code
+$a + $r"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(s.result(), PsValue::Int(13));
+        assert_eq!(
+            s.deobfuscated(),
+            [
+                r#"$code = "Set-Variable a (3 * 2);$a + 1""#,
+                "$a = 6",
+                "$r = 7",
+                "13",
+            ]
+            .join(NEWLINE)
+        );
+
+        let s = p.parse_input(r#"'[char]0x41' | iex"#).unwrap();
+        assert_eq!(s.result(), PsValue::Char(0x41));
+    }
+
+    #[test]
+    fn invoke_expression_output() {
+        let mut p = PowerShellSession::new();
+        let s = p.parse_input(r#"iex 'Write-Host hi'"#).unwrap();
+        assert_eq!(s.output(), "hi");
+        assert_eq!(s.deobfuscated(), "iex Write-Host hi");
+
+        let s = p
+            .parse_input(r#"iex '$b = 2; Write-Warning "b is $b"; $b + 1'"#)
+            .unwrap();
+        assert_eq!(s.output(), ["WARNING: b is 2", "3"].join(NEWLINE));
+        assert_eq!(s.deobfuscated(), ["$b = 2", "3"].join(NEWLINE));
+    }
+}
@@ -0,0 +1,95 @@
+use super::{CommandElem, CommandError, CommandOutput, Val};
+use crate::{
+    PowerShellSession,
+    parser::{ParserResult, value::RuntimeObject},
+};
+
+// Join-String cmdlet implementation (PowerShell 7). Items, or their -Property
+// values, are optionally quoted and joined with -Separator, which is empty by
+// default. The result is wrapped with -OutputPrefix and -OutputSuffix
+pub(super) fn join_string(
+    args: &mut [CommandElem],
+    ps: &mut PowerShellSession,
+) -> ParserResult<CommandOutput> {
+    let mut input = None;
+    let mut property = None;
+    let mut separator = None;
+    let mut quote = "";
+    let mut prefix = String::new();
+    let mut suffix = String::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg {
+            CommandElem::Parameter(p) if p == "-singlequote" => quote = "'",
+            CommandElem::Parameter(p) if p == "-doublequote" => quote = "\"",
+            CommandElem::Parameter(p) => {
+                let Some(CommandElem::Argument(val)) = iter.next() else {
+                    return Err(CommandError::IncorrectArgs("Join-String".into()).into());
+                };
+                match p.as_str() {
+                    "-property" => property = Some(val.clone()),
+                    "-separator" => separator = Some(val.cast_to_string()),
+                    "-outputprefix" => prefix = val.cast_to_string(),
+                    "-outputsuffix" => suffix = val.cast_to_string(),
+                    "-inputobject" => input = Some(val.clone()),
+                    _ => return Err(CommandError::IncorrectArgs("Join-String".into()).into()),
+                }
+            }
+            // piped value is always the first argument
+            CommandElem::Argument(val) if input.is_none() => input = Some(val.clone()),
+            CommandElem::Argument(val) if property.is_none() => property = Some(val.clone()),
+            CommandElem::Argument(val) if separator.is_none() => {
+                separator = Some(val.cast_to_string())
+            }
+            _ => return Err(CommandError::IncorrectArgs("Join-String".into()).into()),
+        }
+    }
+
+    let items = input
+        .map(|input| input.cast_to_array())
+        .unwrap_or_default()
+        .into_iter()
+        .map(|val| {
+            let val = match &property {
+                // script block computes the joined value from $_
+                Some(Val::ScriptBlock(sb)) => sb.run(vec![], ps, Some(val))?.val,
+                Some(property) => val
+                    .readonly_member(&property.cast_to_string())
+                    .unwrap_or_default(),
+                None => val,
+            };
+            Ok(format!("{quote}{}{quote}", val.cast_to_string()))
+        })
+        .collect::<ParserResult<Vec<_>>>()?;
+
+    let joined = items.join(&separator.unwrap_or_default());
+    Ok(Val::String(format!("{prefix}{joined}{suffix}").into()).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{NEWLINE, PowerShellSession, PsValue};
+
+    #[test]
+    fn join_string() {
+        let mut p = PowerShellSession::new();
+        let s = p.parse_input("1..3 | Join-String -Separator '-'").unwrap();
+        assert_eq!(s.result(), PsValue::String("1-2-3".into()));
+
+        let input = r#"$items = @{ Name = "a" }, @{ Name = "b" }
+$items | Join-String -Property Name -Separator ', ' -DoubleQuote
+$items | Join-String Name -SingleQuote -OutputPrefix "(" -OutputSuffix ")"
+"x", "y" | Join-String"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(s.output(), ["\"a\", \"b\"", "('a''b')", "xy"].join(NEWLINE));
+
+        let s = p
+            .parse_input("1..3 | Join-String { $_ * 2 } -Separator ','")
+            .unwrap();
+        assert_eq!(s.result(), PsValue::String("2,4,6".into()));
+        let s = p
+            .parse_input("'a', 'b' | Join-String -Property { $_.ToUpper() }")
+            .unwrap();
+        assert_eq!(s.result(), PsValue::String("AB".into()));
+    }
+}
@@ -0,0 +1,185 @@
+use std::collections::HashMap;
+
+use super::{CommandElem, CommandError, CommandOutput, Val};
+use crate::{
+    PowerShellSession,
+    parser::{ParserResult, value::RuntimeObject},
+};
+
+// Measure-Object cmdlet implementation. The result is a hashtable like
+// GenericMeasureInfo (count, sum, average, maximum, minimum) or, with any of
+// -Character, -Word, -Line, like TextMeasureInfo (characters, words, lines).
+// Statistics which were not requested are $null
+pub(super) fn measure_object(
+    args: &mut [CommandElem],
+    _: &mut PowerShellSession,
+) -> ParserResult<CommandOutput> {
+    let mut input = None;
+    let mut property = None;
+    let mut stats = vec![];
+    let mut ignore_white_space = false;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg {
+            CommandElem::Parameter(p) => match p.as_str() {
+                "-sum" | "-average" | "-maximum" | "-minimum" | "-character" | "-word"
+                | "-line" => stats.push(p.trim_start_matches('-').to_string()),
+                "-ignorewhitespace" => ignore_white_space = true,
+                "-property" | "-inputobject" => {
+                    let Some(CommandElem::Argument(val)) = iter.next() else {
+                        return Err(CommandError::IncorrectArgs("Measure-Object".into()).into());
+                    };
+                    if p == "-property" {
+                        property = Some(val.cast_to_string());
+                    } else {
+                        input = Some(val.clone());
+                    }
+                }
+                _ => return Err(CommandError::IncorrectArgs("Measure-Object".into()).into()),
+            },
+            // piped value is always the first argument
+            CommandElem::Argument(val) if input.is_none() => input = Some(val.clone()),
+            CommandElem::Argument(val) if property.is_none() => {
+                property = Some(val.cast_to_string())
+            }
+            _ => return Err(CommandError::IncorrectArgs("Measure-Object".into()).into()),
+        }
+    }
+
+    let values = input
+        .map(|input| input.cast_to_array())
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|val| match &property {
+            Some(property) => val.readonly_member(property).ok(),
+            None => Some(val),
+        })
+        .filter(|val| *val != Val::Null)
+        .collect::<Vec<_>>();
+
+    let is_text = stats
+        .iter()
+        .any(|stat| matches!(stat.as_str(), "character" | "word" | "line"));
+    let mut res = if is_text {
+        text_measure(&values, &stats, ignore_white_space)
+    } else {
+        generic_measure(&values, &stats)?
+    };
+    res.insert(
+        "property".to_string(),
+        property.map(|p| Val::String(p.into())).unwrap_or_default(),
+    );
+    Ok(Val::HashTable(res).into())
+}
+
+fn generic_measure(values: &[Val], stats: &[String]) -> ParserResult<HashMap<String, Val>> {
+    let mut res = HashMap::from([("count".to_string(), Val::Int(values.len() as i64))]);
+    for stat in ["sum", "average", "maximum", "minimum"] {
+        res.insert(stat.to_string(), Val::Null);
+    }
+    if stats.is_empty() {
+        return Ok(res);
+    }
+
+    let not_numeric = |val: &Val| {
+        CommandError::ExecutionError(format!(
+            "Input object \"{}\" is not numeric.",
+            val.cast_to_string()
+        ))
+    };
+    let numbers = values
+        .iter()
+        .map(|val| match val {
+            // objects like dates are not numbers, even if they can be converted
+            Val::RuntimeObject(_) => Err(not_numeric(val)),
+            val => val.cast_to_float().map_err(|_| not_numeric(val)),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let sum = numbers.iter().sum::<f64>();
+    for stat in stats {
+        let val = match stat.as_str() {
+            "sum" => Some(sum),
+            "average" => (!numbers.is_empty()).then(|| sum / numbers.len() as f64),
+            "maximum" => numbers.iter().copied().reduce(f64::max),
+            "minimum" => numbers.iter().copied().reduce(f64::min),
+            _ => None,
+        };
+        res.insert(stat.clone(), val.map(Val::Float).unwrap_or_default());
+    }
+    Ok(res)
+}
+
+fn text_measure(
+    values: &[Val],
+    stats: &[String],
+    ignore_white_space: bool,
+) -> HashMap<String, Val> {
+    let texts = values.iter().map(Val::cast_to_string).collect::<Vec<_>>();
+    let mut res = HashMap::new();
+    for (stat, key) in [
+        ("character", "characters"),
+        ("word", "words"),
+        ("line", "lines"),
+    ] {
+        let val = stats.iter().any(|s| s == stat).then(|| {
+            texts
+                .iter()
+                .map(|text| match stat {
+                    "character" if ignore_white_space => {
+                        text.chars().filter(|c| !c.is_whitespace()).count()
+                    }
+                    "character" => text.chars().count(),
+                    "word" => text.split_whitespace().count(),
+                    _ => text.lines().count(),
+                })
+                .sum::<usize>()
+        });
+        res.insert(
+            key.to_string(),
+            val.map(|v| Val::Int(v as i64)).unwrap_or_default(),
+        );
+    }
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{NEWLINE, PowerShellSession, PsValue};
+
+    #[test]
+    fn measure_text() {
+        let mut p = PowerShellSession::new();
+        let input = r#"$m = "The quick  brown fox" | Measure-Object -Word -Character
+"$($m.Words) $($m.Characters) $($null -eq $m.Lines)""#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(s.result(), PsValue::String("4 20 True".into()));
+
+        let input = r#"$m = "a b", "c" | Measure-Object -Line -Word -Character -IgnoreWhiteSpace
+"$($m.Lines) $($m.Words) $($m.Characters)""#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(s.result(), PsValue::String("2 3 3".into()));
+    }
+
+    #[test]
+    fn measure_numbers() {
+        let mut p = PowerShellSession::new();
+        let input = r#"$m = 4, 1, 7 | Measure-Object -Sum -Maximum -Average
+$m.Count; $m.Sum; $m.Maximum; $m.Average
+(@{ size = 10 }, @{ size = 5 } | Measure-Object -Property size -Minimum).Minimum"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(s.output(), ["3", "12", "7", "4", "5"].join(NEWLINE));
+
+        let s = p.parse_input(r#""a" | Measure-Object -Sum"#).unwrap();
+        assert_eq!(
+            s.errors()[0].to_string(),
+            "CommandError: Input object \"a\" is not numeric."
+        );
+
+        let s = p
+            .parse_input("(Get-Date),1 | Measure-Object -Sum; @((Get-Date),1).Sum(); 5")
+            .unwrap();
+        assert_eq!(s.result(), PsValue::Int(5));
+        assert_eq!(s.errors().len(), 2);
+        assert!(s.errors()[1].to_string().ends_with("is not numeric."));
+    }
+}
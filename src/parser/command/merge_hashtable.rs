@@ -0,0 +1,92 @@
+use std::collections::HashMap;
+
+use super::{CommandElem, CommandError, CommandOutput, Val};
+use crate::{PowerShellSession, parser::ParserResult};
+
+// Merge-HashTable cmdlet implementation. Unlike `+`, nested hashtables are
+// merged recursively instead of being overwritten
+pub(super) fn merge_hashtable(
+    args: &mut [CommandElem],
+    _: &mut PowerShellSession,
+) -> ParserResult<CommandOutput> {
+    let tables = args
+        .iter()
+        .map(|arg| match arg {
+            CommandElem::Argument(Val::HashTable(ht)) => Ok(ht.clone()),
+            _ => Err(CommandError::IncorrectArgs("Merge-HashTable".into())),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if tables.is_empty() {
+        return Err(CommandError::IncorrectArgs("Merge-HashTable".into()).into());
+    }
+
+    let merged = tables.into_iter().reduce(|mut left, right| {
+        merge(&mut left, right);
+        left
+    });
+
+    Ok(Val::HashTable(merged.unwrap_or_default()).into())
+}
+
+fn merge(left: &mut HashMap<String, Val>, right: HashMap<String, Val>) {
+    for (key, value) in right {
+        match (left.get_mut(&key), value) {
+            (Some(Val::HashTable(inner_left)), Val::HashTable(inner_right)) => {
+                merge(inner_left, inner_right)
+            }
+            (_, value) => {
+                left.insert(key, value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::{PowerShellSession, PsValue, Variables};
+
+    #[test]
+    fn merge_nested_hashtables() {
+        let mut p = PowerShellSession::new().with_variables(Variables::new().values_persist());
+        let input = r#"
+$a = @{ Name = "a"; Settings = @{ Theme = "Dark"; Size = 1 } }
+$b = @{ Settings = @{ Size = 2; Language = "en-US" }; Extra = 5 }
+Merge-HashTable $a $b"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(s.errors().len(), 0);
+        assert_eq!(
+            s.result(),
+            PsValue::HashTable(HashMap::from([
+                ("name".to_string(), PsValue::String("a".to_string())),
+                ("extra".to_string(), PsValue::Int(5)),
+                (
+                    "settings".to_string(),
+                    PsValue::HashTable(HashMap::from([
+                        ("theme".to_string(), PsValue::String("Dark".to_string())),
+                        ("size".to_string(), PsValue::Int(2)),
+                        ("language".to_string(), PsValue::String("en-US".to_string())),
+                    ]))
+                ),
+            ]))
+        );
+
+        let input = r#"$a | Merge-HashTable $b | % { $_.settings.theme }"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(s.result(), PsValue::String("Dark".to_string()));
+
+        // "+" does a shallow extend
+        let input = r#"($a + $b).settings.theme"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(s.result(), PsValue::Null);
+
+        let input = r#"Merge-HashTable $a 5"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(
+            s.errors()[0].to_string(),
+            "CommandError: Incorrect arguments for method \"Merge-HashTable\""
+        );
+    }
+}
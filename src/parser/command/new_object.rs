@@ -0,0 +1,124 @@
+use super::{CommandElem, CommandError, CommandOutput, Val};
+use crate::{
+    PowerShellSession,
+    parser::{ParserResult, ValType, value::ObjectPlaceholder},
+};
+
+// New-Object cmdlet implementation. PSObject is modeled as a hashtable, types
+// which are not modeled are replaced by a placeholder keeping the type name
+pub(super) fn new_object(
+    args: &mut [CommandElem],
+    _: &mut PowerShellSession,
+) -> ParserResult<CommandOutput> {
+    let mut type_name = None;
+    let mut properties = None;
+    let mut arguments = vec![];
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg {
+            CommandElem::Parameter(p) => {
+                let Some(CommandElem::Argument(val)) = iter.next() else {
+                    return Err(CommandError::IncorrectArgs("New-Object".into()).into());
+                };
+                match p.as_str() {
+                    "-typename" | "-comobject" => type_name = Some(val.cast_to_string()),
+                    "-property" => properties = Some(val.cast_to_hashtable()?),
+                    // constructor arguments are not used by any of modeled types,
+                    // only placeholders keep them
+                    "-argumentlist" => arguments = val.cast_to_array(),
+                    _ => return Err(CommandError::IncorrectArgs("New-Object".into()).into()),
+                }
+            }
+            CommandElem::Argument(val) if type_name.is_none() => {
+                type_name = Some(val.cast_to_string())
+            }
+            // positional argument list
+            CommandElem::Argument(val) => arguments = val.cast_to_array(),
+            CommandElem::ArgList(_) => {}
+        }
+    }
+
+    let Some(type_name) = type_name else {
+        return Err(CommandError::IncorrectArgs("New-Object".into()).into());
+    };
+
+    let lowercase_name = type_name.to_ascii_lowercase();
+    let short_name = lowercase_name
+        .strip_prefix("system.management.automation.")
+        .unwrap_or(&lowercase_name);
+
+    let val = match short_name {
+        "psobject" | "pscustomobject" => Val::HashTable(properties.unwrap_or_default()),
+        _ => match ValType::cast(&type_name).and_then(Val::init) {
+            Ok(Val::Null) | Err(_) => {
+                Val::RuntimeObject(Box::new(ObjectPlaceholder::new(&type_name, arguments)))
+            }
+            Ok(val) => val,
+        },
+    };
+
+    Ok(val.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::{NEWLINE, PowerShellSession, PsValue};
+
+    #[test]
+    fn new_psobject() {
+        let mut p = PowerShellSession::new();
+        let input = r#"$o = New-Object PSObject -Property @{Name='x'; Size = 3}; $o.Name"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(s.result(), PsValue::String("x".to_string()));
+
+        let input = r#"New-Object -TypeName System.Management.Automation.PSCustomObject"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(s.result(), PsValue::HashTable(HashMap::new()));
+    }
+
+    #[test]
+    fn new_known_type() {
+        let mut p = PowerShellSession::new();
+        let input = r#"$e = New-Object System.Text.UTF8Encoding; $e.GetString([byte[]](104, 105))"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(s.result(), PsValue::String("hi".to_string()));
+
+        let s = p.parse_input("New-Object int").unwrap();
+        assert_eq!(s.result(), PsValue::Int(0));
+    }
+
+    #[test]
+    fn new_unknown_type() {
+        let mut p = PowerShellSession::new();
+        let input = r#"$wc = New-Object System.Net.WebClient; "$wc"; $wc"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(
+            s.output(),
+            ["System.Net.WebClient", "System.Net.WebClient"].join(NEWLINE)
+        );
+        assert_eq!(
+            s.deobfuscated(),
+            [
+                "$wc = (New-Object System.Net.WebClient)",
+                "\"System.Net.WebClient\"",
+                "(New-Object System.Net.WebClient)"
+            ]
+            .join(NEWLINE)
+        );
+
+        let input = r#"$ip = "10.0.0.1"; New-Object Net.Sockets.TCPClient -ArgumentList $ip, 443
+New-Object Net.Sockets.TCPClient $ip, 443"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(
+            s.deobfuscated(),
+            [
+                "$ip = \"10.0.0.1\"",
+                "(New-Object Net.Sockets.TCPClient -ArgumentList \"10.0.0.1\", 443)",
+                "(New-Object Net.Sockets.TCPClient -ArgumentList \"10.0.0.1\", 443)"
+            ]
+            .join(NEWLINE)
+        );
+    }
+}
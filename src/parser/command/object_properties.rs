@@ -0,0 +1,17 @@
+use std::collections::BTreeMap;
+
+use super::Val;
+
+// keys and values of the object, like the columns of a csv row or a table
+pub(super) fn properties(val: &Val) -> Vec<(String, Val)> {
+    match val {
+        // hashtable is unordered, so sort the keys to get stable output
+        Val::HashTable(h) => BTreeMap::from_iter(h.iter())
+            .into_iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect(),
+        Val::RuntimeObject(rt) => rt.properties().unwrap_or_default(),
+        Val::NonDisplayed(inner) => properties(inner),
+        _ => vec![],
+    }
+}
@@ -0,0 +1,187 @@
+use std::cmp::Ordering;
+
+use super::{CommandElem, CommandError, CommandOutput, Val};
+use crate::{
+    PowerShellSession,
+    parser::{ParserResult, value::RuntimeObject},
+};
+
+// Sort-Object cmdlet implementation. Elements are sorted by the values of
+// properties (or script blocks) given in -Property, the first key decides and
+// the next ones are tiebreakers. Without properties the elements themselves
+// are compared
+pub(super) fn sort_object(
+    args: &mut [CommandElem],
+    ps: &mut PowerShellSession,
+) -> ParserResult<CommandOutput> {
+    let mut input = None;
+    let mut properties = None;
+    let mut descending = false;
+    let mut unique = false;
+    let mut case_sensitive = false;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg {
+            CommandElem::Parameter(p) => match p.as_str() {
+                "-descending" => descending = true,
+                "-unique" => unique = true,
+                "-casesensitive" => case_sensitive = true,
+                "-property" | "-inputobject" => {
+                    let Some(CommandElem::Argument(val)) = iter.next() else {
+                        return Err(CommandError::IncorrectArgs("Sort-Object".into()).into());
+                    };
+                    if p == "-property" {
+                        properties = Some(val.clone());
+                    } else {
+                        input = Some(val.clone());
+                    }
+                }
+                _ => return Err(CommandError::IncorrectArgs("Sort-Object".into()).into()),
+            },
+            // piped value is always the first argument
+            CommandElem::Argument(val) if input.is_none() => input = Some(val.clone()),
+            CommandElem::Argument(val) if properties.is_none() => properties = Some(val.clone()),
+            _ => return Err(CommandError::IncorrectArgs("Sort-Object".into()).into()),
+        }
+    }
+
+    let elements = match input {
+        None | Some(Val::Null) => return Ok(Val::Null.into()),
+        Some(Val::Array(elements)) => elements,
+        Some(val) => vec![val],
+    };
+
+    let properties = match properties {
+        None => vec![],
+        Some(Val::Array(props)) => props,
+        Some(prop) => vec![prop],
+    };
+
+    let mut keyed = Vec::with_capacity(elements.len());
+    for element in elements {
+        let keys = if properties.is_empty() {
+            vec![element.clone()]
+        } else {
+            properties
+                .iter()
+                .map(|prop| sort_key(&element, prop, ps))
+                .collect::<ParserResult<Vec<_>>>()?
+        };
+        keyed.push((keys, element));
+    }
+
+    // sort_by is stable, so elements with equal keys keep the input order
+    keyed.sort_by(|(a, _), (b, _)| {
+        let ordering = compare_keys(a, b, !case_sensitive);
+        if descending {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    });
+    if unique {
+        keyed.dedup_by(|(a, _), (b, _)| compare_keys(a, b, !case_sensitive) == Ordering::Equal);
+    }
+
+    let mut sorted = keyed
+        .into_iter()
+        .map(|(_, element)| element)
+        .collect::<Vec<_>>();
+    let val = match sorted.len() {
+        0 => Val::Null,
+        1 => sorted.remove(0),
+        _ => Val::Array(sorted),
+    };
+
+    Ok(val.into())
+}
+
+fn sort_key(element: &Val, property: &Val, ps: &mut PowerShellSession) -> ParserResult<Val> {
+    Ok(match property {
+        Val::ScriptBlock(sb) => sb.run(vec![], ps, Some(element.clone()))?.val,
+        // missing properties are sorted like $null
+        prop => element
+            .readonly_member(&prop.cast_to_string())
+            .unwrap_or(Val::Null),
+    })
+}
+
+// lexicographic comparison of the sort keys, $null goes first
+fn compare_keys(a: &[Val], b: &[Val], case_insensitive: bool) -> Ordering {
+    for (x, y) in a.iter().zip(b.iter()) {
+        let ordering = match (x, y) {
+            (Val::Null, Val::Null) => Ordering::Equal,
+            (Val::Null, _) => Ordering::Less,
+            (_, Val::Null) => Ordering::Greater,
+            // arrays and objects are not comparable, use their string form
+            (Val::Array(_) | Val::RuntimeObject(_), _)
+            | (_, Val::Array(_) | Val::RuntimeObject(_)) => compare_vals(
+                &Val::from(x.cast_to_string()),
+                &Val::from(y.cast_to_string()),
+                case_insensitive,
+            ),
+            _ => compare_vals(x, y, case_insensitive),
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    Ordering::Equal
+}
+
+fn compare_vals(x: &Val, y: &Val, case_insensitive: bool) -> Ordering {
+    if x.lt(y.clone(), case_insensitive).unwrap_or_default() {
+        Ordering::Less
+    } else if x.gt(y.clone(), case_insensitive).unwrap_or_default() {
+        Ordering::Greater
+    } else {
+        Ordering::Equal
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{PowerShellSession, PsValue};
+
+    #[test]
+    fn sort_values() {
+        let mut p = PowerShellSession::new();
+        let s = p.parse_input(r#"3, 1, 2 | Sort-Object"#).unwrap();
+        assert_eq!(
+            s.result(),
+            PsValue::Array(vec![PsValue::Int(1), PsValue::Int(2), PsValue::Int(3)])
+        );
+
+        let s = p
+            .parse_input(r#""b", "A", "c", "a" | Sort-Object -Descending -Unique"#)
+            .unwrap();
+        assert_eq!(
+            s.result(),
+            PsValue::Array(vec![
+                PsValue::String("c".into()),
+                PsValue::String("b".into()),
+                PsValue::String("A".into()),
+            ])
+        );
+    }
+
+    #[test]
+    fn sort_by_multiple_properties() {
+        let mut p = PowerShellSession::new();
+        let input = r#"$items = @(
+    [pscustomobject]@{ Name = "b"; Size = 2 },
+    [pscustomobject]@{ Name = "a"; Size = 2 },
+    [pscustomobject]@{ Name = "c"; Size = 1 }
+)
+($items | Sort-Object -Property Size, Name | ForEach-Object { $_.Name }) -join ","
+"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(s.result(), PsValue::String("c,a,b".into()));
+
+        let input = r#"$items = @(@{ n = "b"; a = 2 }, @{ n = "a"; a = 2 }, @{ n = "c"; a = 1 })
+($items | Sort-Object a, { $_.n } -Descending | ForEach-Object { $_.n }) -join ","
+"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(s.result(), PsValue::String("b,a,c".into()));
+    }
+}
@@ -0,0 +1,170 @@
+use super::{CommandElem, CommandError, CommandOutput, Val};
+use crate::{
+    PowerShellSession,
+    parser::{
+        ParserResult,
+        variables::{Scope, VarName},
+    },
+};
+
+// Set-Variable cmdlet implementation. The assignment is deobfuscated to the
+// plain `$name = value` statement
+pub(super) fn set_variable(
+    args: &mut [CommandElem],
+    ps: &mut PowerShellSession,
+) -> ParserResult<CommandOutput> {
+    let args = variable_args("Set-Variable", args)?;
+    let mut positional = args.positional.into_iter();
+    let name = args.name.or_else(|| positional.next());
+    let value = args.value.or_else(|| positional.next());
+    let (Some(name), None) = (name, positional.next()) else {
+        return Err(CommandError::IncorrectArgs("Set-Variable".into()).into());
+    };
+
+    let value = value.unwrap_or_default();
+    let var_name = VarName::new(args.scope, name.cast_to_string().to_ascii_lowercase());
+    ps.variables.set(&var_name, value.clone())?;
+    ps.add_deobfuscated_statement(format!("{} = {}", var_name, value.cast_to_script()));
+
+    let val = if args.pass_thru { value } else { Val::Null };
+    Ok(val.into())
+}
+
+// Get-Variable cmdlet implementation. Returns the value of the variable, like
+// with -ValueOnly
+pub(super) fn get_variable(
+    args: &mut [CommandElem],
+    ps: &mut PowerShellSession,
+) -> ParserResult<CommandOutput> {
+    let args = variable_args("Get-Variable", args)?;
+    let values = variable_names("Get-Variable", &args)?
+        .into_iter()
+        .map(|var_name| {
+            ps.variables
+                .get(&var_name)
+                .ok_or_else(|| not_found(&var_name))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let val = match values.len() {
+        1 => values.into_iter().next().unwrap_or_default(),
+        _ => Val::Array(values),
+    };
+    Ok(val.into())
+}
+
+// Remove-Variable cmdlet implementation
+pub(super) fn remove_variable(
+    args: &mut [CommandElem],
+    ps: &mut PowerShellSession,
+) -> ParserResult<CommandOutput> {
+    let args = variable_args("Remove-Variable", args)?;
+    for var_name in variable_names("Remove-Variable", &args)? {
+        if ps.variables.remove(&var_name)?.is_none() {
+            return Err(not_found(&var_name).into());
+        }
+        ps.add_deobfuscated_statement(format!("Remove-Variable -Name {}", var_name.name));
+    }
+    Ok(Val::Null.into())
+}
+
+#[derive(Default)]
+struct VariableArgs {
+    name: Option<Val>,
+    value: Option<Val>,
+    scope: Option<Scope>,
+    pass_thru: bool,
+    positional: Vec<Val>,
+}
+
+fn variable_args(cmdlet: &str, args: &[CommandElem]) -> ParserResult<VariableArgs> {
+    let mut res = VariableArgs::default();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg {
+            CommandElem::Parameter(p) => match p.as_str() {
+                "-passthru" => res.pass_thru = true,
+                // the value is returned anyway
+                "-valueonly" => {}
+                "-name" | "-value" | "-scope" => {
+                    let Some(CommandElem::Argument(val)) = iter.next() else {
+                        return Err(CommandError::IncorrectArgs(cmdlet.into()).into());
+                    };
+                    match p.as_str() {
+                        "-name" => res.name = Some(val.clone()),
+                        "-value" => res.value = Some(val.clone()),
+                        _ => res.scope = Some(Scope::from(val.cast_to_string().as_str())),
+                    }
+                }
+                _ => return Err(CommandError::IncorrectArgs(cmdlet.into()).into()),
+            },
+            CommandElem::Argument(val) => res.positional.push(val.clone()),
+            _ => return Err(CommandError::IncorrectArgs(cmdlet.into()).into()),
+        }
+    }
+    Ok(res)
+}
+
+// Get-Variable and Remove-Variable accept the list of names
+fn variable_names(cmdlet: &str, args: &VariableArgs) -> ParserResult<Vec<VarName>> {
+    let (Some(names), None) = (
+        args.name.as_ref().or(args.positional.first()),
+        args.positional.get(args.name.is_none() as usize),
+    ) else {
+        return Err(CommandError::IncorrectArgs(cmdlet.into()).into());
+    };
+    Ok(names
+        .cast_to_array()
+        .iter()
+        .map(|name| {
+            VarName::new(
+                args.scope.clone(),
+                name.cast_to_string().to_ascii_lowercase(),
+            )
+        })
+        .collect())
+}
+
+fn not_found(var_name: &VarName) -> CommandError {
+    CommandError::ExecutionError(format!(
+        "Cannot find a variable with the name '{}'.",
+        var_name.name
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{NEWLINE, PowerShellSession, PsValue};
+
+    #[test]
+    fn set_and_get() {
+        let mut p = PowerShellSession::new();
+        let input = r#"Set-Variable -Name cmd -Value ("I" + "EX")
+Set-Variable count 2 -Scope Global
+(Get-Variable -Name cmd -ValueOnly) + $global:count"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(s.result(), PsValue::String("IEX2".into()));
+        assert_eq!(
+            s.deobfuscated(),
+            [r#"$cmd = "IEX""#, "$global:count = 2", r#""IEX2""#].join(NEWLINE)
+        );
+
+        let s = p.parse_input(r#"Set-Variable x 5 -PassThru"#).unwrap();
+        assert_eq!(s.result(), PsValue::Int(5));
+    }
+
+    #[test]
+    fn remove() {
+        let mut p = PowerShellSession::new();
+        let input = r#"$a = 1; Remove-Variable -Name a; Get-Variable a"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(
+            s.errors()[0].to_string(),
+            "CommandError: Cannot find a variable with the name 'a'."
+        );
+        assert_eq!(
+            s.deobfuscated(),
+            ["$a = 1", "Remove-Variable -Name a", "Get-Variable a"].join(NEWLINE)
+        );
+    }
+}
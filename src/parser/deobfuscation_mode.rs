@@ -0,0 +1,34 @@
+/// What the deobfuscated script shows for evaluated loops.
+///
+/// # Examples
+///
+/// ```rust
+/// use ps_parser::{DeobfuscationMode, PowerShellSession};
+///
+/// let script = "$s = ''; foreach ($c in 'a', 'b') { $s += $c }";
+///
+/// let mut session = PowerShellSession::new();
+/// let script_result = session.parse_input(script).unwrap();
+/// assert_eq!(
+///     script_result.deobfuscated(),
+///     "$s = \"\"\nforeach ($c in 'a', 'b') {\n    $s += $c\n}"
+/// );
+///
+/// let mut session =
+///     PowerShellSession::new().with_deobfuscation_mode(DeobfuscationMode::Aggressive);
+/// let script_result = session.parse_input(script).unwrap();
+/// assert_eq!(
+///     script_result.deobfuscated(),
+///     "$s = \"\"\n$c = \"a\"\n$s = \"a\"\n$c = \"b\"\n$s = \"ab\""
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DeobfuscationMode {
+    /// Loops are unrolled: the statements emitted by every iteration are
+    /// shown in the order they were evaluated, followed by the loop output.
+    Aggressive,
+    /// Loops keep their structure: the loop is shown as in the script, with
+    /// one statement of the body per line.
+    #[default]
+    Structural,
+}
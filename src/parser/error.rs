@@ -3,7 +3,7 @@ use std::num::ParseFloatError;
 use thiserror_no_std::Error;
 
 use super::{
-    CommandError, PestError,
+    CommandError, LimitError, PestError,
     predicates::{BitwiseError, OpError},
     value::{MethodError, RuntimeError, ValError},
     variables::VariableError,
@@ -38,11 +38,26 @@ pub enum ParserError {
     #[error("ParseFloatError: {0}")]
     ParseFloatError(ParseFloatError),
 
+    #[error("LimitExceeded: {0}")]
+    LimitExceeded(LimitError),
+
+    #[error("Exception: {0}")]
+    Exception(String),
+
+    #[error("IoError: {0}")]
+    Io(String),
+
     #[error("NotImplementedError: {0}")]
     NotImplemented(String),
 
     #[error("Skip")]
     Skip,
+
+    #[error("Break")]
+    Break,
+
+    #[error("Continue")]
+    Continue,
 }
 
 impl From<PestError> for ParserError {
@@ -93,6 +108,18 @@ impl From<RuntimeError> for ParserError {
     }
 }
 
+impl From<LimitError> for ParserError {
+    fn from(value: LimitError) -> Self {
+        Self::LimitExceeded(value)
+    }
+}
+
+impl From<std::io::Error> for ParserError {
+    fn from(value: std::io::Error) -> Self {
+        Self::Io(value.to_string())
+    }
+}
+
 impl std::error::Error for ParserError {}
 
 impl From<ParseFloatError> for ParserError {
@@ -0,0 +1,281 @@
+use thiserror_no_std::Error;
+
+use super::{ParserError, ParserResult, PowerShellSession, Val, value::PsString};
+
+#[derive(Error, Debug, PartialEq, Clone)]
+pub enum LimitError {
+    #[error("Loop exceeded the maximum number of iterations ({0})")]
+    LoopIterations(u64),
+    #[error("Maximum recursion depth ({0}) exceeded")]
+    RecursionDepth(u32),
+    #[error("Maximum number of evaluated statements ({0}) exceeded")]
+    Statements(u64),
+    #[error("Maximum depth of dynamically evaluated code ({0}) exceeded")]
+    DynamicDepth(u32),
+    #[error("Result of the multiplication exceeds the maximum size ({0})")]
+    RepeatSize(usize),
+}
+
+/// Safety limits applied while evaluating a script.
+///
+/// Malicious samples can contain infinite loops or unbounded recursion. When
+/// one of the limits is hit, evaluation stops and
+/// `ParserError::LimitExceeded` is reported instead of hanging the caller.
+///
+/// # Examples
+///
+/// ```rust
+/// use ps_parser::{ExecutionLimits, PowerShellSession};
+///
+/// let limits = ExecutionLimits {
+///     max_statements: 10,
+///     ..Default::default()
+/// };
+/// let mut session = PowerShellSession::new().with_limits(limits);
+/// let script_result = session.parse_input("1;2;3;4;5;6;7;8;9;10;11;12").unwrap();
+/// assert_eq!(script_result.errors().len(), 1);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExecutionLimits {
+    /// Maximum number of iterations of a single loop (or elements of a
+    /// range).
+    pub max_loop_iterations: u64,
+    /// Maximum nesting of script blocks, functions and sub-scripts.
+    pub max_recursion_depth: u32,
+    /// Maximum number of statements evaluated during one `parse_input` call.
+    pub max_statements: u64,
+    /// Maximum nesting of dynamically evaluated code, like `Invoke-Expression`
+    /// or script blocks invoked with `.Invoke()`.
+    pub max_dynamic_depth: u32,
+    /// Maximum length of a string or array created by multiplication, like
+    /// `"a" * 1000`.
+    pub max_repeat_size: usize,
+}
+
+impl Default for ExecutionLimits {
+    fn default() -> Self {
+        Self {
+            max_loop_iterations: 1_000_000,
+            max_recursion_depth: 64,
+            max_statements: 1_000_000,
+            max_dynamic_depth: 16,
+            max_repeat_size: 10_000_000,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub(crate) struct ExecutionCounters {
+    statements: u64,
+    depth: u32,
+    dynamic_depth: u32,
+    exceeded: Option<LimitError>,
+}
+
+impl PowerShellSession {
+    fn limit_exceeded(&mut self, err: LimitError) -> ParserError {
+        // once any limit is hit, every following statement fails as well, so the
+        // whole evaluation stops
+        self.counters.exceeded = Some(err.clone());
+        ParserError::LimitExceeded(err)
+    }
+
+    pub(crate) fn reset_counters(&mut self) {
+        if self.counters.depth == 0 {
+            self.counters = ExecutionCounters::default();
+        }
+    }
+
+    pub(crate) fn count_statement(&mut self) -> ParserResult<()> {
+        if let Some(err) = &self.counters.exceeded {
+            return Err(ParserError::LimitExceeded(err.clone()));
+        }
+
+        self.counters.statements += 1;
+        if self.counters.statements > self.limits.max_statements {
+            Err(self.limit_exceeded(LimitError::Statements(self.limits.max_statements)))?
+        }
+        Ok(())
+    }
+
+    pub(crate) fn enter_subscript(&mut self) -> ParserResult<()> {
+        if self.counters.depth >= self.limits.max_recursion_depth {
+            Err(self.limit_exceeded(LimitError::RecursionDepth(self.limits.max_recursion_depth)))?
+        }
+        self.counters.depth += 1;
+        Ok(())
+    }
+
+    pub(crate) fn leave_subscript(&mut self) {
+        self.counters.depth = self.counters.depth.saturating_sub(1);
+    }
+
+    // the top level script is the first entered subscript
+    pub(crate) fn is_nested_subscript(&self) -> bool {
+        self.counters.depth > 1
+    }
+
+    // code evaluated from strings may invoke itself, like `$c = 'iex $c'; iex
+    // $c`, so such nesting has its own, lower limit
+    pub(crate) fn enter_dynamic_eval(&mut self) -> ParserResult<()> {
+        if self.counters.dynamic_depth >= self.limits.max_dynamic_depth {
+            Err(self.limit_exceeded(LimitError::DynamicDepth(self.limits.max_dynamic_depth)))?
+        }
+        self.counters.dynamic_depth += 1;
+        Ok(())
+    }
+
+    pub(crate) fn leave_dynamic_eval(&mut self) {
+        self.counters.dynamic_depth = self.counters.dynamic_depth.saturating_sub(1);
+    }
+
+    pub(crate) fn check_loop_iterations(&mut self, iterations: u64) -> ParserResult<()> {
+        if iterations > self.limits.max_loop_iterations {
+            Err(self.limit_exceeded(LimitError::LoopIterations(self.limits.max_loop_iterations)))?
+        }
+        Ok(())
+    }
+
+    // strings and arrays multiplied by a huge count would exhaust the memory.
+    // Invalid counts are left for the multiplication to report
+    pub(crate) fn check_repeat_size(&mut self, left: &Val, right: &Val) -> ParserResult<()> {
+        let len = match left {
+            Val::String(PsString(s)) => s.len(),
+            Val::Array(v) => v.len(),
+            _ => return Ok(()),
+        };
+        let Ok(count) = right.repeat_count() else {
+            return Ok(());
+        };
+        if len
+            .checked_mul(count)
+            .is_none_or(|size| size > self.limits.max_repeat_size)
+        {
+            Err(self.limit_exceeded(LimitError::RepeatSize(self.limits.max_repeat_size)))?
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ExecutionLimits, PowerShellSession, PsValue};
+
+    #[test]
+    fn statements_limit() {
+        let limits = ExecutionLimits {
+            max_statements: 3,
+            ..Default::default()
+        };
+        let mut p = PowerShellSession::new().with_limits(limits);
+        let s = p.parse_input("$a = 1; $a = 2; $a = 3; $a = 4; $a").unwrap();
+        assert_eq!(s.errors().len(), 1);
+        assert_eq!(
+            s.errors()[0].to_string(),
+            "LimitExceeded: Maximum number of evaluated statements (3) exceeded"
+        );
+        assert_eq!(s.script_variables().get("a"), Some(&PsValue::Int(3)));
+
+        // counters are reset for every script
+        let s = p.parse_input("1; 2").unwrap();
+        assert_eq!(s.errors().len(), 0);
+    }
+
+    #[test]
+    fn recursion_limit() {
+        let mut p = PowerShellSession::new();
+        let input =
+            r#"function Recurse($x) { Recurse ($x + 1) }; $x = 1; Recurse 1; "after"; "after2""#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(
+            s.errors().last().unwrap().to_string(),
+            "LimitExceeded: Maximum recursion depth (64) exceeded"
+        );
+        // evaluation stops at the statement which hit the limit
+        assert_eq!(s.result(), PsValue::Null);
+        assert_eq!(s.output(), "");
+        assert_eq!(
+            s.deobfuscated(),
+            [
+                "function Recurse($x) { Recurse ($x + 1) }",
+                "$x = 1",
+                "Recurse 1"
+            ]
+            .join(crate::NEWLINE)
+        );
+    }
+
+    #[test]
+    fn dynamic_depth_limit() {
+        let mut p = PowerShellSession::new();
+        let input = r#"$code = 'iex $code'; iex $code; 5"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(
+            s.errors()[0].to_string(),
+            "LimitExceeded: Maximum depth of dynamically evaluated code (16) exceeded"
+        );
+        // evaluation stops at the limit
+        assert_ne!(s.result(), PsValue::Int(5));
+
+        let limits = ExecutionLimits {
+            max_dynamic_depth: 2,
+            ..Default::default()
+        };
+        let mut p = PowerShellSession::new().with_limits(limits);
+        let input =
+            r#"$s = '[scriptblock]::Create($s).Invoke()'; [scriptblock]::Create($s).Invoke()"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(
+            s.errors()[0].to_string(),
+            "LimitExceeded: Maximum depth of dynamically evaluated code (2) exceeded"
+        );
+
+        let s = p.parse_input(r#"iex "iex '1 + 1'""#).unwrap();
+        assert_eq!(s.result(), PsValue::Int(2));
+        assert_eq!(s.errors().len(), 0);
+    }
+
+    #[test]
+    fn range_limit() {
+        let limits = ExecutionLimits {
+            max_loop_iterations: 100,
+            ..Default::default()
+        };
+        let mut p = PowerShellSession::new().with_limits(limits);
+        let s = p.parse_input("$a = 1..1000").unwrap();
+        assert_eq!(
+            s.errors()[0].to_string(),
+            "LimitExceeded: Loop exceeded the maximum number of iterations (100)"
+        );
+
+        let s = p.parse_input("1..100").unwrap();
+        assert_eq!(s.errors().len(), 0);
+    }
+
+    #[test]
+    fn repeat_size_limit() {
+        let mut p = PowerShellSession::new();
+        let s = p.parse_input(r#""abc" * 9223372036854775807; 5"#).unwrap();
+        assert_eq!(
+            s.errors()[0].to_string(),
+            "LimitExceeded: Result of the multiplication exceeds the maximum size (10000000)"
+        );
+
+        let limits = ExecutionLimits {
+            max_repeat_size: 10,
+            ..Default::default()
+        };
+        let mut p = PowerShellSession::new().with_limits(limits);
+        let s = p.parse_input(r#"$a = 1, 2; $a *= 6"#).unwrap();
+        assert_eq!(
+            s.errors()[0].to_string(),
+            "LimitExceeded: Result of the multiplication exceeds the maximum size (10)"
+        );
+
+        let s = p
+            .parse_input(r#"("ab" * 5).Length; (@(1) * 10).Count"#)
+            .unwrap();
+        assert_eq!(s.errors().len(), 0);
+        assert_eq!(s.output(), "10\n10");
+    }
+}
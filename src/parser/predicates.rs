@@ -14,7 +14,7 @@ pub(crate) use comparison::ComparisonPred;
 pub(crate) use contain::ContainPred;
 pub(crate) use join::JoinPred;
 pub(crate) use logical::LogicalPred;
-pub(crate) use replace::ReplacePred;
+pub(crate) use replace::{ReplacePred, regex};
 pub(crate) use split::SplitPred;
 use thiserror_no_std::Error;
 pub(crate) use type_check::TypeCheckPred;
@@ -32,6 +32,8 @@ pub enum OpError {
     ValError(ValError),
     #[error("Unknown type: {0}")]
     UnknownType(String),
+    #[error("The regular expression pattern {0} is not valid.")]
+    InvalidRegex(String),
 }
 
 impl From<ValError> for OpError {
@@ -55,7 +57,21 @@ impl StringPred {
         }
 
         if let Some(compare) = ComparisonPred::get(name_lowercase.as_str()) {
-            return Some(Box::new(move |v1, v2| Ok(Val::Bool(compare(v1, v2)))));
+            return Some(Box::new(move |v1, v2| {
+                let Val::Array(arr) = v1 else {
+                    return Ok(Val::Bool(compare(v1, v2)));
+                };
+                // array on the left side is filtered, like with Where-Object
+                let mut filtered = arr
+                    .into_iter()
+                    .filter(|elem| compare(elem.clone(), v2.clone()))
+                    .collect::<Vec<_>>();
+                Ok(match filtered.len() {
+                    0 => Val::Null,
+                    1 => filtered.remove(0),
+                    _ => Val::Array(filtered),
+                })
+            }));
         }
 
         if let Some(replace) = ReplacePred::get(name_lowercase.as_str()) {
@@ -71,7 +87,7 @@ impl StringPred {
                 } else {
                     (v2, Val::Null)
                 };
-                Ok(Val::String(replace(v1, from, to).into()))
+                Ok(Val::String(replace(v1, from, to)?.into()))
             }));
         }
 
@@ -206,13 +206,11 @@ mod tests {
             PowerShellSession::new().safe_eval(r#" 8%" 16 " "#).unwrap(),
             "8".to_string()
         );
-        //assert_eq!(PowerShellParser::new().safe_eval(r#" " 8 "% 0.3
-        // "#).unwrap(), "0.2".to_string());
         assert_eq!(
             PowerShellSession::new()
                 .safe_eval(r#" " 8 "% 0.3 "#)
                 .unwrap(),
-            "0.2000000000000003".to_string()
+            "0.2".to_string()
         );
         assert_eq!(
             PowerShellSession::new()
@@ -43,12 +43,16 @@ impl BitwisePred {
     }
 }
 
-pub fn prepare(a: Val, b: Val) -> BitwiseResult<(i64, i64)> {
-    if let Val::RuntimeObject(a) = a {
-        return Err(BitwiseError::NotDefined(
-            "band".into(),
-            a.type_definition().unwrap_or_default(),
-        ));
+// both operands have to be integers, types (runtime objects) don't have the
+// integer representation
+pub fn prepare(op: &str, a: Val, b: Val) -> BitwiseResult<(i64, i64)> {
+    for val in [&a, &b] {
+        if let Val::RuntimeObject(object) = val {
+            return Err(BitwiseError::NotDefined(
+                op.into(),
+                object.type_definition().unwrap_or_default(),
+            ));
+        }
     }
     Ok((a.cast_to_int()?, b.cast_to_int()?))
 }
@@ -58,7 +62,7 @@ fn band_imp(a: i64, b: i64) -> i64 {
 }
 
 pub fn band(a: Val, b: Val) -> BitwiseResult<Val> {
-    let (a, b) = prepare(a, b)?;
+    let (a, b) = prepare("band", a, b)?;
     let res = band_imp(a, b);
     Ok(Val::Int(res))
 }
@@ -68,7 +72,7 @@ fn bor_imp(a: i64, b: i64) -> i64 {
 }
 
 pub fn bor(a: Val, b: Val) -> BitwiseResult<Val> {
-    let (a, b) = prepare(a, b)?;
+    let (a, b) = prepare("bor", a, b)?;
     let res = bor_imp(a, b);
     Ok(Val::Int(res))
 }
@@ -78,27 +82,35 @@ fn bxor_imp(a: i64, b: i64) -> i64 {
 }
 
 pub fn bxor(a: Val, b: Val) -> BitwiseResult<Val> {
-    let (a, b) = prepare(a, b)?;
+    let (a, b) = prepare("bxor", a, b)?;
     let res = bxor_imp(a, b);
     Ok(Val::Int(res))
 }
 
+// like in .NET, the shift count is masked by the width of the operand, 5 bits
+// for Int32 and 6 bits for Int64, so large counts don't overflow
 fn shl_imp(a: i64, b: i64) -> i64 {
-    a << b
+    match i32::try_from(a) {
+        Ok(a) => a.wrapping_shl(b as u32) as i64,
+        Err(_) => a.wrapping_shl(b as u32),
+    }
 }
 
 pub fn shl(a: Val, b: Val) -> BitwiseResult<Val> {
-    let (a, b) = prepare(a, b)?;
+    let (a, b) = prepare("shl", a, b)?;
     let res = shl_imp(a, b);
     Ok(Val::Int(res))
 }
 
 fn shr_imp(a: i64, b: i64) -> i64 {
-    a >> b
+    match i32::try_from(a) {
+        Ok(a) => a.wrapping_shr(b as u32) as i64,
+        Err(_) => a.wrapping_shr(b as u32),
+    }
 }
 
 pub fn shr(a: Val, b: Val) -> BitwiseResult<Val> {
-    let (a, b) = prepare(a, b)?;
+    let (a, b) = prepare("shr", a, b)?;
     let res = shr_imp(a, b);
     Ok(Val::Int(res))
 }
@@ -115,6 +127,22 @@ mod tests {
         assert_eq!(p.safe_eval(r#" 5 -Band 9 "#).unwrap(), "1".to_string());
     }
 
+    #[test]
+    fn test_band_bor_bxor() {
+        let mut p = PowerShellSession::new();
+        assert_eq!(p.safe_eval(r#" 5 -band 3 "#).unwrap(), "1".to_string());
+        assert_eq!(p.safe_eval(r#" 5 -bor 2 "#).unwrap(), "7".to_string());
+        assert_eq!(p.safe_eval(r#" 5 -bxor 1 "#).unwrap(), "4".to_string());
+
+        for (input, op) in [("[int] -band 1", "band"), ("1 -bor [int]", "bor")] {
+            let s = p.parse_input(input).unwrap();
+            assert_eq!(
+                s.errors()[0].to_string(),
+                format!("BitwiseError: {op} not defined for Int")
+            );
+        }
+    }
+
     #[test]
     fn test_bor() {
         let mut p = PowerShellSession::new();
@@ -179,6 +207,27 @@ mod tests {
             p.safe_eval(r#" "96.5as" -shr 3.5 "#).unwrap(),
             "".to_string()
         );
+        assert_eq!(p.safe_eval(r#" 8 -shr 1 "#).unwrap(), "4".to_string());
+        assert_eq!(p.safe_eval(r#" 8 -shr 65 "#).unwrap(), "4".to_string());
+        assert_eq!(p.safe_eval(r#" 1 -shl 64 "#).unwrap(), "1".to_string());
+        assert_eq!(p.safe_eval(r#" 1 -shl 32 "#).unwrap(), "1".to_string());
+        assert_eq!(
+            p.safe_eval(r#" 1 -shl 31 "#).unwrap(),
+            "-2147483648".to_string()
+        );
+        assert_eq!(p.safe_eval(r#" 8 -shr 33 "#).unwrap(), "4".to_string());
+        assert_eq!(
+            p.safe_eval(r#" 4294967296 -shl 65 "#).unwrap(),
+            "8589934592".to_string()
+        );
+
+        for (input, op) in [("[string] -shr 1", "shr"), ("1 -shl [string]", "shl")] {
+            let s = p.parse_input(input).unwrap();
+            assert_eq!(
+                s.errors()[0].to_string(),
+                format!("BitwiseError: {op} not defined for String")
+            );
+        }
     }
 
     #[test]
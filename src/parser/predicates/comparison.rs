@@ -214,7 +214,8 @@ fn wildcard_to_regex(pattern: &str, case_insensitive: bool) -> String {
 
 #[cfg(test)]
 mod tests {
-    use crate::PowerShellSession;
+    use super::{Val, ceq, ieq, ine};
+    use crate::{PowerShellSession, PsValue};
 
     #[test]
     fn test_eq() {
@@ -223,6 +224,8 @@ mod tests {
         assert_eq!(p.safe_eval("1 -eq 2").unwrap(), "False".to_string());
         assert_eq!(p.safe_eval("\"1\" -ieq 1").unwrap(), "True".to_string());
         assert_eq!(p.safe_eval("\"A\" -ieq \"a\"").unwrap(), "True".to_string());
+        assert_eq!(p.safe_eval("'A' -eq 'a'").unwrap(), "True".to_string());
+        assert_eq!(p.safe_eval("'A' -ceq 'a'").unwrap(), "False".to_string());
         assert_eq!(
             p.safe_eval("\"A\" -ceq \"a\"").unwrap(),
             "False".to_string()
@@ -235,6 +238,76 @@ mod tests {
         assert_eq!(p.safe_eval("\"A\" -cne \"a\"").unwrap(), "True".to_string());
     }
 
+    #[test]
+    fn test_eq_script_text() {
+        let mut p = PowerShellSession::new();
+        // unresolved command is kept as its source text
+        let input = r#"$cmd = Invoke-Unknown -Name 1; $cmd -eq "Invoke-Unknown -Name 1""#;
+        assert_eq!(p.safe_eval(input).unwrap(), "True".to_string());
+        let input = r#"$cmd = Invoke-Unknown -Name 1; $cmd -ceq "invoke-unknown -name 1""#;
+        assert_eq!(p.safe_eval(input).unwrap(), "False".to_string());
+        let input = r#"$cmd = Invoke-Unknown; $cmd -ne "Invoke-Other""#;
+        assert_eq!(p.safe_eval(input).unwrap(), "True".to_string());
+    }
+
+    #[test]
+    fn test_eq_assignment_result() {
+        let mut p = PowerShellSession::new();
+        // value of the parenthesized assignment is not displayed, but still
+        // takes part in comparisons
+        assert_eq!(p.safe_eval("($x = 5) -eq 5").unwrap(), "True".to_string());
+        assert_eq!(p.safe_eval("($x = 3) -eq 4").unwrap(), "False".to_string());
+        assert_eq!(p.safe_eval("($x = 3) -ne 4").unwrap(), "True".to_string());
+        assert_eq!(
+            p.safe_eval(r#"($s = "A") -eq "a""#).unwrap(),
+            "True".to_string()
+        );
+        assert_eq!(p.safe_eval("($x = 3) -lt 4").unwrap(), "True".to_string());
+        assert_eq!(p.safe_eval("($x = 3) -gt 4").unwrap(), "False".to_string());
+
+        let assigned = |v| Val::NonDisplayed(Box::new(v));
+        assert!(ieq(assigned(Val::Int(5)), Val::Int(5)));
+        assert!(ieq(Val::Int(5), assigned(Val::Int(5))));
+        assert!(ine(assigned(Val::Int(3)), Val::Int(4)));
+        assert!(ieq(
+            assigned(Val::String("A".into())),
+            Val::String("a".into())
+        ));
+        assert!(!ceq(
+            assigned(Val::String("A".into())),
+            Val::String("a".into())
+        ));
+    }
+
+    #[test]
+    fn test_array_filter() {
+        let mut p = PowerShellSession::new();
+        let s = p.parse_input("@(1,2,3,4) -gt 2").unwrap();
+        assert_eq!(
+            s.result(),
+            PsValue::Array(vec![PsValue::Int(3), PsValue::Int(4)])
+        );
+        assert_eq!(s.deobfuscated(), "@(3,4)");
+
+        for (input, expected) in [
+            ("@(1,2,3,4) -ge 3", "3\n4"),
+            ("@(1,2,3,4) -lt 2", "1"),
+            ("@(1,2,3,4) -le 2", "1\n2"),
+            ("@(1,2,1) -ne 1", "2"),
+            (r#""a", "B", "A" -eq "a""#, "a\nA"),
+            (r#""a", "B", "A" -ceq "a""#, "a"),
+            (r#""ab", "cd", "ae" -like "a*""#, "ab\nae"),
+        ] {
+            assert_eq!(p.safe_eval(input).unwrap(), expected.to_string(), "{input}");
+        }
+
+        // like Where-Object, no match gives $null
+        let s = p.parse_input("(1, 2) -eq 5").unwrap();
+        assert_eq!(s.result(), PsValue::Null);
+        let input = r#"if ((1, 2, 3) -eq 7) { "yes" } else { "no" }"#;
+        assert_eq!(p.safe_eval(input).unwrap(), "no".to_string());
+    }
+
     #[test]
     fn test_gt() {
         let mut p = PowerShellSession::new();
@@ -39,7 +39,7 @@ pub fn join(input: Val, delimeter: Val) -> String {
 
 #[cfg(test)]
 mod tests {
-    use crate::PowerShellSession;
+    use crate::{NEWLINE, PowerShellSession};
 
     #[test]
     fn test_join() {
@@ -106,4 +106,29 @@ mod tests {
             "xy".to_string()
         );
     }
+
+    #[test]
+    fn char_array_reconstruction() {
+        // joined characters are deobfuscated to the string literal
+        let input = r#"$t = (65,109,115,105 | %{[char]$_}) -join ''
+$u = [string]::Join('', [char[]]@(0x55, 0x74, 0x69, 0x6c, 0x73))
+$c = -join [char[]]'Context'
+$n = [string]::new('Init'.ToCharArray())
+"System.Management.Automation.$t$u"
+"$($t.ToLower())$c", $n"#;
+        let script_res = PowerShellSession::new().parse_input(input).unwrap();
+        assert_eq!(
+            script_res.deobfuscated(),
+            [
+                r#"$t = "Amsi""#,
+                r#"$u = "Utils""#,
+                r#"$c = "Context""#,
+                r#"$n = "Init""#,
+                r#""System.Management.Automation.AmsiUtils""#,
+                r#"@("amsiContext","Init")"#,
+            ]
+            .join(NEWLINE)
+        );
+        assert!(script_res.errors().is_empty());
+    }
 }
@@ -31,7 +31,7 @@ pub fn xor(a: Val, b: Val) -> bool {
 
 #[cfg(test)]
 mod tests {
-    use crate::PowerShellSession;
+    use crate::{PowerShellSession, PsValue};
 
     #[test]
     fn test_and() {
@@ -125,4 +125,27 @@ mod tests {
             "False".to_string()
         );
     }
+
+    #[test]
+    fn test_not_types() {
+        let mut p = PowerShellSession::new();
+        for (input, expected) in [
+            ("-not $false", true),
+            ("!$true", false),
+            ("!!1", true),
+            ("-not $null", true),
+            ("-not 0.0", true),
+            ("-not 'False'", false),
+            ("-not @()", true),
+            ("-not @(1, 2)", false),
+            ("-not @(0)", true),
+            ("!@($false)", true),
+            ("-not @(0, 0)", false),
+            ("-not @{}", false),
+            ("!@{a = 1}", false),
+        ] {
+            let s = p.parse_input(input).unwrap();
+            assert_eq!(s.result(), PsValue::Bool(expected), "{input}");
+        }
+    }
 }
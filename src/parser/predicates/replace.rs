@@ -1,10 +1,10 @@
 use std::{collections::HashMap, sync::LazyLock};
 
-use regex::Regex;
+use regex::{Regex, RegexBuilder};
 
-use super::Val;
+use super::{OpError, OpResult, Val};
 
-pub(crate) type ReplacePredType = fn(Val, Val, Val) -> String;
+pub(crate) type ReplacePredType = fn(Val, Val, Val) -> OpResult<String>;
 
 pub(crate) struct ReplacePred;
 
@@ -23,29 +23,30 @@ impl ReplacePred {
     }
 }
 
-pub fn ireplace(input: Val, pattern: Val, replacement: Val) -> String {
-    let ci_pattern = format!("(?i){}", pattern.cast_to_string()); // make regex case-insensitive
-    match Regex::new(&ci_pattern) {
-        Ok(re) => re
-            .replace_all(
-                input.cast_to_string().as_str(),
-                replacement.cast_to_string(),
-            )
-            .to_string(),
-        Err(_) => input.cast_to_string(),
-    }
+// the pattern is reported as it was given, without the case-insensitive flag
+pub(crate) fn regex(pattern: &str, case_sensitive: bool) -> OpResult<Regex> {
+    RegexBuilder::new(pattern)
+        .case_insensitive(!case_sensitive)
+        .build()
+        .map_err(|_| OpError::InvalidRegex(pattern.to_string()))
 }
 
-fn creplace(input: Val, pattern: Val, replacement: Val) -> String {
-    match Regex::new(pattern.cast_to_string().as_str()) {
-        Ok(re) => re
-            .replace_all(
-                input.cast_to_string().as_str(),
-                replacement.cast_to_string(),
-            )
-            .to_string(),
-        Err(_) => input.cast_to_string(), // fallback: return input unchanged on invalid regex
-    }
+pub fn ireplace(input: Val, pattern: Val, replacement: Val) -> OpResult<String> {
+    replace(input, pattern, replacement, false)
+}
+
+fn creplace(input: Val, pattern: Val, replacement: Val) -> OpResult<String> {
+    replace(input, pattern, replacement, true)
+}
+
+fn replace(input: Val, pattern: Val, replacement: Val, case_sensitive: bool) -> OpResult<String> {
+    let re = regex(&pattern.cast_to_string(), case_sensitive)?;
+    Ok(re
+        .replace_all(
+            input.cast_to_string().as_str(),
+            replacement.cast_to_string(),
+        )
+        .to_string())
 }
 
 #[cfg(test)]
@@ -113,4 +114,80 @@ mod tests {
             "96".to_string()
         );
     }
+
+    #[test]
+    fn test_replace_no_match() {
+        let mut p = PowerShellSession::new();
+        assert_eq!(
+            p.safe_eval(r#" "abc" -replace 'z','y' "#).unwrap(),
+            "abc".to_string()
+        );
+        assert_eq!(
+            p.safe_eval(r#" "abc" -creplace 'B','y' "#).unwrap(),
+            "abc".to_string()
+        );
+        // empty pattern matches at every position, like in .NET
+        assert_eq!(
+            p.safe_eval(r#" "abc" -replace '','-' "#).unwrap(),
+            "-a-b-c-".to_string()
+        );
+        // invalid regex is an error
+        let s = p.parse_input(r#" "a(c" -replace '(','y' "#).unwrap();
+        assert_eq!(
+            s.errors()[0].to_string(),
+            "OperatorError: The regular expression pattern ( is not valid."
+        );
+    }
+
+    #[test]
+    fn test_replace_script_block() {
+        let mut p = PowerShellSession::new();
+        assert_eq!(
+            p.safe_eval(r#" "hello world" -replace 'o(.)', { $_.Value.ToUpper() } "#)
+                .unwrap(),
+            "hellO wORld".to_string()
+        );
+        assert_eq!(
+            p.safe_eval(r#" "a1B22" -creplace '\d+|b', { $_.Groups[0].Length } "#)
+                .unwrap(),
+            "a1B2".to_string()
+        );
+        assert_eq!(
+            p.safe_eval(
+                r#" "key=val" -replace '(?<k>\w+)=(\w+)', { "$($Matches.k):$($Matches[2])" } "#
+            )
+            .unwrap(),
+            "key:val".to_string()
+        );
+        // failing script block leaves the matched text
+        let s = p
+            .parse_input(r#" "abc" -replace 'b', { throw "x" } "#)
+            .unwrap();
+        assert_eq!(s.result(), crate::PsValue::String("abc".into()));
+
+        // $_ and $Matches of the caller are kept
+        let s = p
+            .parse_input(
+                r#""ab","cd" | % { $x = $_ -replace "[ac]", { "X" }; "$x/$_" }
+$Matches = "kept"; $y = "z" -replace "z", { $Matches[0] }; $y; $Matches"#,
+            )
+            .unwrap();
+        assert_eq!(
+            s.output(),
+            ["Xb/ab", "Xd/cd", "z", "kept"].join(crate::NEWLINE)
+        );
+
+        // indexes are in chars
+        assert_eq!(
+            p.safe_eval(r#" "żółw x" -replace 'x', { "$($_.Index),$($_.Length)" } "#)
+                .unwrap(),
+            "żółw 5,1".to_string()
+        );
+
+        let s = p.parse_input(r#" "a(c" -replace '(', { "y" } "#).unwrap();
+        assert_eq!(
+            s.errors()[0].to_string(),
+            "OperatorError: The regular expression pattern ( is not valid."
+        );
+    }
 }
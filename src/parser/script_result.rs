@@ -1,12 +1,16 @@
 use std::{collections::HashMap, fmt::Display};
 
-use super::{ParserError, Tokens, Val as InternalVal};
+use super::{CommandToken, ParserError, Tokens, Val as InternalVal};
 use crate::{
     NEWLINE,
-    parser::{StreamMessage, value::PsString},
+    parser::{
+        PowerShellStream, StreamMessage,
+        value::{PsString, ScriptBlock},
+    },
 };
 
 #[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
 pub enum PsValue {
     Null,
     Bool(bool),
@@ -16,6 +20,27 @@ pub enum PsValue {
     String(String),
     Array(Vec<PsValue>),
     HashTable(HashMap<String, PsValue>),
+    ScriptBlock(PsScriptBlock),
+}
+
+/// Script block returned by the script. It keeps the parameters of the block,
+/// so converting it back to the session value loses nothing
+#[derive(Debug, Clone)]
+pub struct PsScriptBlock(ScriptBlock);
+
+impl PsScriptBlock {
+    /// Returns the source text of the block, without the braces.
+    pub fn source(&self) -> &str {
+        &self.0.raw_text
+    }
+}
+
+impl PartialEq for PsScriptBlock {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.raw_text == other.0.raw_text
+            && self.0.body == other.0.body
+            && self.0.params.0 == other.0.params.0
+    }
 }
 
 impl core::fmt::Display for PsString {
@@ -32,11 +57,36 @@ impl PsValue {
             PsValue::Float(f) => *f != 0.0,
             PsValue::Char(c) => *c != 0,
             PsValue::String(s) => !s.is_empty(),
-            PsValue::Array(arr) => !arr.is_empty(),
-            PsValue::HashTable(hash) => !hash.is_empty(),
+            PsValue::Array(arr) => match arr.as_slice() {
+                [] => false,
+                [elem] => elem.is_true(),
+                _ => true,
+            },
+            PsValue::HashTable(_) | PsValue::ScriptBlock(_) => true,
             PsValue::Null => false,
         }
     }
+
+    /// Returns the source text of a script block value.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ps_parser::PowerShellSession;
+    ///
+    /// let mut session = PowerShellSession::new();
+    /// let script_result = session.parse_input("$sb = { Write-Output 'hi' }; $sb").unwrap();
+    /// assert_eq!(
+    ///     script_result.result().script_block_source(),
+    ///     Some("Write-Output 'hi' ")
+    /// );
+    /// ```
+    pub fn script_block_source(&self) -> Option<&str> {
+        match self {
+            PsValue::ScriptBlock(sb) => Some(sb.source()),
+            _ => None,
+        }
+    }
 }
 impl From<char> for PsValue {
     fn from(c: char) -> Self {
@@ -51,6 +101,33 @@ impl Display for PsValue {
     }
 }
 
+// values are converted like by ConvertTo-Json, hashtables become objects and
+// values without a JSON counterpart, like script blocks, chars or NaN, become
+// strings
+#[cfg(feature = "serde")]
+impl From<PsValue> for serde_json::Value {
+    fn from(val: PsValue) -> Self {
+        match val {
+            PsValue::Null => serde_json::Value::Null,
+            PsValue::Bool(b) => serde_json::Value::Bool(b),
+            PsValue::Int(i) => serde_json::Value::from(i),
+            PsValue::Float(f) => serde_json::Number::from_f64(f)
+                .map(serde_json::Value::Number)
+                .unwrap_or_else(|| serde_json::Value::String(PsValue::Float(f).to_string())),
+            PsValue::Char(c) => {
+                serde_json::Value::String(char::from_u32(c).unwrap_or_default().to_string())
+            }
+            PsValue::String(s) => serde_json::Value::String(s),
+            PsValue::ScriptBlock(sb) => serde_json::Value::String(sb.source().to_string()),
+            PsValue::Array(arr) => arr.into_iter().map(serde_json::Value::from).collect(),
+            PsValue::HashTable(hash) => hash
+                .into_iter()
+                .map(|(k, v)| (k, serde_json::Value::from(v)))
+                .collect(),
+        }
+    }
+}
+
 impl From<PsValue> for InternalVal {
     fn from(val: PsValue) -> Self {
         match val {
@@ -68,6 +145,7 @@ impl From<PsValue> for InternalVal {
                     .map(|(k, v)| (k.clone(), v.clone().into()))
                     .collect(),
             ),
+            PsValue::ScriptBlock(PsScriptBlock(sb)) => InternalVal::ScriptBlock(sb),
         }
     }
 }
@@ -89,8 +167,14 @@ impl From<InternalVal> for PsValue {
                     .map(|(k, v)| (k.clone(), v.clone().into()))
                     .collect(),
             ),
-            InternalVal::RuntimeObject(obj) => PsValue::String(obj.name()),
-            InternalVal::ScriptBlock(sb) => PsValue::String(sb.raw_text),
+            InternalVal::RuntimeObject(obj) => match obj.properties() {
+                // objects with named properties, like pscustomobject
+                Some(properties) => {
+                    PsValue::HashTable(properties.into_iter().map(|(k, v)| (k, v.into())).collect())
+                }
+                None => PsValue::String(obj.cast_to_string()),
+            },
+            InternalVal::ScriptBlock(sb) => PsValue::ScriptBlock(PsScriptBlock(sb)),
             InternalVal::ScriptText(st) => PsValue::String(st.clone()),
             InternalVal::NonDisplayed(box_val) => (*box_val).into(),
         }
@@ -100,9 +184,10 @@ impl From<InternalVal> for PsValue {
 #[derive(Debug)]
 pub struct ScriptResult {
     result: PsValue,
-    stream: Vec<String>,
+    stream: Vec<StreamMessage>,
     evaluated_statements: Vec<String>,
     tokens: Tokens,
+    attempted_commands: Vec<CommandToken>,
     errors: Vec<ParserError>,
     script_values: HashMap<String, PsValue>,
 }
@@ -118,11 +203,9 @@ impl ScriptResult {
     ) -> Self {
         Self {
             result: result.into(),
-            stream: stream
-                .iter()
-                .map(|msg| msg.to_string())
-                .collect::<Vec<String>>(),
+            stream,
             evaluated_statements,
+            attempted_commands: tokens.commands(),
             tokens,
             errors,
             script_values,
@@ -145,16 +228,68 @@ impl ScriptResult {
         self.tokens.clone()
     }
 
+    /// Every command the script tried to run, with its resolved arguments, in
+    /// the order of execution. Commands which are not implemented or failed
+    /// are included too, so it's an inventory of the script's actions.
+    pub fn attempted_commands(&self) -> &[CommandToken] {
+        &self.attempted_commands
+    }
+
     pub fn errors(&self) -> Vec<ParserError> {
         self.errors.clone()
     }
 
     pub fn output(&self) -> String {
-        self.stream.join(NEWLINE)
+        self.output_lines().join(NEWLINE)
     }
 
     pub fn output_lines(&self) -> Vec<String> {
-        self.stream.clone()
+        self.stream.iter().map(|msg| msg.to_string()).collect()
+    }
+
+    /// Output messages in the order they were written, each tagged with the
+    /// stream it was written to.
+    pub fn output_messages(&self) -> &[StreamMessage] {
+        &self.stream
+    }
+
+    /// Messages written to the success stream, i.e. the regular output.
+    pub fn success_stream(&self) -> Vec<String> {
+        self.stream_messages(PowerShellStream::Success)
+    }
+
+    /// Messages written to the error stream, e.g. by `Write-Error`.
+    pub fn errors_stream(&self) -> Vec<String> {
+        self.stream_messages(PowerShellStream::Error)
+    }
+
+    /// Messages written to the warning stream by `Write-Warning`.
+    pub fn warnings(&self) -> Vec<String> {
+        self.stream_messages(PowerShellStream::Warning)
+    }
+
+    /// Messages written to the verbose stream by `Write-Verbose`.
+    pub fn verbose(&self) -> Vec<String> {
+        self.stream_messages(PowerShellStream::Verbose)
+    }
+
+    /// Messages written to the debug stream by `Write-Debug`.
+    pub fn debug(&self) -> Vec<String> {
+        self.stream_messages(PowerShellStream::Debug)
+    }
+
+    /// Messages written to the information stream, e.g. by `Write-Host` or
+    /// `Write-Information`.
+    pub fn information(&self) -> Vec<String> {
+        self.stream_messages(PowerShellStream::Information)
+    }
+
+    fn stream_messages(&self, stream: PowerShellStream) -> Vec<String> {
+        self.stream
+            .iter()
+            .filter(|msg| msg.stream == stream)
+            .map(|msg| msg.content.clone())
+            .collect()
     }
 
     pub fn script_variables(&self) -> HashMap<String, PsValue> {
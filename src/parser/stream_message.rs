@@ -1,12 +1,14 @@
 use std::fmt::Display;
 
-// PowerShell-like output streams
+/// PowerShell output stream, which a message was written to.
 #[derive(Debug, Clone, PartialEq)]
 pub enum PowerShellStream {
-    Success, // Stream 1 - regular output
-    Error,   // Stream 2 - errors
-    Warning, // Stream 3 - warnings
-    Verbose, // Stream 4 - verbose messages
+    Success,     // Stream 1 - regular output
+    Error,       // Stream 2 - errors
+    Warning,     // Stream 3 - warnings
+    Verbose,     // Stream 4 - verbose messages
+    Debug,       // Stream 5 - debug messages
+    Information, // Stream 6 - Write-Host and Write-Information
 }
 
 impl Display for PowerShellStream {
@@ -16,11 +18,14 @@ impl Display for PowerShellStream {
             PowerShellStream::Error => "ERROR",
             PowerShellStream::Warning => "WARNING",
             PowerShellStream::Verbose => "VERBOSE",
+            PowerShellStream::Debug => "DEBUG",
+            PowerShellStream::Information => "INFORMATION",
         };
         write!(f, "{}", msg)
     }
 }
 
+/// Single message of the script output.
 #[derive(Debug, Clone)]
 pub struct StreamMessage {
     pub content: String,
@@ -40,21 +45,15 @@ impl From<StreamMessage> for String {
     }
 }
 
+// messages are displayed like in the console, warnings, errors, verbose and
+// debug messages are prefixed with the stream name
 impl Display for StreamMessage {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        if self.stream == PowerShellStream::Success {
-            write!(f, "{}", self.content)
-        } else {
-            write!(
-                f,
-                "[{}] {}: {}",
-                self.stream,
-                self.timestamp
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .unwrap()
-                    .as_secs(),
-                self.content
-            )
+        match self.stream {
+            PowerShellStream::Success | PowerShellStream::Information => {
+                write!(f, "{}", self.content)
+            }
+            _ => write!(f, "{}: {}", self.stream, self.content),
         }
     }
 }
@@ -64,35 +63,35 @@ impl StreamMessage {
         self.content.is_empty()
     }
 
-    pub fn success(content: String) -> Self {
+    pub fn new(content: String, stream: PowerShellStream) -> Self {
         StreamMessage {
             content,
-            stream: PowerShellStream::Success,
+            stream,
             timestamp: std::time::SystemTime::now(),
         }
     }
 
+    pub fn success(content: String) -> Self {
+        Self::new(content, PowerShellStream::Success)
+    }
+
     pub fn warning(message: String) -> Self {
-        StreamMessage {
-            content: format!("WARNING: {}", message),
-            stream: PowerShellStream::Warning,
-            timestamp: std::time::SystemTime::now(),
-        }
+        Self::new(message, PowerShellStream::Warning)
     }
 
     pub fn error(message: String) -> Self {
-        StreamMessage {
-            content: format!("ERROR: {}", message),
-            stream: PowerShellStream::Error,
-            timestamp: std::time::SystemTime::now(),
-        }
+        Self::new(message, PowerShellStream::Error)
     }
 
     pub fn verbose(message: String) -> Self {
-        StreamMessage {
-            content: format!("VERBOSE: {}", message),
-            stream: PowerShellStream::Verbose,
-            timestamp: std::time::SystemTime::now(),
-        }
+        Self::new(message, PowerShellStream::Verbose)
+    }
+
+    pub fn debug(message: String) -> Self {
+        Self::new(message, PowerShellStream::Debug)
+    }
+
+    pub fn information(message: String) -> Self {
+        Self::new(message, PowerShellStream::Information)
     }
 }
@@ -88,6 +88,14 @@ impl ExpressionToken {
     pub fn new(token: String, value: PsValue) -> Self {
         Self { token, value }
     }
+
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+
+    pub fn value(&self) -> &PsValue {
+        &self.value
+    }
 }
 
 /// Represents a double-quoted PowerShell string with variable expansion.
@@ -225,6 +233,21 @@ impl Tokens {
             .collect()
     }
 
+    /// Returns the expression tokens whose source contains `needle`, ignoring
+    /// case, e.g. to find where `FromBase64String` is referenced.
+    pub fn find_expressions_containing(&self, needle: &str) -> Vec<&ExpressionToken> {
+        let needle = needle.to_lowercase();
+        self.0
+            .iter()
+            .filter_map(|token| match token {
+                Token::Expression(expr) if expr.token.to_lowercase().contains(&needle) => {
+                    Some(expr)
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
     pub fn methods(&self) -> Vec<MethodToken> {
         self.0
             .iter()
@@ -1,11 +1,24 @@
+mod command_info;
+mod input_enumerator;
+mod member;
 mod method_error;
+mod object_placeholder;
 mod params;
+mod primitive_type;
+mod ps_cmdlet;
+mod ps_custom_object;
 mod ps_string;
 mod runtime_object;
 mod script_block;
 mod system_convert;
+mod system_datetime;
 mod system_encoding;
+mod system_guid;
+mod system_math;
+mod system_timespan;
+mod system_version;
 mod type_info;
+mod type_object;
 mod val_error;
 use std::{
     collections::{BTreeMap, HashMap},
@@ -14,24 +27,39 @@ use std::{
     sync::LazyLock,
 };
 
+pub(crate) use command_info::{CommandInfo, CommandType};
+use input_enumerator::InputEnumerator;
+pub(crate) use member::Member;
 pub(crate) use method_error::{MethodError, MethodResult};
-pub(crate) use params::Param;
-pub(crate) use ps_string::PsString;
+pub(crate) use object_placeholder::ObjectPlaceholder;
+pub(crate) use params::{Param, Params};
+use primitive_type::PrimitiveType;
+use ps_cmdlet::PsCmdlet;
+pub(crate) use ps_custom_object::PsCustomObject;
 use ps_string::str_cmp;
+pub(crate) use ps_string::{PsString, format_with_vec};
 pub(crate) use runtime_object::RuntimeError;
 pub(super) use runtime_object::RuntimeObject;
 use runtime_object::{MethodCallType, StaticFnCallType};
 pub(crate) use script_block::ScriptBlock;
 use smart_default::SmartDefault;
 use system_convert::Convert;
+pub(crate) use system_datetime::{DateTime, DateTimeType};
 use system_encoding::Encoding;
+pub(crate) use system_guid::{Guid, GuidType};
+use system_math::{Math, MidpointRounding, Type};
+pub(crate) use system_timespan::TimeSpan;
+use system_timespan::TimeSpanType;
+pub(crate) use system_version::Version;
+use system_version::VersionType;
 pub(super) use type_info::TypeError;
 use type_info::TypeInfoTrait;
+use type_object::TypeObject;
 pub(crate) use val_error::ValError;
 pub type ValResult<T> = core::result::Result<T, ValError>;
 use runtime_object::RuntimeResult;
 
-use crate::{NEWLINE, parser::value::system_encoding::UnicodeEncoding};
+use crate::{NEWLINE, parser::value::system_encoding::TextEncoding};
 
 #[derive(PartialEq, Debug, SmartDefault, Clone)]
 pub enum ValType {
@@ -61,8 +89,22 @@ impl ValType {
     fn static_objects(name: &str) -> ValResult<Box<dyn RuntimeObject>> {
         Ok(match name.to_ascii_lowercase().as_str() {
             "system.convert" => Box::new(CONVERT) as _,
+            "system.math" => Box::new(Math {}) as _,
+            Type::TYPE_NAME => Box::new(Type {}) as _,
+            "system.midpointrounding" => Box::new(MidpointRounding::ToEven) as _,
+            DateTimeType::TYPE_NAME => Box::new(DateTimeType {}) as _,
+            GuidType::TYPE_NAME => Box::new(GuidType {}) as _,
+            TimeSpanType::TYPE_NAME => Box::new(TimeSpanType {}) as _,
+            VersionType::TYPE_NAME => Box::new(VersionType {}) as _,
+            PsCustomObject::TYPE_NAME => Box::new(PsCustomObject::default()) as _,
             "system.text.encoding" => Box::new(Encoding {}) as _,
-            "system.text.encoding::unicode" => Box::new(UnicodeEncoding {}) as _,
+            "system.text.encoding::unicode" | "system.text.unicodeencoding" => {
+                Box::new(TextEncoding::Unicode) as _
+            }
+            "system.text.bigendianunicodeencoding" => Box::new(TextEncoding::BigEndianUnicode) as _,
+            "system.text.utf8encoding" => Box::new(TextEncoding::Utf8) as _,
+            "system.text.utf7encoding" => Box::new(TextEncoding::Utf7) as _,
+            "system.text.asciiencoding" => Box::new(TextEncoding::Ascii) as _,
             _ => Err(ValError::UnknownType(name.to_string()))?,
         })
     }
@@ -70,10 +112,44 @@ impl ValType {
         LazyLock::new(|| {
             HashMap::from([
                 ("system.convert", Box::new(CONVERT) as _),
+                ("system.math", Box::new(Math {}) as _),
+                (Type::TYPE_NAME, Box::new(Type {}) as _),
+                (
+                    "system.midpointrounding",
+                    Box::new(MidpointRounding::ToEven) as _,
+                ),
+                (DateTimeType::TYPE_NAME, Box::new(DateTimeType {}) as _),
+                (GuidType::TYPE_NAME, Box::new(GuidType {}) as _),
+                (TimeSpanType::TYPE_NAME, Box::new(TimeSpanType {}) as _),
+                (VersionType::TYPE_NAME, Box::new(VersionType {}) as _),
+                (
+                    PsCustomObject::TYPE_NAME,
+                    Box::new(PsCustomObject::default()) as _,
+                ),
                 ("system.text.encoding", Box::new(Encoding {}) as _),
                 (
                     "system.text.encoding::unicode",
-                    Box::new(UnicodeEncoding {}) as _,
+                    Box::new(TextEncoding::Unicode) as _,
+                ),
+                (
+                    "system.text.unicodeencoding",
+                    Box::new(TextEncoding::Unicode) as _,
+                ),
+                (
+                    "system.text.bigendianunicodeencoding",
+                    Box::new(TextEncoding::BigEndianUnicode) as _,
+                ),
+                (
+                    "system.text.utf8encoding",
+                    Box::new(TextEncoding::Utf8) as _,
+                ),
+                (
+                    "system.text.utf7encoding",
+                    Box::new(TextEncoding::Utf7) as _,
+                ),
+                (
+                    "system.text.asciiencoding",
+                    Box::new(TextEncoding::Ascii) as _,
                 ),
             ])
         });
@@ -88,19 +164,30 @@ impl ValType {
         let t = match s.as_str() {
             "char" | "byte" => Self::Char,
             "bool" => Self::Bool,
-            "int" | "long" | "decimal" => Self::Int,
-            "float" | "double" => Self::Float,
+            "int" | "long" | "decimal" | "sbyte" | "int16" | "uint16" | "int32" | "uint32"
+            | "int64" => Self::Int,
+            "float" | "double" | "single" => Self::Float,
             "string" => Self::String,
             "array" => Self::Array(None),
             "scriptblock" => Self::ScriptBlock,
             "hashtable" => Self::HashTable,
             "switch" => Self::Switch,
             _ => {
-                if !Self::STATIC_OBJECT_MAP.contains_key(s.as_str()) {
-                    Err(ValError::UnknownType(s.clone()))?;
-                }
+                // namespace prefix is optional, e.g. [Text.Encoding] or [Convert]
+                let name = [
+                    "",
+                    "system.",
+                    "system.text.",
+                    "system.management.automation.",
+                ]
+                .iter()
+                .map(|namespace| format!("{namespace}{s}"))
+                .find(|name| Self::STATIC_OBJECT_MAP.contains_key(name.as_str()));
 
-                Self::RuntimeType(s)
+                let Some(name) = name else {
+                    Err(ValError::UnknownType(s.clone()))?
+                };
+                Self::RuntimeType(name)
             }
         };
         Ok(t)
@@ -109,16 +196,26 @@ impl ValType {
     pub(crate) fn runtime(s: &str) -> ValResult<Val> {
         let val_type = Self::cast(s)?;
 
-        Ok(Val::RuntimeObject(match val_type {
-            ValType::RuntimeType(name) => Self::static_objects(&name)?,
+        let inner: Box<dyn RuntimeObject> = match &val_type {
+            ValType::RuntimeType(name) => Self::static_objects(name)?,
             ValType::String => Box::new(PsString::default()),
             ValType::ScriptBlock => Box::new(ScriptBlock::default()),
-            _ => Box::new(val_type),
-        }))
+            ValType::Bool | ValType::Int | ValType::Float | ValType::Char => {
+                Box::new(PrimitiveType::new(val_type.clone(), s))
+            }
+            _ => Box::new(val_type.clone()),
+        };
+        Ok(Val::RuntimeObject(Box::new(TypeObject::new(
+            s, &val_type, inner,
+        ))))
     }
 }
 
 impl RuntimeObject for ValType {
+    fn static_method(&self, name: &str) -> RuntimeResult<StaticFnCallType> {
+        primitive_type::static_method(self, name)
+    }
+
     fn type_definition(&self) -> RuntimeResult<ValType> {
         Ok(self.clone())
     }
@@ -165,7 +262,7 @@ impl std::fmt::Display for Val {
                 .collect::<Vec<String>>()
                 .join(NEWLINE),
             Val::RuntimeObject(rt) => {
-                let v = Val::init(ValType::cast(rt.name().as_str()).unwrap_or_default())
+                let v = Val::init(ValType::cast(rt.cast_to_string().as_str()).unwrap_or_default())
                     .unwrap_or_default();
                 if let Ok(t_info) = v.type_info() {
                     let mut first_line = format!(
@@ -180,7 +277,7 @@ impl std::fmt::Display for Val {
                     first_line.push_str(&second_line);
                     first_line
                 } else {
-                    String::new()
+                    rt.cast_to_string()
                 }
             }
             Val::NonDisplayed(_) => String::new(),
@@ -199,7 +296,9 @@ impl PartialEq for Val {
             (Val::Char(a), Val::Char(b)) => a == b,
             (Val::String(a), Val::String(b)) => a == b,
             (Val::Array(a), Val::Array(b)) => a == b,
-            (Val::RuntimeObject(a), Val::RuntimeObject(b)) => a.name() == b.name(),
+            (Val::RuntimeObject(a), Val::RuntimeObject(b)) => {
+                a.name() == b.name() && a.cast_to_string() == b.cast_to_string()
+            }
             (Val::NonDisplayed(box_a), Val::NonDisplayed(box_b)) => *box_a == *box_b,
             _ => false,
         }
@@ -217,7 +316,7 @@ impl Clone for Val {
             Val::String(a) => Val::String(a.clone()),
             Val::Array(a) => Val::Array(a.clone()),
             Val::HashTable(a) => Val::HashTable(a.clone()),
-            Val::RuntimeObject(s) => ValType::runtime(s.name().as_str()).unwrap_or_default(),
+            Val::RuntimeObject(s) => s.clone_object(),
             Val::ScriptBlock(a) => Val::ScriptBlock(a.clone()),
             Val::ScriptText(a) => Val::ScriptText(a.clone()),
             Val::NonDisplayed(box_val) => Val::NonDisplayed(box_val.clone()),
@@ -257,13 +356,27 @@ impl Val {
                     false
                 }
             }
-            Val::RuntimeObject(s1) => {
-                if let Val::RuntimeObject(s2) = val {
-                    str_cmp(&s1.name(), &s2.name(), case_insensitive) == std::cmp::Ordering::Equal
-                } else {
-                    false
-                }
+            Val::RuntimeObject(s1) if s1.compare(&val).is_some() => {
+                s1.compare(&val).transpose()? == Some(std::cmp::Ordering::Equal)
             }
+            Val::RuntimeObject(s1) => match val {
+                // objects with named properties, like pscustomobject, are equal
+                // when they have the same properties with equal values
+                Val::RuntimeObject(s2) => match (s1.properties(), s2.properties()) {
+                    (Some(p1), Some(p2)) => {
+                        p1.len() == p2.len()
+                            && p1.into_iter().zip(p2).all(|((k1, v1), (k2, v2))| {
+                                k1.eq_ignore_ascii_case(&k2)
+                                    && v1.eq(v2, case_insensitive).unwrap_or_default()
+                            })
+                    }
+                    _ => {
+                        str_cmp(&s1.cast_to_string(), &s2.cast_to_string(), case_insensitive)
+                            == std::cmp::Ordering::Equal
+                    }
+                },
+                _ => false,
+            },
             Val::ScriptBlock(sb1) => {
                 if let Val::ScriptBlock(sb2) = val {
                     str_cmp(&sb1.raw_text, &sb2.raw_text, case_insensitive)
@@ -272,8 +385,12 @@ impl Val {
                     false
                 }
             }
-            Val::ScriptText(_) => false,
-            Val::NonDisplayed(box_val) => box_val.lt(val, case_insensitive)?,
+            // unresolved command is compared by its source text
+            Val::ScriptText(st) => {
+                let s2 = val.cast_to_string();
+                str_cmp(st, &s2, case_insensitive) == std::cmp::Ordering::Equal
+            }
+            Val::NonDisplayed(box_val) => Val::eq(box_val, val, case_insensitive)?,
         })
     }
 
@@ -288,9 +405,13 @@ impl Val {
                 let s2 = val.cast_to_string();
                 str_cmp(s1, &s2, case_insensitive) == std::cmp::Ordering::Greater
             }
-            Val::Array(_) => todo!(),
+            // operators filter arrays element-wise, so they are never compared
+            Val::Array(_) => Err(Self::not_defined(self, &val, "-gt"))?,
             Val::HashTable(_) => false, // HashTables can't be compared with >
-            Val::RuntimeObject(_) => todo!(),
+            Val::RuntimeObject(rt) => match rt.compare(&val) {
+                Some(ordering) => ordering? == std::cmp::Ordering::Greater,
+                None => Err(Self::not_defined(self, &val, "-gt"))?,
+            },
             Val::ScriptBlock(_) => false, // ScriptBlocks can't be compared
             Val::ScriptText(_) => false,
             Val::NonDisplayed(box_val) => box_val.gt(val, case_insensitive)?,
@@ -308,9 +429,13 @@ impl Val {
                 let s2 = val.cast_to_string();
                 str_cmp(s1, &s2, case_insensitive) == std::cmp::Ordering::Less
             }
-            Val::Array(_) => todo!(),
+            // operators filter arrays element-wise, so they are never compared
+            Val::Array(_) => Err(Self::not_defined(self, &val, "-lt"))?,
             Val::HashTable(_) => false, // HashTables can't be compared with <
-            Val::RuntimeObject(_) => todo!(),
+            Val::RuntimeObject(rt) => match rt.compare(&val) {
+                Some(ordering) => ordering? == std::cmp::Ordering::Less,
+                None => Err(Self::not_defined(self, &val, "-lt"))?,
+            },
             Val::ScriptBlock(_) => false, // ScriptBlocks can't be compared
             Val::ScriptText(_) => false,
             Val::NonDisplayed(box_val) => box_val.lt(val, case_insensitive)?,
@@ -335,15 +460,38 @@ impl Val {
     }
 
     pub fn add(&mut self, val: Val) -> ValResult<()> {
+        if let Val::RuntimeObject(rt) = self
+            && let Some(res) = rt.op_addition(&val)
+        {
+            *self = res?;
+            return Ok(());
+        }
+
         match self {
             Val::Null => *self = val,
             Val::Bool(_) | Val::Int(_) | Val::Float(_) => {
-                *self = if val.ttype() == ValType::Float {
+                *self = if self.ttype() == ValType::Float || val.ttype() == ValType::Float {
                     Val::Float(self.cast_to_float()? + val.cast_to_float()?)
                 } else {
-                    Val::Int(self.cast_to_int()? + val.cast_to_int()?)
+                    Self::int_arithmetic(
+                        self.cast_to_int()?,
+                        val.cast_to_int()?,
+                        i64::checked_add,
+                        |a, b| a + b,
+                    )
                 };
             }
+            // [char]65 + 1 throws in PowerShell, the char has to be casted to
+            // int first, e.g. [char]([int][char]'A' + 1)
+            Val::Char(_)
+                if matches!(val.ttype(), ValType::Bool | ValType::Int | ValType::Float) =>
+            {
+                return Err(ValError::OperationNotDefined(
+                    "add".to_string(),
+                    self.ttype().to_string(),
+                    val.ttype().to_string(),
+                ));
+            }
             Val::Char(_) | Val::String(_) => {
                 *self = Val::String(PsString(
                     self.cast_to_string() + val.cast_to_string().as_str(),
@@ -412,7 +560,29 @@ impl Val {
         self.inc_or_dec_operation(-1, "--".to_string())
     }
 
+    // Powershell promotes the result to double, if it overflows the type of
+    // operands. Int32 for typical numbers, Int64 if any operand doesn't fit in Int32
+    fn int_arithmetic(
+        a: i64,
+        b: i64,
+        int_op: fn(i64, i64) -> Option<i64>,
+        float_op: fn(f64, f64) -> f64,
+    ) -> Val {
+        let fits_int32 = |i: i64| i32::try_from(i).is_ok();
+        match int_op(a, b) {
+            Some(res) if fits_int32(res) || !fits_int32(a) || !fits_int32(b) => Val::Int(res),
+            _ => Val::Float(float_op(a as f64, b as f64)),
+        }
+    }
+
     pub fn sub(&mut self, val: Val) -> ValResult<()> {
+        if let Val::RuntimeObject(rt) = self
+            && let Some(res) = rt.op_subtraction(&val)
+        {
+            *self = res?;
+            return Ok(());
+        }
+
         if let ValType::RuntimeType(_) = self.ttype() {
             Err(Self::not_defined(self, &val, "-"))?
         }
@@ -436,7 +606,12 @@ impl Val {
         if self.ttype() == ValType::Float || val.ttype() == ValType::Float {
             *self = Val::Float(self.cast_to_float()? - val.cast_to_float()?);
         } else {
-            *self = Val::Int(self.cast_to_int()? - val.cast_to_int()?);
+            *self = Self::int_arithmetic(
+                self.cast_to_int()?,
+                val.cast_to_int()?,
+                i64::checked_sub,
+                |a, b| a - b,
+            );
         }
 
         Ok(())
@@ -450,7 +625,12 @@ impl Val {
                 if self.ttype() == ValType::Float || val.ttype() == ValType::Float {
                     Ok(Val::Float(self.cast_to_float()? * val.cast_to_float()?))
                 } else {
-                    Ok(Val::Int(self.cast_to_int()? * val.cast_to_int()?))
+                    Ok(Self::int_arithmetic(
+                        self.cast_to_int()?,
+                        val.cast_to_int()?,
+                        i64::checked_mul,
+                        |a, b| a * b,
+                    ))
                 }
             }
             Val::Char(_) => Err(ValError::OperationNotDefined(
@@ -459,19 +639,14 @@ impl Val {
                 val.ttype().to_string(),
             ))?,
             Val::String(PsString(s)) => {
-                let repeat_count = val.cast_to_int()?;
-                if repeat_count < 0 {
-                    Err(ValError::ArgumentOutOfRange("*".to_string(), repeat_count))?
+                let count = val.repeat_count()?;
+                // str::repeat panics when the length overflows
+                if s.len().checked_mul(count).is_none() {
+                    Err(ValError::ArgumentOutOfRange("*".to_string(), count as i64))?
                 }
-                Ok(Val::String(PsString(s.repeat(repeat_count as usize))))
-            }
-            Val::Array(v) => {
-                let repeat_count = val.cast_to_int()?;
-                if repeat_count < 0 {
-                    Err(ValError::ArgumentOutOfRange("*".to_string(), repeat_count))?
-                }
-                Ok(Val::Array(Self::repeat(v, repeat_count as usize)))
+                Ok(Val::String(PsString(s.repeat(count))))
             }
+            Val::Array(v) => Ok(Val::Array(Self::repeat(v, val.repeat_count()?)?)),
             _ => Err(ValError::OperationNotDefined(
                 "*".to_string(),
                 self.ttype().to_string(),
@@ -510,7 +685,7 @@ impl Val {
                     Val::Float(self.cast_to_float()? / val.cast_to_float()?)
                 }
             }
-            Val::Float(_) => Val::Float(self.cast_to_float()? / self.cast_to_float()?),
+            Val::Float(_) => Val::Float(self.cast_to_float()? / val.cast_to_float()?),
             _ => Err(ValError::OperationNotDefined(
                 "/".to_string(),
                 self.ttype().to_string(),
@@ -547,7 +722,7 @@ impl Val {
                     Val::Float(self.cast_to_float()? % val.cast_to_float()?)
                 }
             }
-            Val::Float(_) => Val::Float(self.cast_to_float()? % self.cast_to_float()?),
+            Val::Float(_) => Val::Float(self.cast_to_float()? % val.cast_to_float()?),
             _ => Err(ValError::OperationNotDefined(
                 "%".to_string(),
                 self.ttype().to_string(),
@@ -560,9 +735,12 @@ impl Val {
     pub fn neg(&mut self) -> ValResult<()> {
         match self {
             Val::Float(f) => *f = f.neg(),
-            Val::Null | Val::Bool(_) | Val::Int(_) | Val::Char(_) | Val::String(_) => {
-                *self = Val::Int(self.cast_to_int()?.neg())
-            }
+            Val::Null
+            | Val::Bool(_)
+            | Val::Int(_)
+            | Val::Char(_)
+            | Val::String(_)
+            | Val::RuntimeObject(_) => *self = Val::Int(self.cast_to_int()?.neg()),
             Val::Array(_) => Err(ValError::OperationNotDefined(
                 "-".to_string(),
                 self.ttype().to_string(),
@@ -573,7 +751,6 @@ impl Val {
                 self.ttype().to_string(),
                 self.ttype().to_string(),
             ))?,
-            Val::RuntimeObject(_) => todo!(),
             Val::ScriptBlock(_) => Err(ValError::OperationNotDefined(
                 "-".to_string(),
                 self.ttype().to_string(),
@@ -605,6 +782,27 @@ impl Val {
             ValType::HashTable => Val::HashTable(self.cast_to_hashtable()?),
             ValType::ScriptBlock => Val::ScriptBlock(self.cast_to_scriptblock()?),
             ValType::ScriptText => Val::ScriptText(self.cast_to_script()),
+            ValType::RuntimeType(name) if name == PsCustomObject::TYPE_NAME => match self {
+                Val::HashTable(h) => {
+                    Val::RuntimeObject(Box::new(PsCustomObject::from_hashtable(h)))
+                }
+                _ => self.clone(),
+            },
+            ValType::RuntimeType(name) if name == DateTimeType::TYPE_NAME => {
+                DateTime::try_from_val(self)?.into()
+            }
+            ValType::RuntimeType(name) if name == GuidType::TYPE_NAME => {
+                Guid::try_from_val(self)?.into()
+            }
+            ValType::RuntimeType(name) if name == TimeSpanType::TYPE_NAME => {
+                TimeSpan::try_from_val(self)?.into()
+            }
+            ValType::RuntimeType(name) if name == Type::TYPE_NAME => {
+                TypeObject::try_from_val(self)?
+            }
+            ValType::RuntimeType(name) if name == VersionType::TYPE_NAME => {
+                Version::try_from_val(self)?.into()
+            }
             ValType::RuntimeType(_) => Err(ValError::InvalidCast(
                 self.ttype().to_string(),
                 "RuntimeType".to_string(),
@@ -641,8 +839,13 @@ impl Val {
             Val::Int(i) => *i != 0,
             Val::Float(f) => *f != 0.,
             Val::String(PsString(s)) => !s.is_empty(),
-            Val::Array(v) => !v.is_empty(),
-            Val::HashTable(h) => !h.is_empty(),
+            // single element array is as true as its element
+            Val::Array(v) => match v.as_slice() {
+                [] => false,
+                [elem] => elem.cast_to_bool(),
+                _ => true,
+            },
+            Val::HashTable(_) => true,
             Val::RuntimeObject(rt) => !rt.name().is_empty(),
             Val::ScriptBlock(_) => true,
             Val::ScriptText(st) => !st.is_empty(),
@@ -679,7 +882,9 @@ impl Val {
                 "HashTable".to_string(),
                 "Char".to_string(),
             ))?,
-            Val::RuntimeObject(_) => todo!(),
+            Val::RuntimeObject(_) => {
+                Err(ValError::InvalidCast(self.to_string(), "Char".to_string()))?
+            }
             Val::ScriptBlock(_) => Err(ValError::InvalidCast(
                 "ScriptBlock".to_string(),
                 "Char".to_string(),
@@ -701,15 +906,13 @@ impl Val {
             Val::Float(f) => f.round() as i64,
             Val::Char(c) => *c as i64,
             Val::String(PsString(s)) => {
-                let s = s.to_ascii_lowercase();
-                if let Some(hex) = s.strip_prefix("0x") {
+                let trimmed = s.trim().to_ascii_lowercase();
+                if let Some(hex) = trimmed.strip_prefix("0x") {
                     i64::from_str_radix(hex, 16)?
-                } else if let Ok(casted) = s.trim().parse::<f64>() {
-                    Self::round_bankers(casted) as i64
+                } else if let Ok(i) = trimmed.parse::<i64>() {
+                    i
                 } else {
-                    s.trim()
-                        .parse::<i64>()
-                        .map_err(|_| ValError::InvalidCast(format!("\"{s}\""), "Int".to_string()))?
+                    Self::round_bankers(Self::parse_number(s, "Int")?) as i64
                 }
             }
             Val::Array(_) => Err(ValError::InvalidCast(
@@ -742,10 +945,7 @@ impl Val {
             Val::Int(i) => *i as f64,
             Val::Float(f) => *f,
             Val::Char(c) => *c as f64,
-            Val::String(PsString(s)) => s
-                .trim()
-                .parse::<f64>()
-                .map_err(|_| ValError::InvalidCast(format!("\"{s}\""), "Float".to_string()))?,
+            Val::String(PsString(s)) => Self::parse_number(s, "Float")?,
             Val::Array(_) => Err(ValError::InvalidCast(
                 "Array".to_string(),
                 "Float".to_string(),
@@ -754,7 +954,9 @@ impl Val {
                 "HashTable".to_string(),
                 "Float".to_string(),
             ))?,
-            Val::RuntimeObject(_) => todo!(),
+            Val::RuntimeObject(_) => {
+                Err(ValError::InvalidCast(self.to_string(), "Float".to_string()))?
+            }
             Val::ScriptBlock(_) => Err(ValError::InvalidCast(
                 "ScriptBlock".to_string(),
                 "Float".to_string(),
@@ -772,7 +974,7 @@ impl Val {
             Val::Null => String::new(),
             Val::Bool(b) => String::from(if *b { "True" } else { "False" }),
             Val::Int(i) => i.to_string(),
-            Val::Float(f) => f.to_string(),
+            Val::Float(f) => format_float(*f),
             Val::Char(c) => char::from_u32(*c).unwrap_or_default().to_string(),
             Val::String(PsString(s)) => s.clone(),
             Val::Array(v) => v
@@ -781,7 +983,7 @@ impl Val {
                 .collect::<Vec<String>>()
                 .join(" "),
             Val::HashTable(_) => "System.Collections.Hashtable".to_string(),
-            Val::RuntimeObject(s) => s.name(),
+            Val::RuntimeObject(s) => s.cast_to_string(),
             Val::ScriptBlock(sb) => sb.to_string(),
             Val::ScriptText(st) => st.clone(),
             Val::NonDisplayed(box_val) => box_val.cast_to_string(),
@@ -799,12 +1001,18 @@ impl Val {
     pub(crate) fn cast_to_typed_array(&self, ttype: Option<Box<ValType>>) -> ValResult<Vec<Self>> {
         let mut arr = match self {
             Val::Null => vec![],
+            // [char[]]"abc" splits the string into characters
+            Val::String(PsString(s)) if ttype.as_deref() == Some(&ValType::Char) => {
+                s.chars().map(|c| Val::Char(c as u32)).collect()
+            }
             Val::Bool(_) | Val::Int(_) | Val::Float(_) | Val::Char(_) | Val::String(_) => {
                 vec![self.clone()]
             }
             Val::Array(v) => v.clone(),
             Val::HashTable(_) => vec![self.clone()],
-            Val::RuntimeObject(a) => vec![Val::String(a.name().into())],
+            Val::RuntimeObject(a) => a
+                .elements()
+                .unwrap_or_else(|| vec![Val::String(a.cast_to_string().into())]),
             Val::ScriptBlock(sb) => vec![Val::String(sb.to_string().into())],
             Val::ScriptText(s) => vec![Val::String(s.clone().into())],
             Val::NonDisplayed(s) => s.cast_to_typed_array(ttype.clone())?,
@@ -821,12 +1029,39 @@ impl Val {
         self.cast_to_typed_array(None).unwrap_or_default()
     }
 
-    fn repeat(v: &[Val], amount: usize) -> Vec<Val> {
-        let mut res = v.to_owned();
-        for _ in 1..amount {
-            res.append(&mut v.to_owned());
+    fn repeat(v: &[Val], amount: usize) -> ValResult<Vec<Val>> {
+        let Some(capacity) = v.len().checked_mul(amount) else {
+            Err(ValError::ArgumentOutOfRange("*".to_string(), amount as i64))?
+        };
+        let mut res = Vec::with_capacity(capacity);
+        for _ in 0..amount {
+            res.extend_from_slice(v);
         }
-        res
+        Ok(res)
+    }
+
+    // count of a string or array multiplication. Like in PowerShell, fractions
+    // are rounded to even, zero gives an empty result and negative counts are
+    // out of range
+    pub(crate) fn repeat_count(&self) -> ValResult<usize> {
+        let count = match self {
+            Val::Float(f) => Self::round_bankers(*f) as i64,
+            _ => self.cast_to_int()?,
+        };
+        usize::try_from(count).map_err(|_| ValError::ArgumentOutOfRange("*".to_string(), count))
+    }
+
+    // numbers are parsed culture-invariant: "." is the only decimal separator and
+    // group separators, like in "1,000", are not allowed
+    fn parse_number(s: &str, type_name: &str) -> ValResult<f64> {
+        let trimmed = s.trim();
+        trimmed.parse::<f64>().map_err(|_| {
+            if trimmed.contains(',') && trimmed.replace(',', "").parse::<f64>().is_ok() {
+                ValError::NonInvariantNumber(s.to_string(), type_name.to_string())
+            } else {
+                ValError::InvalidCast(format!("\"{s}\""), type_name.to_string())
+            }
+        })
     }
 
     fn round_bankers(x: f64) -> f64 {
@@ -853,14 +1088,39 @@ impl Val {
         }
     }
 
+    // besides hashtables, arrays of pairs are cast, where every pair is a
+    // two-element array or an object with the Key and Value properties
     pub(crate) fn cast_to_hashtable(&self) -> ValResult<HashMap<String, Val>> {
-        if let Val::HashTable(h) = self {
-            Ok(h.clone())
-        } else {
-            Err(ValError::InvalidCast(
-                self.ttype().to_string(),
-                "HashTable".to_string(),
-            ))
+        let invalid_cast =
+            || ValError::InvalidCast(self.ttype().to_string(), "HashTable".to_string());
+        let pair = |val: &Val| match val {
+            Val::Array(pair) if pair.len() == 2 => Some((pair[0].clone(), pair[1].clone())),
+            Val::HashTable(h) if h.len() == 2 => {
+                Some((h.get("key")?.clone(), h.get("value")?.clone()))
+            }
+            Val::RuntimeObject(obj) => {
+                let properties = obj.properties()?;
+                let property = |name: &str| {
+                    properties
+                        .iter()
+                        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+                        .map(|(_, val)| val.clone())
+                };
+                Some((property("key")?, property("value")?))
+            }
+            _ => None,
+        };
+
+        match self {
+            Val::HashTable(h) => Ok(h.clone()),
+            Val::Array(arr) => arr
+                .iter()
+                .map(|elem| {
+                    let (key, value) = pair(elem).ok_or_else(invalid_cast)?;
+                    Ok((key.cast_to_string().to_ascii_lowercase(), value))
+                })
+                .collect(),
+            _ => Err(invalid_cast()),
         }
     }
 
@@ -924,6 +1184,11 @@ impl Val {
             Val::Null => "$null".to_string(),
             Val::Bool(b) => String::from(if *b { "$true" } else { "$false" }),
             Val::Int(i) => i.to_string(),
+            Val::Float(f) if f.is_nan() => "[double]::NaN".to_string(),
+            Val::Float(f) if f.is_infinite() => {
+                let sign = if *f > 0. { "Positive" } else { "Negative" };
+                format!("[double]::{sign}Infinity")
+            }
             Val::Float(f) => f.to_string(),
             Val::Char(c) => format!("'{}'", char::from_u32(*c).unwrap_or_default()),
             Val::String(PsString(s)) => format!("\"{}\"", s),
@@ -944,7 +1209,7 @@ impl Val {
                     .join(NEWLINE);
                 format!("@{{{NEWLINE}{}{NEWLINE}}}", inner)
             }
-            Val::RuntimeObject(s) => format!("[{}]", s.name()),
+            Val::RuntimeObject(s) => s.cast_to_script(),
             Val::ScriptBlock(sb) => format!("{{{}}}", sb),
             Val::ScriptText(st) => st.clone(),
             Val::NonDisplayed(box_val) => (*box_val).cast_to_script(),
@@ -970,6 +1235,57 @@ impl From<&String> for Val {
     }
 }
 
+// Formats the float like PowerShell does: up to 15 significant digits, so
+// rounding errors like in 0.1 + 0.2 are not displayed. Very large and very small
+// numbers are written in the exponential notation, e.g. 1E+15 or 1E-05
+fn format_float(f: f64) -> String {
+    const PRECISION: i32 = 15;
+    if f.is_nan() {
+        return "NaN".to_string();
+    }
+    if f.is_infinite() {
+        return if f > 0. { "Infinity" } else { "-Infinity" }.to_string();
+    }
+
+    // rust rounds the mantissa, e.g. "3.00000000000000e-1"
+    let formatted = format!("{:.*e}", PRECISION as usize - 1, f);
+    let Some((mantissa, exp)) = formatted.split_once('e') else {
+        return f.to_string();
+    };
+    let exp = exp.parse::<i32>().unwrap_or_default();
+    let (sign, mantissa) = match mantissa.strip_prefix('-') {
+        Some(m) => ("-", m),
+        None => ("", mantissa),
+    };
+    let digits = mantissa.replace('.', "");
+    let digits = digits.trim_end_matches('0');
+    if digits.is_empty() {
+        return "0".to_string();
+    }
+
+    if !(-4..PRECISION).contains(&exp) {
+        let (first, rest) = digits.split_at(1);
+        let mantissa = if rest.is_empty() {
+            first.to_string()
+        } else {
+            format!("{first}.{rest}")
+        };
+        let exp_sign = if exp < 0 { '-' } else { '+' };
+        return format!("{sign}{mantissa}E{exp_sign}{:02}", exp.abs());
+    }
+
+    let point = exp + 1;
+    let number = if point <= 0 {
+        format!("0.{}{digits}", "0".repeat(-point as usize))
+    } else if point as usize >= digits.len() {
+        format!("{digits}{}", "0".repeat(point as usize - digits.len()))
+    } else {
+        let (int, fract) = digits.split_at(point as usize);
+        format!("{int}.{fract}")
+    };
+    format!("{sign}{number}")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -984,9 +1300,12 @@ mod tests {
         val.add(Val::Float(0.1)).unwrap();
         assert_eq!(val, Val::String(" 1230.1".into()));
 
+        let mut val = Val::Float(1.5);
+        val.add(Val::Int(1)).unwrap();
+        assert_eq!(val, Val::Float(2.5));
+
         let mut val = Val::Char(97);
-        val.add(Val::Float(0.1)).unwrap();
-        assert_eq!(val, Val::String("a0.1".into()));
+        assert!(val.add(Val::Float(0.1)).is_err());
 
         let mut val = Val::Int(4);
         val.add(Val::Int(1)).unwrap();
@@ -997,12 +1316,11 @@ mod tests {
         assert_eq!(val, Val::String(" 1231".into()));
 
         let mut val = Val::Char(97);
-        val.add(Val::Int(1)).unwrap();
-        assert_eq!(val, Val::String("a1".into()));
+        assert!(val.add(Val::Int(1)).is_err());
 
         let mut val = Val::Char(97);
-        val.add(Val::Int(1)).unwrap();
-        assert_eq!(val, Val::String("a1".into()));
+        val.add(Val::Char(98)).unwrap();
+        assert_eq!(val, Val::String("ab".into()));
 
         let mut val = Val::String(" 123".into());
         val.add(Val::Int(1)).unwrap();
@@ -1024,6 +1342,80 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_float_to_string() {
+        for (f, expected) in [
+            (0.1 + 0.2, "0.3"),
+            (1.5, "1.5"),
+            (-2.25, "-2.25"),
+            (3.0, "3"),
+            (0.0, "0"),
+            (100.0 / 3.0, "33.3333333333333"),
+            (123456789012345.0, "123456789012345"),
+            (1e15, "1E+15"),
+            (-1.5e20, "-1.5E+20"),
+            (0.0001, "0.0001"),
+            (0.00001, "1E-05"),
+            (1.23e-10, "1.23E-10"),
+            (f64::NAN, "NaN"),
+            (f64::NEG_INFINITY, "-Infinity"),
+        ] {
+            assert_eq!(Val::Float(f).cast_to_string(), expected, "{f}");
+        }
+
+        let mut p = crate::PowerShellSession::new();
+        assert_eq!(p.safe_eval("0.1 + 0.2").unwrap(), "0.3".to_string());
+        assert_eq!(
+            p.safe_eval("1000000.5 * 1000000000000").unwrap(),
+            "1.0000005E+18".to_string()
+        );
+    }
+
+    #[test]
+    fn test_non_displayed_eq() {
+        for val in [
+            Val::Int(3),
+            Val::Float(2.5),
+            Val::String("abc".into()),
+            Val::Bool(false),
+            Val::Null,
+        ] {
+            let wrapped = Val::NonDisplayed(Box::new(val.clone()));
+            assert!(wrapped.eq(val.clone(), true).unwrap(), "{val:?}");
+            assert!(wrapped.eq(wrapped.clone(), true).unwrap(), "{val:?}");
+        }
+        let wrapped = Val::NonDisplayed(Box::new(Val::Int(3)));
+        assert!(!wrapped.eq(Val::Int(4), true).unwrap());
+
+        let mut p = crate::PowerShellSession::new();
+        let input = r#"if (($x = 5) -eq 5) { "eq" } else { "ne" }"#;
+        assert_eq!(p.safe_eval(input).unwrap(), "eq".to_string());
+    }
+
+    #[test]
+    fn test_char_increment() {
+        let mut p = crate::PowerShellSession::new();
+        for (input, expected) in [
+            ("[char]([int][char]'A' + 1)", "B"),
+            ("[int][char]'A' + 1", "66"),
+            ("$c = [char]'a'; [char]([int]$c + 2)", "c"),
+            (
+                "-join ((72, 105) | ForEach-Object { [char]($_ + 0) })",
+                "Hi",
+            ),
+            ("[char]'a' + 'b'", "ab"),
+            ("'x' + [char]65", "xA"),
+        ] {
+            assert_eq!(p.safe_eval(input).unwrap(), expected, "{input}");
+        }
+
+        let s = p.parse_input("[char]65 + 1").unwrap();
+        assert_eq!(
+            s.errors()[0].to_string(),
+            "ValError: Operation \"add\" is not defined for types \"Char\" op \"Int\""
+        );
+    }
+
     #[test]
     fn test_sub() {
         let mut val = Val::Int(4);
@@ -1101,6 +1493,86 @@ mod tests {
                 Val::String(" adsf".into())
             ])
         );
+
+        // zero count gives an empty string or array
+        let mut val = Val::String("ab".into());
+        val.mul(Val::Int(0)).unwrap();
+        assert_eq!(val, Val::String("".into()));
+
+        let mut val = Val::Array(vec![Val::Int(1)]);
+        val.mul(Val::Int(0)).unwrap();
+        assert_eq!(val, Val::Array(vec![]));
+
+        // fractional counts are rounded to even
+        let mut val = Val::String("ab".into());
+        val.mul(Val::Float(2.5)).unwrap();
+        assert_eq!(val, Val::String("abab".into()));
+
+        let mut val = Val::String("ab".into());
+        val.mul(Val::Float(2.9)).unwrap();
+        assert_eq!(val, Val::String("ababab".into()));
+
+        let mut val = Val::Array(vec![Val::Int(1)]);
+        val.mul(Val::Float(3.5)).unwrap();
+        assert_eq!(val, Val::Array(vec![Val::Int(1); 4]));
+
+        let mut val = Val::String("ab".into());
+        val.mul(Val::Float(-0.4)).unwrap();
+        assert_eq!(val, Val::String("".into()));
+
+        // negative count is out of range
+        let mut val = Val::String("ab".into());
+        assert_eq!(
+            val.mul(Val::Int(-1)),
+            Err(ValError::ArgumentOutOfRange("*".to_string(), -1))
+        );
+        let mut val = Val::Array(vec![Val::Int(1)]);
+        assert_eq!(
+            val.mul(Val::Float(-1.5)),
+            Err(ValError::ArgumentOutOfRange("*".to_string(), -2))
+        );
+    }
+
+    #[test]
+    fn test_div() {
+        let mut val = Val::Float(3.);
+        val.div(Val::Int(2)).unwrap();
+        assert_eq!(val, Val::Float(1.5));
+
+        let mut val = Val::Float(10.);
+        val.div(Val::Int(4)).unwrap();
+        assert_eq!(val, Val::Float(2.5));
+
+        let mut val = Val::Float(1.);
+        val.div(Val::Float(0.5)).unwrap();
+        assert_eq!(val, Val::Float(2.));
+
+        let mut val = Val::Float(7.5);
+        val.modulo(Val::Int(2)).unwrap();
+        assert_eq!(val, Val::Float(1.5));
+    }
+
+    #[test]
+    fn test_int_overflow() {
+        let mut val = Val::Int(i32::MAX as i64);
+        val.add(Val::Int(1)).unwrap();
+        assert_eq!(val, Val::Float(2147483648.));
+
+        let mut val = Val::Int(i32::MIN as i64);
+        val.sub(Val::Int(1)).unwrap();
+        assert_eq!(val, Val::Float(-2147483649.));
+
+        let mut val = Val::Int(65536);
+        val.mul(Val::Int(65536)).unwrap();
+        assert_eq!(val, Val::Float(4294967296.));
+
+        let mut val = Val::Int(i64::MAX);
+        val.add(Val::Int(1)).unwrap();
+        assert_eq!(val, Val::Float(i64::MAX as f64 + 1.));
+
+        let mut val = Val::Int(i64::MAX - 1);
+        val.add(Val::Int(1)).unwrap();
+        assert_eq!(val, Val::Int(i64::MAX));
     }
 
     #[test]
@@ -1121,6 +1593,18 @@ mod tests {
         assert_eq!(Val::String("".into()).cast_to_bool(), false);
         assert_eq!(Val::Array(vec![]).cast_to_bool(), false);
         assert_eq!(Val::Array(vec![Val::Int(7)]).cast_to_bool(), true);
+        assert!(!Val::Array(vec![Val::Int(0)]).cast_to_bool());
+        assert!(Val::Array(vec![Val::Int(0), Val::Int(0)]).cast_to_bool());
+        assert!(Val::HashTable(HashMap::new()).cast_to_bool());
+
+        // any non-empty string is true, even "false" and "0"
+        assert!(Val::String("false".into()).cast_to_bool());
+        assert!(Val::String("0".into()).cast_to_bool());
+        let mut p = crate::PowerShellSession::new();
+        assert_eq!(p.safe_eval(r#"[bool]"false""#).unwrap(), "True");
+        assert_eq!(p.safe_eval(r#"[bool]"0""#).unwrap(), "True");
+        assert_eq!(p.safe_eval(r#"[bool]"""#).unwrap(), "False");
+        assert_eq!(p.safe_eval(r#"[bool]'' -or [bool]0"#).unwrap(), "False");
     }
 
     #[test]
@@ -1175,6 +1659,16 @@ mod tests {
             Val::String("  888  a".into()).cast_to_int().unwrap_err(),
             ValError::InvalidCast("\"  888  a\"".to_string(), "Int".to_string())
         );
+        assert_eq!(
+            Val::String("1,000".into()).cast_to_int().unwrap_err(),
+            ValError::NonInvariantNumber("1,000".to_string(), "Int".to_string())
+        );
+        assert_eq!(
+            Val::String("9007199254740993".into())
+                .cast_to_int()
+                .unwrap(),
+            9007199254740993
+        );
         assert_eq!(
             Val::Array(vec![Val::Int(7)]).cast_to_int().unwrap_err(),
             ValError::InvalidCast("Array".to_string(), "Int".to_string())
@@ -1204,6 +1698,10 @@ mod tests {
             Val::String("  888  a".into()).cast_to_float().unwrap_err(),
             ValError::InvalidCast("\"  888  a\"".to_string(), "Float".to_string())
         );
+        assert_eq!(
+            Val::String("1,5".into()).cast_to_float().unwrap_err(),
+            ValError::NonInvariantNumber("1,5".to_string(), "Float".to_string())
+        );
         assert_eq!(
             Val::Array(vec![Val::Float(7.)])
                 .cast_to_float()
@@ -1259,6 +1757,31 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_cast_to_hashtable() {
+        let pairs = Val::Array(vec![
+            Val::Array(vec![Val::String("A".into()), Val::Int(1)]),
+            Val::Array(vec![Val::String("b".into()), Val::Int(2)]),
+        ]);
+        assert_eq!(
+            pairs.cast_to_hashtable().unwrap(),
+            HashMap::from([("a".into(), Val::Int(1)), ("b".into(), Val::Int(2))])
+        );
+        assert_eq!(
+            Val::Array(vec![Val::Int(1)])
+                .cast_to_hashtable()
+                .unwrap_err(),
+            ValError::InvalidCast("Array(None)".into(), "HashTable".into())
+        );
+
+        let mut p = crate::PowerShellSession::new();
+        let input = r#"$h = [hashtable]@(@('a', 1), @('b', 2)); $h.Count + $h.a + $h['B']"#;
+        assert_eq!(p.safe_eval(input).unwrap(), "5".to_string());
+
+        let input = r#"$h = [hashtable]@([pscustomobject]@{ Key = 'k'; Value = 'v' }); $h.k"#;
+        assert_eq!(p.safe_eval(input).unwrap(), "v".to_string());
+    }
+
     #[test]
     fn runtime_type() {
         assert_eq!(
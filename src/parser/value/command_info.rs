@@ -0,0 +1,77 @@
+use super::{Member, RuntimeError, RuntimeObject, Val, ValType, runtime_object::RuntimeResult};
+
+// kinds of commands, in the order Get-Command lists them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum CommandType {
+    Alias,
+    Function,
+    Cmdlet,
+}
+
+impl std::fmt::Display for CommandType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+// Command reference returned by Get-Command. It can be invoked with & or .
+#[derive(Debug, Clone)]
+pub(crate) struct CommandInfo {
+    pub name: String,
+    pub command_type: CommandType,
+    // target of the alias, body of the function or the name of the cmdlet
+    pub definition: String,
+    pub source: String,
+}
+
+impl CommandInfo {
+    pub(crate) const TYPE_NAME: &'static str = "system.management.automation.commandinfo";
+
+    // name of the referenced command, when the value is a command reference
+    pub(crate) fn command_name(val: &Val) -> Option<String> {
+        let Val::RuntimeObject(rt) = val else {
+            return None;
+        };
+        let ttype = rt.type_definition().ok()?;
+        (ttype == ValType::RuntimeType(Self::TYPE_NAME.to_string())).then(|| rt.name())
+    }
+}
+
+impl RuntimeObject for CommandInfo {
+    fn readonly_member(&self, name: &str) -> RuntimeResult<Val> {
+        let val = match name.to_ascii_lowercase().as_str() {
+            "name" => self.name.clone(),
+            "commandtype" => self.command_type.to_string(),
+            "definition" => self.definition.clone(),
+            "source" | "modulename" => self.source.clone(),
+            _ => Err(RuntimeError::MemberNotFound(name.to_string()))?,
+        };
+        Ok(Val::String(val.into()))
+    }
+
+    fn members(&self) -> Vec<Member> {
+        vec![
+            Member::property("CommandType", "CommandTypes CommandType {get;}"),
+            Member::property("Definition", "string Definition {get;}"),
+            Member::property("ModuleName", "string ModuleName {get;}"),
+            Member::property("Name", "string Name {get;}"),
+            Member::property("Source", "string Source {get;}"),
+        ]
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn type_definition(&self) -> RuntimeResult<ValType> {
+        Ok(ValType::RuntimeType(Self::TYPE_NAME.to_string()))
+    }
+
+    fn clone_object(&self) -> Val {
+        Val::RuntimeObject(Box::new(self.clone()))
+    }
+
+    fn cast_to_script(&self) -> String {
+        format!("(Get-Command {})", self.name)
+    }
+}
@@ -0,0 +1,135 @@
+use std::{cell::Cell, rc::Rc};
+
+use super::{
+    Member, MethodCallType, MethodError, RuntimeError, RuntimeObject, Val,
+    runtime_object::RuntimeResult,
+};
+
+// $input of functions and script blocks, the enumerator of the pipeline input.
+// Like references to the same .NET enumerator, clones share the position, and
+// enumerating the whole $input, e.g. in a pipeline, consumes it
+#[derive(Debug, Clone)]
+pub(crate) struct InputEnumerator {
+    items: Rc<Vec<Val>>,
+    // number of MoveNext calls, the current item is the previous one
+    position: Rc<Cell<usize>>,
+}
+
+impl InputEnumerator {
+    pub(crate) fn new(items: Vec<Val>) -> Self {
+        Self {
+            items: Rc::new(items),
+            position: Rc::new(Cell::new(0)),
+        }
+    }
+}
+
+impl RuntimeObject for InputEnumerator {
+    fn method(&self, name: &str) -> RuntimeResult<MethodCallType> {
+        let enumerator = self.clone();
+        let method: MethodCallType = match name.to_ascii_lowercase().as_str() {
+            "movenext" => Box::new(move |_, args| {
+                if !args.is_empty() {
+                    return Err(MethodError::new_incorrect_args("MoveNext", args));
+                }
+                let position = (enumerator.position.get() + 1).min(enumerator.items.len() + 1);
+                enumerator.position.set(position);
+                Ok(Val::Bool(position <= enumerator.items.len()))
+            }),
+            "reset" => Box::new(move |_, args| {
+                if !args.is_empty() {
+                    return Err(MethodError::new_incorrect_args("Reset", args));
+                }
+                enumerator.position.set(0);
+                Ok(Val::Null)
+            }),
+            _ => Err(MethodError::MethodNotFound(name.to_string()))?,
+        };
+        Ok(method)
+    }
+
+    fn readonly_member(&self, name: &str) -> RuntimeResult<Val> {
+        match name.to_ascii_lowercase().as_str() {
+            "current" => Ok(self
+                .position
+                .get()
+                .checked_sub(1)
+                .and_then(|i| self.items.get(i))
+                .cloned()
+                .unwrap_or_default()),
+            _ => Err(RuntimeError::MemberNotFound(name.to_string())),
+        }
+    }
+
+    fn members(&self) -> Vec<Member> {
+        vec![
+            Member::method("MoveNext", "bool MoveNext()"),
+            Member::method("Reset", "void Reset()"),
+            Member::property("Current", "System.Object Current {get;}"),
+        ]
+    }
+
+    fn elements(&self) -> Option<Vec<Val>> {
+        let rest = self.items.get(self.position.get()..).unwrap_or_default();
+        self.position.set(self.items.len() + 1);
+        Some(rest.to_vec())
+    }
+
+    fn name(&self) -> String {
+        "System.Collections.ArrayList+ArrayListEnumeratorSimple".to_string()
+    }
+
+    fn clone_object(&self) -> Val {
+        Val::RuntimeObject(Box::new(self.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{PowerShellSession, PsValue};
+
+    #[test]
+    fn move_next() {
+        let mut p = PowerShellSession::new();
+        let input = r#"function Get-Sum {
+    $sum = 0
+    while ($input.MoveNext()) { $sum += $input.Current }
+    $sum
+}
+1, 2, 3 | Get-Sum"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(s.result(), PsValue::Int(6));
+
+        // enumerated input is consumed until Reset
+        let input = r#"function f {
+    $all = $input | % { $_ * 2 }
+    $moved = $input.MoveNext()
+    $input.Reset()
+    $first = $input.MoveNext()
+    "$all $moved $first $($input.Current)"
+}
+"a", "b" | f"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(s.result(), PsValue::String("aa bb False True a".into()));
+
+        // without the pipeline $input is empty
+        let input = r#"function f { foreach ($i in $input) { $i + 1 } }; 5 | f; f"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(s.output(), "6");
+    }
+
+    #[test]
+    fn enumerated_in_output() {
+        let mut p = PowerShellSession::new();
+        let s = p.parse_input("function f { $input }; 1, 2 | f").unwrap();
+        assert_eq!(
+            s.result(),
+            PsValue::Array(vec![PsValue::Int(1), PsValue::Int(2)])
+        );
+
+        let s = p
+            .parse_input("function f { @($input).Count }; 1, 2, 3 | f")
+            .unwrap();
+        assert_eq!(s.result(), PsValue::Int(3));
+    }
+}
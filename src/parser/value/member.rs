@@ -0,0 +1,106 @@
+use std::collections::BTreeMap;
+
+use super::{RuntimeObject, Val};
+
+// kinds of members, in the order Get-Member lists them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum MemberType {
+    Method,
+    Property,
+    NoteProperty,
+}
+
+impl std::fmt::Display for MemberType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+// member of an object, as reported by Get-Member
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Member {
+    pub name: String,
+    pub member_type: MemberType,
+    pub definition: String,
+}
+
+impl Member {
+    pub(crate) fn method(name: &str, definition: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            member_type: MemberType::Method,
+            definition: definition.to_string(),
+        }
+    }
+
+    pub(crate) fn property(name: &str, definition: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            member_type: MemberType::Property,
+            definition: definition.to_string(),
+        }
+    }
+
+    // properties of hashtables and custom objects, defined by their value
+    fn note_property(name: &str, val: &Val) -> Self {
+        let ttype = match val {
+            Val::Bool(_) => "bool",
+            Val::Int(_) => "int",
+            Val::Float(_) => "double",
+            Val::Char(_) => "char",
+            Val::String(_) => "string",
+            Val::Array(_) => "Object[]",
+            Val::HashTable(_) => "hashtable",
+            _ => "System.Object",
+        };
+        Self {
+            name: name.to_string(),
+            member_type: MemberType::NoteProperty,
+            definition: format!("{ttype} {name}={}", val.cast_to_string()),
+        }
+    }
+}
+
+impl Val {
+    // members which can be accessed on the value, sorted like in Get-Member
+    pub(crate) fn members(&self) -> Vec<Member> {
+        let mut members = match self {
+            Val::Null => return vec![],
+            Val::NonDisplayed(inner) => return inner.members(),
+            Val::String(s) => s.members(),
+            Val::Array(_) => vec![
+                Member::property("Count", "int Count {get;}"),
+                Member::property("Length", "int Length {get;}"),
+            ],
+            Val::HashTable(h) => BTreeMap::from_iter(h.iter())
+                .into_iter()
+                .map(|(key, val)| Member::note_property(key, val))
+                .chain([Member::property("Count", "int Count {get;}")])
+                .collect(),
+            Val::RuntimeObject(rt) => rt
+                .members()
+                .into_iter()
+                .chain(
+                    rt.properties()
+                        .unwrap_or_default()
+                        .iter()
+                        .map(|(key, val)| Member::note_property(key, val)),
+                )
+                .collect(),
+            _ => vec![],
+        };
+
+        members.push(Member::method("GetType", "type GetType()"));
+        if matches!(
+            self,
+            Val::Bool(_) | Val::Int(_) | Val::Float(_) | Val::Char(_) | Val::String(_)
+        ) {
+            members.push(Member::method(
+                "CompareTo",
+                "int CompareTo(System.Object value)",
+            ));
+        }
+        members.sort_by_cached_key(|member| (member.member_type, member.name.to_ascii_lowercase()));
+        members
+    }
+}
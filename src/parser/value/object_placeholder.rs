@@ -0,0 +1,42 @@
+use super::{RuntimeObject, Val};
+
+// Instance of a .NET type which is not modeled. Only the type name and the
+// constructor arguments are kept, so the object is still readable in the output
+// and the deobfuscated script creates it again
+#[derive(Debug, Clone)]
+pub(crate) struct ObjectPlaceholder {
+    type_name: String,
+    arguments: Vec<Val>,
+}
+
+impl ObjectPlaceholder {
+    pub(crate) fn new(type_name: &str, arguments: Vec<Val>) -> Self {
+        Self {
+            type_name: type_name.to_string(),
+            arguments,
+        }
+    }
+}
+
+impl RuntimeObject for ObjectPlaceholder {
+    fn name(&self) -> String {
+        self.type_name.clone()
+    }
+
+    fn clone_object(&self) -> Val {
+        Val::RuntimeObject(Box::new(self.clone()))
+    }
+
+    fn cast_to_script(&self) -> String {
+        if self.arguments.is_empty() {
+            return format!("(New-Object {})", self.type_name);
+        }
+        let arguments = self
+            .arguments
+            .iter()
+            .map(Val::cast_to_script)
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!("(New-Object {} -ArgumentList {arguments})", self.type_name)
+    }
+}
@@ -0,0 +1,240 @@
+use super::{
+    MethodError, MethodResult, RuntimeError, RuntimeObject, StaticFnCallType, Val, ValError,
+    ValType,
+};
+use crate::parser::value::runtime_object::RuntimeResult;
+
+// [int], [long], [byte], etc. ValType merges the types with the same
+// representation, so the type name is kept for members which depend on the
+// exact type, like MinValue and MaxValue
+#[derive(Debug, Clone)]
+pub(crate) struct PrimitiveType {
+    ttype: ValType,
+    type_name: String,
+}
+
+impl PrimitiveType {
+    pub(crate) fn new(ttype: ValType, type_name: &str) -> Self {
+        let mut type_name = type_name.to_ascii_lowercase();
+        type_name.retain(|c| !c.is_whitespace());
+        Self { ttype, type_name }
+    }
+
+    fn limits(&self) -> Option<(Val, Val)> {
+        Some(match self.type_name.as_str() {
+            "byte" => (Val::Int(u8::MIN.into()), Val::Int(u8::MAX.into())),
+            "sbyte" => (Val::Int(i8::MIN.into()), Val::Int(i8::MAX.into())),
+            "int16" => (Val::Int(i16::MIN.into()), Val::Int(i16::MAX.into())),
+            "uint16" => (Val::Int(u16::MIN.into()), Val::Int(u16::MAX.into())),
+            "int" | "int32" => (Val::Int(i32::MIN.into()), Val::Int(i32::MAX.into())),
+            "uint32" => (Val::Int(u32::MIN.into()), Val::Int(u32::MAX.into())),
+            "long" | "int64" => (Val::Int(i64::MIN), Val::Int(i64::MAX)),
+            "char" => (Val::Char(u16::MIN.into()), Val::Char(u16::MAX.into())),
+            "float" | "single" => (Val::Float(f32::MIN.into()), Val::Float(f32::MAX.into())),
+            "double" => (Val::Float(f64::MIN), Val::Float(f64::MAX)),
+            _ => None?,
+        })
+    }
+}
+
+impl RuntimeObject for PrimitiveType {
+    fn static_method(&self, name: &str) -> RuntimeResult<StaticFnCallType> {
+        static_method(&self.ttype, name)
+    }
+
+    fn readonly_static_member(&self, name: &str) -> RuntimeResult<Val> {
+        let limits = self.limits();
+        match (limits, name.to_ascii_lowercase().as_str()) {
+            (Some((min, _)), "minvalue") => Ok(min),
+            (Some((_, max)), "maxvalue") => Ok(max),
+            (_, "nan") if self.ttype == ValType::Float => Ok(Val::Float(f64::NAN)),
+            (_, "positiveinfinity") if self.ttype == ValType::Float => {
+                Ok(Val::Float(f64::INFINITY))
+            }
+            (_, "negativeinfinity") if self.ttype == ValType::Float => {
+                Ok(Val::Float(f64::NEG_INFINITY))
+            }
+            _ => Err(RuntimeError::MemberNotFound(name.to_string())),
+        }
+    }
+
+    fn name(&self) -> String {
+        self.ttype.to_string()
+    }
+
+    fn type_definition(&self) -> RuntimeResult<ValType> {
+        Ok(self.ttype.clone())
+    }
+
+    fn clone_object(&self) -> Val {
+        Val::RuntimeObject(Box::new(self.clone()))
+    }
+}
+
+// static methods of [int], [long], [double], [bool], etc.
+pub(super) fn static_method(ttype: &ValType, name: &str) -> RuntimeResult<StaticFnCallType> {
+    let f = match (ttype, name.to_ascii_lowercase().as_str()) {
+        (ValType::Int, "parse") => parse_int,
+        (ValType::Int, "tryparse") => try_parse_int,
+        (ValType::Float, "parse") => parse_float,
+        (ValType::Float, "tryparse") => try_parse_float,
+        (ValType::Bool, "parse") => parse_bool,
+        (ValType::Bool, "tryparse") => try_parse_bool,
+        _ => Err(MethodError::MethodNotFound(name.to_string()))?,
+    };
+    Ok(f)
+}
+
+// first argument is the parsed string, TryParse has also the out variable,
+// which is ignored
+fn string_arg(fn_name: &str, args: &[Val], args_count: usize) -> MethodResult<String> {
+    match args {
+        [arg, ..] if args.len() == args_count => Ok(arg.cast_to_string()),
+        _ => Err(MethodError::new_incorrect_args(fn_name, args.to_vec())),
+    }
+}
+
+fn parse_with<T>(s: &str, type_name: &str, parse: fn(&str) -> Option<T>) -> MethodResult<T> {
+    parse(s.trim())
+        .ok_or_else(|| ValError::InvalidCast(format!("\"{s}\""), type_name.into()).into())
+}
+
+fn int_from_str(s: &str) -> Option<i64> {
+    s.parse().ok()
+}
+
+fn float_from_str(s: &str) -> Option<f64> {
+    s.parse().ok()
+}
+
+fn bool_from_str(s: &str) -> Option<bool> {
+    match s.to_ascii_lowercase().as_str() {
+        "true" => Some(true),
+        "false" => Some(false),
+        _ => None,
+    }
+}
+
+fn parse_int(args: Vec<Val>) -> MethodResult<Val> {
+    let s = string_arg("Parse", &args, 1)?;
+    Ok(Val::Int(parse_with(&s, "Int", int_from_str)?))
+}
+
+fn parse_float(args: Vec<Val>) -> MethodResult<Val> {
+    let s = string_arg("Parse", &args, 1)?;
+    Ok(Val::Float(parse_with(&s, "Float", float_from_str)?))
+}
+
+fn parse_bool(args: Vec<Val>) -> MethodResult<Val> {
+    let s = string_arg("Parse", &args, 1)?;
+    Ok(Val::Bool(parse_with(&s, "Bool", bool_from_str)?))
+}
+
+// TryParse tells if the string is valid. [ref] variables are not modeled, so
+// the out variable is not set
+fn try_parse<T>(args: &[Val], parse: fn(&str) -> Option<T>) -> MethodResult<Val> {
+    let s = string_arg("TryParse", args, 2)?;
+    Ok(Val::Bool(parse(s.trim()).is_some()))
+}
+
+fn try_parse_int(args: Vec<Val>) -> MethodResult<Val> {
+    try_parse(&args, int_from_str)
+}
+
+fn try_parse_float(args: Vec<Val>) -> MethodResult<Val> {
+    try_parse(&args, float_from_str)
+}
+
+fn try_parse_bool(args: Vec<Val>) -> MethodResult<Val> {
+    try_parse(&args, bool_from_str)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{NEWLINE, PowerShellSession, PsValue};
+
+    #[test]
+    fn parse() {
+        let mut p = PowerShellSession::new();
+        for (input, expected) in [
+            (r#"[int]::Parse("42")"#, PsValue::Int(42)),
+            (r#"[long]::Parse(" -7 ")"#, PsValue::Int(-7)),
+            (r#"[double]::Parse("3.14")"#, PsValue::Float(3.14)),
+            (r#"[bool]::Parse("True")"#, PsValue::Bool(true)),
+            (r#"[bool]::parse(" false")"#, PsValue::Bool(false)),
+            (r#"[int]::TryParse("12", [ref]$x)"#, PsValue::Bool(true)),
+            (r#"[int]::TryParse("1.5", [ref]$x)"#, PsValue::Bool(false)),
+            (r#"[double]::TryParse("1.5", [ref]$x)"#, PsValue::Bool(true)),
+            (r#"[bool]::TryParse("yes", [ref]$x)"#, PsValue::Bool(false)),
+        ] {
+            let s = p.parse_input(input).unwrap();
+            assert_eq!(s.result(), expected, "{input}");
+        }
+
+        let s = p.parse_input(r#"[int]::Parse("4.2")"#).unwrap();
+        assert_eq!(
+            s.errors()[0].to_string(),
+            "MethodError: RuntimeError: Failed to convert value \"4.2\" to type Int"
+        );
+
+        let s = p.parse_input(r#"[double]::Parse("abc")"#).unwrap();
+        assert_eq!(
+            s.errors()[0].to_string(),
+            "MethodError: RuntimeError: Failed to convert value \"abc\" to type Float"
+        );
+    }
+
+    #[test]
+    fn min_max_value() {
+        let mut p = PowerShellSession::new();
+        for (input, expected) in [
+            ("[int]::MaxValue", PsValue::Int(2147483647)),
+            ("[Int32]::MinValue", PsValue::Int(-2147483648)),
+            ("[byte]::MaxValue", PsValue::Int(255)),
+            ("[long]::MinValue", PsValue::Int(i64::MIN)),
+            ("[char]::maxvalue", PsValue::Char(0xffff)),
+            ("[double]::MaxValue", PsValue::Float(f64::MAX)),
+            ("300 -gt [byte]::MaxValue", PsValue::Bool(true)),
+        ] {
+            let s = p.parse_input(input).unwrap();
+            assert_eq!(s.result(), expected, "{input}");
+        }
+
+        let s = p.parse_input("[bool]::MaxValue").unwrap();
+        assert_eq!(
+            s.errors().last().unwrap().to_string(),
+            "RuntimeError: Member \"MaxValue\" not found"
+        );
+    }
+
+    #[test]
+    fn nan() {
+        let mut p = PowerShellSession::new();
+        let input = r#"$n = [double]::NaN
+$n -eq $n
+[double]::NaN -eq [double]::NaN
+$n -ne $n
+$n + 1
+[double]::PositiveInfinity
+[double]::NegativeInfinity -lt 0"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(
+            s.output(),
+            ["False", "False", "True", "NaN", "Infinity", "True"].join(NEWLINE)
+        );
+        assert_eq!(s.errors().len(), 0);
+        assert_eq!(
+            s.deobfuscated(),
+            [
+                "$n = [double]::NaN",
+                "$false",
+                "$false",
+                "$true",
+                "[double]::NaN",
+                "[double]::PositiveInfinity",
+                "$true",
+            ]
+            .join(NEWLINE)
+        );
+    }
+}
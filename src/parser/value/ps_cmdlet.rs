@@ -0,0 +1,79 @@
+use super::{MethodCallType, MethodError, RuntimeObject, Val, runtime_object::RuntimeResult};
+
+// $PSCmdlet of advanced functions. Confirmation prompts can't be shown, so
+// ShouldProcess and ShouldContinue give the answer configured in the session
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct PsCmdlet {
+    should_process: bool,
+}
+
+impl PsCmdlet {
+    pub(crate) fn new(should_process: bool) -> Self {
+        Self { should_process }
+    }
+}
+
+impl RuntimeObject for PsCmdlet {
+    fn method(&self, name: &str) -> RuntimeResult<MethodCallType> {
+        let answer = self.should_process;
+        match name.to_ascii_lowercase().as_str() {
+            "shouldprocess" | "shouldcontinue" => Ok(Box::new(move |_, args| {
+                if args.is_empty() {
+                    return Err(MethodError::new_incorrect_args("ShouldProcess", args));
+                }
+                Ok(Val::Bool(answer))
+            })),
+            _ => Err(MethodError::MethodNotFound(name.to_string()).into()),
+        }
+    }
+
+    fn name(&self) -> String {
+        "System.Management.Automation.PSScriptCmdlet".to_string()
+    }
+
+    fn clone_object(&self) -> Val {
+        Val::RuntimeObject(Box::new(*self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{PowerShellSession, PsValue};
+
+    #[test]
+    fn should_process() {
+        let input = r#"
+function Remove-Thing {
+    [CmdletBinding(SupportsShouldProcess = $true)]
+    param([string]$Name)
+    if ($PSCmdlet.ShouldProcess($Name, "Remove")) {
+        "removed $Name"
+    } else {
+        "skipped $Name"
+    }
+}
+Remove-Thing -Name "a"
+"#;
+        let s = PowerShellSession::new().parse_input(input).unwrap();
+        assert_eq!(s.result(), PsValue::String("removed a".into()));
+
+        let s = PowerShellSession::new()
+            .with_should_process(false)
+            .parse_input(input)
+            .unwrap();
+        assert_eq!(s.result(), PsValue::String("skipped a".into()));
+
+        let input = r#"function f([Parameter(Mandatory)]$x) { $PSCmdlet.ShouldContinue($x, "c") }
+f "q""#;
+        let s = PowerShellSession::new().parse_input(input).unwrap();
+        assert_eq!(s.result(), PsValue::Bool(true));
+
+        // $PSCmdlet is defined only in advanced functions
+        let input = r#"function f { $PSCmdlet.ShouldProcess("x") }; f"#;
+        let s = PowerShellSession::new().parse_input(input).unwrap();
+        assert_eq!(
+            s.errors().last().unwrap().to_string(),
+            "VariableError: Variable \"pscmdlet\" is not defined"
+        );
+    }
+}
@@ -0,0 +1,163 @@
+use std::collections::BTreeMap;
+
+use super::{NEWLINE, RuntimeError, RuntimeObject, Val, ValType, runtime_object::RuntimeResult};
+
+// [pscustomobject]@{...}. Unlike hashtable, properties keep the order and the
+// case in which they were defined
+#[derive(Debug, Clone, Default)]
+pub(crate) struct PsCustomObject {
+    properties: Vec<(String, Val)>,
+}
+
+impl PsCustomObject {
+    pub(crate) const TYPE_NAME: &'static str = "system.management.automation.pscustomobject";
+
+    pub(crate) fn new(properties: Vec<(String, Val)>) -> Self {
+        let mut obj = Self::default();
+        for (name, value) in properties {
+            // later entry overrides the earlier one, but keeps its position
+            match obj.property_mut(&name) {
+                Some(v) => *v = value,
+                None => obj.properties.push((name, value)),
+            }
+        }
+        obj
+    }
+
+    pub(crate) fn from_hashtable(hashtable: &std::collections::HashMap<String, Val>) -> Self {
+        // hashtable is unordered, so sort the keys to get stable output
+        let sorted = BTreeMap::from_iter(hashtable.clone());
+        Self::new(sorted.into_iter().collect())
+    }
+
+    fn property_mut(&mut self, name: &str) -> Option<&mut Val> {
+        self.properties
+            .iter_mut()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v)
+    }
+}
+
+impl RuntimeObject for PsCustomObject {
+    fn member(&mut self, name: &str) -> RuntimeResult<&mut Val> {
+        self.property_mut(name)
+            .ok_or_else(|| RuntimeError::MemberNotFound(name.to_string()))
+    }
+
+    fn readonly_member(&self, name: &str) -> RuntimeResult<Val> {
+        Ok(self
+            .properties
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.clone())
+            .unwrap_or_default())
+    }
+
+    fn properties(&self) -> Option<Vec<(String, Val)>> {
+        Some(self.properties.clone())
+    }
+
+    fn name(&self) -> String {
+        "System.Management.Automation.PSCustomObject".to_string()
+    }
+
+    fn cast_to_string(&self) -> String {
+        let properties = self
+            .properties
+            .iter()
+            .map(|(k, v)| format!("{}={}", k, v.cast_to_string()))
+            .collect::<Vec<_>>()
+            .join("; ");
+        format!("@{{{properties}}}")
+    }
+
+    fn type_definition(&self) -> RuntimeResult<ValType> {
+        Ok(ValType::RuntimeType(Self::TYPE_NAME.to_string()))
+    }
+
+    fn cast_to_script(&self) -> String {
+        let inner = self
+            .properties
+            .iter()
+            .map(|(k, v)| format!("\t{} = {}", k, v.cast_to_script()))
+            .collect::<Vec<String>>()
+            .join(NEWLINE);
+        format!("[PSCustomObject]@{{{NEWLINE}{}{NEWLINE}}}", inner)
+    }
+
+    fn clone_object(&self) -> Val {
+        Val::RuntimeObject(Box::new(self.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::{NEWLINE, PowerShellSession, PsValue, Variables};
+
+    #[test]
+    fn cast_hashtable() {
+        let mut p = PowerShellSession::new().with_variables(Variables::new().values_persist());
+        let input = r#"$o = [pscustomobject]@{ Name = 'x'; Age = 3 }; $o.Name; $o.age"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(s.output(), ["x", "3"].join(crate::NEWLINE));
+        assert_eq!(
+            s.deobfuscated(),
+            [
+                "$o = [PSCustomObject]@{",
+                "\tName = \"x\"",
+                "\tAge = 3",
+                "}",
+                "\"x\"",
+                "3"
+            ]
+            .join(crate::NEWLINE)
+        );
+
+        let s = p.parse_input(r#""$o""#).unwrap();
+        assert_eq!(s.result(), PsValue::String("@{Name=x; Age=3}".to_string()));
+
+        let s = p.parse_input(r#"$o.Age = 4; $o.Missing; $o"#).unwrap();
+        assert_eq!(s.output(), "@{Name=x; Age=4}");
+        assert_eq!(
+            s.result(),
+            PsValue::HashTable(HashMap::from([
+                ("Name".to_string(), PsValue::String("x".to_string())),
+                ("Age".to_string(), PsValue::Int(4)),
+            ]))
+        );
+
+        let input = r#"$h = @{ b = 2; a = 1 }; [PSCustomObject]$h"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(s.output(), "@{a=1; b=2}");
+
+        let s = p.parse_input(r#"$o / 2"#).unwrap();
+        assert_eq!(
+            s.errors()[0].to_string(),
+            "ValError: Operation \"/\" is not defined for types \
+             \"RuntimeType(\"System.Management.Automation.PSCustomObject\")\" op \"Int\""
+        );
+    }
+
+    #[test]
+    fn pipeline_and_equality() {
+        let mut p = PowerShellSession::new();
+        let input = r#"$users = [pscustomobject]@{ Name = "a"; Age = 30 },
+    [pscustomobject]@{ Name = "b"; Age = 20 },
+    [pscustomobject]@{ Name = "c"; Age = 40 }
+($users | Where-Object { $_.Age -gt 25 } | ForEach-Object { $_.Name }) -join ","
+($users | ? Age -lt 35 | % Name) -join ","
+$users -contains [pscustomobject]@{ Name = "b"; Age = 20 }"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(s.output(), ["a,c", "a,b", "True"].join(NEWLINE));
+
+        // objects are compared by the values of their properties
+        let input = r#"$x = [pscustomobject]@{ a = 1; b = "x" }
+$x -eq [pscustomobject]@{ A = 1; b = "X" }
+$x -ceq [pscustomobject]@{ a = 1; b = "X" }
+$x -eq [pscustomobject]@{ a = 1 }"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(s.output(), ["True", "False", "False"].join(NEWLINE));
+    }
+}
@@ -1,13 +1,19 @@
 use std::{cmp::Ordering, sync::LazyLock};
 
 use smart_default::SmartDefault;
+mod format;
 mod normalize;
 mod pad;
 mod substring;
 mod to_upper_lower;
 mod trim;
-use super::{MethodCallType, MethodError, MethodResult, RuntimeObject, Val, ValType};
-use crate::parser::value::{RuntimeError, runtime_object::RuntimeResult};
+pub(crate) use format::format_with_vec;
+
+use super::{Member, MethodCallType, MethodError, MethodResult, RuntimeObject, Val, ValType};
+use crate::parser::value::{
+    RuntimeError,
+    runtime_object::{RuntimeResult, StaticFnCallType},
+};
 #[derive(Clone, Debug, SmartDefault, PartialEq)]
 pub(crate) struct PsString(pub String);
 
@@ -23,31 +29,109 @@ impl From<String> for PsString {
     }
 }
 
+type StringMethodType = fn(&PsString, Vec<Val>) -> MethodResult<Val>;
+
+impl PsString {
+    // supported methods: name, definition like in Get-Member, implementation
+    const METHODS: [(&'static str, &'static str, StringMethodType); 19] = [
+        ("Clone", "System.Object Clone()", Self::_clone),
+        (
+            "Insert",
+            "string Insert(int startIndex, string value)",
+            Self::insert,
+        ),
+        (
+            "IsNormalized",
+            "bool IsNormalized(), bool IsNormalized(System.Text.NormalizationForm normalizationForm)",
+            Self::is_normalized,
+        ),
+        (
+            "Normalize",
+            "string Normalize(), string Normalize(System.Text.NormalizationForm normalizationForm)",
+            Self::normalize,
+        ),
+        (
+            "PadLeft",
+            "string PadLeft(int totalWidth), string PadLeft(int totalWidth, char paddingChar)",
+            Self::pad_left,
+        ),
+        (
+            "PadRight",
+            "string PadRight(int totalWidth), string PadRight(int totalWidth, char paddingChar)",
+            Self::pad_right,
+        ),
+        (
+            "Remove",
+            "string Remove(int startIndex, int count), string Remove(int startIndex)",
+            Self::remove,
+        ),
+        (
+            "Replace",
+            "string Replace(string oldValue, string newValue), string Replace(char oldChar, char newChar)",
+            Self::replace,
+        ),
+        (
+            "Split",
+            "string[] Split(Params char[] separator), string[] Split(string[] separator, System.StringSplitOptions options)",
+            Self::split,
+        ),
+        (
+            "Substring",
+            "string Substring(int startIndex), string Substring(int startIndex, int length)",
+            Self::substring,
+        ),
+        (
+            "ToCharArray",
+            "char[] ToCharArray(), char[] ToCharArray(int startIndex, int length)",
+            Self::to_char_array,
+        ),
+        (
+            "ToLower",
+            "string ToLower(), string ToLower(cultureinfo culture)",
+            Self::to_lower,
+        ),
+        (
+            "ToLowerInvariant",
+            "string ToLowerInvariant()",
+            Self::to_lower_invariant,
+        ),
+        ("ToString", "string ToString()", Self::_clone),
+        (
+            "ToUpper",
+            "string ToUpper(), string ToUpper(cultureinfo culture)",
+            Self::to_upper,
+        ),
+        (
+            "ToUpperInvariant",
+            "string ToUpperInvariant()",
+            Self::to_upper_invariant,
+        ),
+        (
+            "Trim",
+            "string Trim(Params char[] trimChars), string Trim()",
+            Self::trim,
+        ),
+        (
+            "TrimEnd",
+            "string TrimEnd(Params char[] trimChars)",
+            Self::trim_end,
+        ),
+        (
+            "TrimStart",
+            "string TrimStart(Params char[] trimChars)",
+            Self::trim_start,
+        ),
+    ];
+}
+
 impl RuntimeObject for PsString {
     fn method(&self, name: &str) -> RuntimeResult<MethodCallType> {
-        let name = name.to_ascii_lowercase();
-        let fn_ptr = match name.to_ascii_lowercase().as_str() {
-            "normalize" => Self::normalize,
-            "replace" => Self::replace,
-            "substring" => Self::substring,
-            "remove" => Self::remove,
-            "clone" => Self::_clone,
-            // "copyto" => Self::copyto,
-            // "tocharsarray" => Self::tocharsarray,
-            "isnormalized" => Self::is_normalized,
-            "split" => Self::split,
-            "tostring" => Self::_clone,
-            "toupper" => Self::to_upper,
-            "toupperinvariant" => Self::to_upper_invariant,
-            "tolower" => Self::to_lower,
-            "tolowerinvariant" => Self::to_lower_invariant,
-            "insert" => Self::insert,
-            "padleft" => Self::pad_left,
-            "padright" => Self::pad_right,
-            "trim" => Self::trim,
-            "trimend" => Self::trim_end,
-            "trimstart" => Self::trim_start,
-            _ => Err(RuntimeError::MethodNotFound(name.to_string()))?,
+        let Some((_, _, fn_ptr)) = Self::METHODS
+            .iter()
+            .find(|(method, _, _)| method.eq_ignore_ascii_case(name))
+            .copied()
+        else {
+            Err(RuntimeError::MethodNotFound(name.to_ascii_lowercase()))?
         };
 
         Ok(Box::new(move |v: &Val, args: Vec<Val>| {
@@ -59,6 +143,24 @@ impl RuntimeObject for PsString {
         }))
     }
 
+    fn members(&self) -> Vec<Member> {
+        Self::METHODS
+            .iter()
+            .map(|(name, definition, _)| Member::method(name, definition))
+            .chain([Member::property("Length", "int Length {get;}")])
+            .collect()
+    }
+
+    fn static_method(&self, name: &str) -> RuntimeResult<StaticFnCallType> {
+        let f = match name.to_ascii_lowercase().as_str() {
+            "format" => Self::format,
+            "join" => Self::join,
+            "new" => Self::from_chars,
+            _ => Err(MethodError::MethodNotFound(name.to_string()))?,
+        };
+        Ok(f)
+    }
+
     fn type_definition(&self) -> RuntimeResult<super::ValType> {
         Ok(ValType::String)
     }
@@ -94,6 +196,11 @@ impl PsString {
 
         let old = args[0].cast_to_string();
         let new = args[1].cast_to_string();
+        if old.is_empty() {
+            return Err(MethodError::RuntimeError(
+                "String cannot be of zero length. (Parameter 'oldValue')".to_string(),
+            ));
+        }
         let res = input.replace(&old, &new);
         Ok(Val::String(PsString(res)))
     }
@@ -120,6 +227,74 @@ impl PsString {
         Ok(Val::String(PsString(input)))
     }
 
+    // [string]::Format(fmt, args...), a single array argument is used as the
+    // whole argument list
+    fn format(args: Vec<Val>) -> MethodResult<Val> {
+        let Some((fmt, rest)) = args.split_first() else {
+            return Err(MethodError::new_incorrect_args("Format", args));
+        };
+        let format_args = match rest {
+            [Val::Array(arr)] => arr.clone(),
+            _ => rest.to_vec(),
+        };
+        let res = format_with_vec(&fmt.cast_to_string(), format_args)?;
+        Ok(Val::String(res.into()))
+    }
+
+    // [string]::Join(separator, values...), values are given as an array or as
+    // separate arguments
+    fn join(args: Vec<Val>) -> MethodResult<Val> {
+        let Some((separator, rest)) = args.split_first() else {
+            return Err(MethodError::new_incorrect_args("Join", args));
+        };
+        let values = match rest {
+            [Val::Array(arr)] => arr.as_slice(),
+            _ => rest,
+        };
+        let res = values
+            .iter()
+            .map(|v| v.cast_to_string())
+            .collect::<Vec<_>>()
+            .join(&separator.cast_to_string());
+        Ok(Val::String(res.into()))
+    }
+
+    fn to_char_array(&self, args: Vec<Val>) -> MethodResult<Val> {
+        if !args.is_empty() {
+            return Err(MethodError::new_incorrect_args("ToCharArray", args));
+        }
+        let PsString(input) = self;
+        Ok(Val::Array(
+            input.chars().map(|c| Val::Char(c as u32)).collect(),
+        ))
+    }
+
+    // [string]::new(chars) or [string]::new(char, count)
+    fn from_chars(args: Vec<Val>) -> MethodResult<Val> {
+        let to_char = |c: u32| char::from_u32(c).unwrap_or_default();
+        let chars = match args.as_slice() {
+            [Val::Char(c), Val::Int(count)] if *count >= 0 => {
+                return Ok(Val::String(
+                    to_char(*c).to_string().repeat(*count as usize).into(),
+                ));
+            }
+            [] => return Err(MethodError::new_incorrect_args("new", args)),
+            [Val::Array(chars)] => chars.as_slice(),
+            chars => chars,
+        };
+        let res = chars
+            .iter()
+            .map(|c| match c {
+                Val::Char(c) => Some(to_char(*c)),
+                _ => None,
+            })
+            .collect::<Option<String>>();
+        let Some(res) = res else {
+            return Err(MethodError::new_incorrect_args("new", args));
+        };
+        Ok(Val::String(res.into()))
+    }
+
     fn split(&self, args: Vec<Val>) -> MethodResult<Val> {
         let PsString(mut input) = self.clone();
 
@@ -207,6 +382,16 @@ $string = $string.replace('wo','d')
 $string = $string.replace('rld','ll');$string"#;
         let script_res = p.parse_input(input).unwrap();
         assert_eq!(script_res.result(), PsValue::String("elo.dll".to_string()));
+
+        // no match, the original string is returned
+        let script_res = p.parse_input(r#""abc".Replace("z","y")"#).unwrap();
+        assert_eq!(script_res.result(), PsValue::String("abc".to_string()));
+
+        let script_res = p.parse_input(r#""abc".Replace("","y")"#).unwrap();
+        assert_eq!(
+            script_res.errors()[0].to_string(),
+            "MethodError: RuntimeError: String cannot be of zero length. (Parameter 'oldValue')"
+        );
     }
 
     #[test]
@@ -272,4 +457,64 @@ $string"#;
         let script_res = p.parse_input(input).unwrap();
         assert_eq!(script_res.result(), PsValue::Array(vec![]));
     }
+
+    #[test]
+    fn compare_to() {
+        let mut p = PowerShellSession::new();
+        let script_res = p.parse_input(r#""a".CompareTo("b")"#).unwrap();
+        assert_eq!(script_res.result(), PsValue::Int(-1));
+
+        let script_res = p.parse_input(r#""b".CompareTo("A")"#).unwrap();
+        assert_eq!(script_res.result(), PsValue::Int(1));
+
+        let script_res = p.parse_input(r#"(5).CompareTo(5)"#).unwrap();
+        assert_eq!(script_res.result(), PsValue::Int(0));
+
+        let script_res = p.parse_input(r#"(1.5).CompareTo(2)"#).unwrap();
+        assert_eq!(script_res.result(), PsValue::Int(-1));
+    }
+
+    #[test]
+    fn format_and_join() {
+        let mut p = PowerShellSession::new();
+        let input = r#"$a = "x"; $b = 5; [string]::Format('{0}-{1}', $a, $b)"#;
+        let script_res = p.parse_input(input).unwrap();
+        assert_eq!(script_res.result(), PsValue::String("x-5".to_string()));
+
+        let input = r#"[string]::Format('{1}{0}', @('a', 'b'))"#;
+        let script_res = p.parse_input(input).unwrap();
+        assert_eq!(script_res.result(), PsValue::String("ba".to_string()));
+
+        let input = r#"$arr = 1, 2, 3; [string]::Join(',', $arr)"#;
+        let script_res = p.parse_input(input).unwrap();
+        assert_eq!(script_res.result(), PsValue::String("1,2,3".to_string()));
+
+        let input = r#"[String]::join('', 'a', 'b')"#;
+        let script_res = p.parse_input(input).unwrap();
+        assert_eq!(script_res.result(), PsValue::String("ab".to_string()));
+    }
+
+    #[test]
+    fn char_arrays() {
+        let mut p = PowerShellSession::new();
+        let script_res = p.parse_input(r#""ab".ToCharArray()"#).unwrap();
+        assert_eq!(
+            script_res.result(),
+            PsValue::Array(vec![PsValue::Char(97), PsValue::Char(98)])
+        );
+
+        let script_res = p.parse_input(r#"[char[]]"ab""#).unwrap();
+        assert_eq!(
+            script_res.result(),
+            PsValue::Array(vec![PsValue::Char(97), PsValue::Char(98)])
+        );
+
+        let script_res = p
+            .parse_input(r#"[string]::new([char[]](72, 105))"#)
+            .unwrap();
+        assert_eq!(script_res.result(), PsValue::String("Hi".to_string()));
+
+        let script_res = p.parse_input(r#"[string]::new([char]'=', 3)"#).unwrap();
+        assert_eq!(script_res.result(), PsValue::String("===".to_string()));
+    }
 }
@@ -0,0 +1,107 @@
+use super::Val;
+use crate::parser::value::ValResult;
+
+// implementation of the -f operator and [string]::Format
+pub(crate) fn format_with_vec(fmt: &str, args: Vec<Val>) -> ValResult<String> {
+    fn strange_special_case(fmt: &str, n: i64) -> String {
+        fn split_digits(n: i64) -> Vec<u8> {
+            n.abs() // ignore sign for digit splitting
+                .to_string()
+                .chars()
+                .filter_map(|c| c.to_digit(10).map(|opt| opt as u8))
+                .collect()
+        }
+
+        //"{0:31sdfg,0100a0b00}" -f 578 evals to 310100a5b78
+        let mut digits = split_digits(n);
+        digits.reverse();
+        let mut fmt_vec = fmt.as_bytes().to_vec();
+        fmt_vec.reverse();
+
+        let mut i = 0;
+        for digit in digits {
+            while i < fmt_vec.len() {
+                if fmt_vec[i] != b'0' {
+                    i += 1
+                } else {
+                    fmt_vec[i] = digit + b'0';
+                    break;
+                }
+            }
+        }
+        fmt_vec.reverse();
+        String::from_utf8(fmt_vec).unwrap_or_default()
+    }
+
+    let mut output = String::new();
+    let mut i = 0;
+
+    while i < fmt.len() {
+        if fmt[i..].starts_with('{') {
+            if let Some(end) = fmt[i..].find('}') {
+                let token = &fmt[i + 1..i + end];
+                let formatted = if token.contains(':') {
+                    let mut parts = token.split(':');
+                    let index: usize = if let Some(p) = parts.next() {
+                        p.parse().unwrap_or(0)
+                    } else {
+                        0
+                    };
+
+                    let spec = parts.next();
+                    match args.get(index) {
+                        Some(val) => match spec {
+                            Some(s) if s.starts_with('N') => {
+                                let precision = s[1..].parse::<usize>().unwrap_or(2);
+                                if let Ok(f) = val.cast_to_float() {
+                                    format!("{:.1$}", f, precision)
+                                } else {
+                                    val.cast_to_string().to_string()
+                                }
+                            }
+                            Some(s) => strange_special_case(s, val.cast_to_int()?),
+                            None => val.cast_to_string().to_string(),
+                        },
+                        None => format!("{{{}}}", token), /* leave as-is if index out of
+                                                           * bounds */
+                    }
+                } else if token.contains(',') {
+                    let mut parts = token.split(',');
+                    let index: usize = parts.next().unwrap().parse().unwrap_or(0);
+                    let spec = parts.next();
+                    match args.get(index) {
+                        Some(val) => match spec {
+                            Some(s) => {
+                                let spaces = s.parse::<usize>().unwrap_or(0);
+                                let spaces_str = " ".repeat(spaces);
+                                format!("{spaces_str}{}", val.cast_to_string())
+                            }
+                            _ => val.cast_to_string().to_string(),
+                        },
+                        None => format!("{{{}}}", token), /* leave as-is if index out of
+                                                           * bounds */
+                    }
+                } else {
+                    let index: usize =
+                        Val::String(token.to_string().into()).cast_to_int()? as usize;
+                    match args.get(index) {
+                        Some(val) => val.cast_to_string().to_string(),
+                        None => format!("{{{}}}", token), /* leave as-is if index out of
+                                                           * bounds */
+                    }
+                };
+
+                output.push_str(&formatted);
+                i += end + 1;
+            } else {
+                output.push('{');
+                i += 1;
+            }
+        } else {
+            output.push(fmt[i..].chars().next().unwrap());
+            i += 1;
+        }
+    }
+
+    Ok(output)
+}
@@ -3,6 +3,8 @@ use crate::parser::value::{MethodError, PsString};
 pub type MethodCallType = Box<dyn Fn(&Val, Vec<Val>) -> MethodResult<Val>>;
 pub type StaticFnCallType = fn(Vec<Val>) -> MethodResult<Val>;
 
+use std::collections::BTreeMap;
+
 use thiserror_no_std::Error;
 
 #[derive(Error, Debug, Clone, PartialEq)]
@@ -43,24 +45,84 @@ pub(crate) trait RuntimeObject: std::fmt::Debug {
     fn readonly_static_member(&self, name: &str) -> RuntimeResult<Val> {
         Err(MethodError::NotImplemented(name.to_string()).into())
     }
+    // named properties of objects like pscustomobject, in their order
+    fn properties(&self) -> Option<Vec<(String, Val)>> {
+        None
+    }
+    // methods and properties reported by Get-Member, besides named properties
+    fn members(&self) -> Vec<Member> {
+        vec![]
+    }
+    // items of enumerable objects like $input, casting them to an array
+    // enumerates them
+    fn elements(&self) -> Option<Vec<Val>> {
+        None
+    }
+    // ordering of comparable objects like [version], the other value is
+    // converted to the type of the object
+    fn compare(&self, _other: &Val) -> Option<ValResult<std::cmp::Ordering>> {
+        None
+    }
+    // operators overloaded by objects like [datetime] + [timespan], None when
+    // the operation is not defined for the object
+    fn op_addition(&self, _other: &Val) -> Option<ValResult<Val>> {
+        None
+    }
+    fn op_subtraction(&self, _other: &Val) -> Option<ValResult<Val>> {
+        None
+    }
     fn name(&self) -> String {
         format!("{:?}", self)
     }
+    // text of the object, like in "$obj". Objects which aren't named by their
+    // value, like pscustomobject, override it
+    fn cast_to_string(&self) -> String {
+        self.name()
+    }
     fn type_definition(&self) -> RuntimeResult<ValType> {
         Err(MethodError::NotImplemented("type_definition()".into()).into())
     }
+    fn clone_object(&self) -> Val {
+        ValType::runtime(self.name().as_str()).unwrap_or_default()
+    }
+    fn cast_to_script(&self) -> String {
+        format!("[{}]", self.name())
+    }
 }
 
 impl Val {
     fn get_type(&self, _: Vec<Val>) -> MethodResult<Val> {
         Ok(self.type_info()?.into())
     }
+
+    // like in .NET, strings are compared culture-aware and case-sensitive
+    fn compare_to(&self, args: Vec<Val>) -> MethodResult<Val> {
+        let [other] = args.as_slice() else {
+            return Err(MethodError::new_incorrect_args("CompareTo", args));
+        };
+        let res = if self.lt(other.clone(), false)? {
+            -1
+        } else if self.gt(other.clone(), false)? {
+            1
+        } else {
+            0
+        };
+        Ok(Val::Int(res))
+    }
 }
 
 impl RuntimeObject for Val {
     fn method(&self, name: &str) -> RuntimeResult<MethodCallType> {
         match name {
             "gettype" => return Ok(Box::new(Self::get_type)),
+            "compareto"
+                if matches!(
+                    self,
+                    Val::Bool(_) | Val::Int(_) | Val::Float(_) | Val::Char(_) | Val::String(_)
+                ) =>
+            {
+                return Ok(Box::new(Self::compare_to));
+            }
             _ => {}
         }
         match self {
@@ -84,23 +146,52 @@ impl RuntimeObject for Val {
                 .ok_or_else(|| RuntimeError::MemberNotFound(name.to_string()));
         }
 
+        if let Val::RuntimeObject(rt) = self {
+            return rt.member(name);
+        }
+
         Err(RuntimeError::MemberNotFound(name.to_string()))
     }
 
     fn readonly_member(&self, name: &str) -> RuntimeResult<Val> {
-        // first check the members
+        let name_lowercase = name.to_ascii_lowercase();
+        let is_count = matches!(name_lowercase.as_str(), "count" | "length");
+
+        // first check the members. Keys and values of the hashtable are sorted
+        // by the key, to get stable output
         if let Val::HashTable(ps) = self {
-            return Ok(ps
-                .get(&name.to_ascii_lowercase())
-                .cloned()
-                .unwrap_or_default());
+            match ps.get(&name_lowercase) {
+                Some(val) => return Ok(val.clone()),
+                None if name_lowercase == "keys" || name_lowercase == "values" => {
+                    let sorted = BTreeMap::from_iter(ps.iter());
+                    return Ok(Val::Array(if name_lowercase == "keys" {
+                        sorted
+                            .into_keys()
+                            .map(|k| Val::String(k.clone().into()))
+                            .collect()
+                    } else {
+                        sorted.into_values().cloned().collect()
+                    }));
+                }
+                None if !is_count => return Ok(Val::Null),
+                None => {}
+            }
+        }
+
+        if let Val::RuntimeObject(rt) = self
+            && let Ok(val) = rt.readonly_member(name)
+        {
+            return Ok(val);
         }
 
-        // then check the length property
-        if name.eq_ignore_ascii_case("length") {
+        // then check the count and length properties. Scalars have count 1,
+        // except string, whose length is the number of chars
+        if is_count {
             return Ok(Val::Int(match self {
                 Val::Null => 0,
-                Val::String(PsString(s)) => s.len() as i64,
+                Val::String(PsString(s)) | Val::ScriptText(s) if name_lowercase == "length" => {
+                    s.chars().count() as i64
+                }
                 Val::Array(ar) => ar.len() as i64,
                 Val::HashTable(ht) => ht.len() as i64,
                 _ => 1,
@@ -1,5 +1,5 @@
 use super::{
-    RuntimeObject, RuntimeResult, Val, ValType,
+    InputEnumerator, PsCmdlet, RuntimeObject, RuntimeResult, Val, ValType,
     params::{Param, Params},
 };
 use crate::{
@@ -13,6 +13,9 @@ pub(crate) struct ScriptBlock {
     pub body: String,
     pub raw_text: String,
     pub deobfuscated: Vec<String>,
+    // [CmdletBinding()] or [Parameter()] attributes make the function advanced,
+    // then $PSCmdlet is defined
+    pub advanced: bool,
 }
 
 impl RuntimeObject for ScriptBlock {
@@ -32,6 +35,7 @@ impl ScriptBlock {
             body: script,
             raw_text,
             deobfuscated: Vec::new(),
+            advanced: false,
         }
     }
     pub fn empty() -> Self {
@@ -40,6 +44,7 @@ impl ScriptBlock {
             body: String::new(),
             raw_text: String::new(),
             deobfuscated: Vec::new(),
+            advanced: false,
         }
     }
 
@@ -55,6 +60,7 @@ impl ScriptBlock {
             body: format!("$_.{}", elements),
             raw_text: String::new(),
             deobfuscated: Vec::new(),
+            advanced: false,
         }
     }
     // pub fn to_function(&self, name: &str, scope: &Option<Scope>) -> String {
@@ -86,9 +92,7 @@ impl ScriptBlock {
     pub fn with_params(self, params: Vec<Param>) -> ScriptBlock {
         ScriptBlock {
             params: Params::new(params),
-            body: self.body,
-            raw_text: self.raw_text,
-            deobfuscated: self.deobfuscated,
+            ..self
         }
     }
 
@@ -101,26 +105,46 @@ impl ScriptBlock {
         if self.body.is_empty() {
             return Ok(CommandOutput::new(Val::Null, vec![]));
         }
+        let pipeline_input = ps.pipeline_input.take();
         if let Some(item) = ps_item {
             ps.variables.set_ps_item(item.clone());
+        } else {
+            let items = match pipeline_input {
+                Some(Val::Array(items)) => items,
+                Some(val) => vec![val],
+                None => vec![],
+            };
+            ps.variables
+                .set_local(
+                    "input",
+                    Val::RuntimeObject(Box::new(InputEnumerator::new(items))),
+                )
+                .map_err(ParserError::from)?;
+        }
+        if self.advanced {
+            let cmdlet = PsCmdlet::new(ps.should_process);
+            ps.variables
+                .set_local("pscmdlet", Val::RuntimeObject(Box::new(cmdlet)))
+                .map_err(ParserError::from)?;
         }
 
-        let args = command_args
-            .iter()
-            .filter_map(|arg| {
-                if let CommandElem::Argument(val) = arg {
-                    Some(val.clone())
+        // parameters not bound by name take the positional arguments in order,
+        // switch parameters are never bound by position
+        let (bound_by_name, positional) = self.split_args(&command_args);
+        let mut positional = positional.into_iter();
+        for (param, by_name) in self.params.0.iter().zip(bound_by_name) {
+            let val = if by_name || param.ttype() == Some(ValType::Switch) {
+                None
+            } else {
+                positional.next()
+            };
+            let val = val.or(param.default_value()).unwrap_or_else(|| {
+                if param.ttype() == Some(ValType::Switch) {
+                    Val::Bool(false)
                 } else {
-                    None
+                    Val::Null
                 }
-            })
-            .collect::<Vec<Val>>();
-
-        for (i, param) in self.params.0.iter().enumerate() {
-            let val = args
-                .get(i)
-                .cloned()
-                .unwrap_or(param.default_value().unwrap_or(Val::Null));
+            });
             ps.variables
                 .set_local(param.name(), val)
                 .map_err(ParserError::from)?;
@@ -139,9 +163,10 @@ impl ScriptBlock {
                     } else {
                         let next_arg =
                             if let Some(CommandElem::Argument(val)) = command_args.get(i + 1) {
-                                let v = val.clone();
-                                v.cast_from_type(&param.ttype().unwrap_or(ValType::String))
-                                    .unwrap_or(Val::Null)
+                                match param.ttype() {
+                                    Some(ttype) => val.cast_from_type(&ttype).unwrap_or(Val::Null),
+                                    None => val.clone(),
+                                }
                             } else {
                                 Val::Null
                             };
@@ -158,6 +183,11 @@ impl ScriptBlock {
             }
         }
 
+        // arguments not bound to any parameter are available in $args
+        ps.variables
+            .set_local("args", Val::Array(positional.collect()))
+            .map_err(ParserError::from)?;
+
         let (
             script_last_output,
             Results {
@@ -172,6 +202,34 @@ impl ScriptBlock {
         Ok(CommandOutput::new(script_last_output, deobfuscated))
     }
 
+    // For every parameter whether it is bound by name, and the arguments which
+    // are not values of named parameters
+    fn split_args(&self, command_args: &[CommandElem]) -> (Vec<bool>, Vec<Val>) {
+        let mut named_values = vec![];
+        let mut bound_by_name = vec![];
+        for param in self.params.0.iter() {
+            let pos = command_args
+                .iter()
+                .position(|arg| *arg == CommandElem::Parameter(param.command_param()));
+            if let Some(pos) = pos
+                && param.ttype() != Some(ValType::Switch)
+            {
+                named_values.push(pos + 1);
+            }
+            bound_by_name.push(pos.is_some());
+        }
+
+        let positional = command_args
+            .iter()
+            .enumerate()
+            .filter_map(|(i, arg)| match arg {
+                CommandElem::Argument(val) if !named_values.contains(&i) => Some(val.clone()),
+                _ => None,
+            })
+            .collect();
+        (bound_by_name, positional)
+    }
+
     pub fn run(
         &self,
         args: Vec<CommandElem>,
@@ -185,7 +243,9 @@ impl ScriptBlock {
 
 #[cfg(test)]
 mod tests {
-    use crate::{NEWLINE, PowerShellSession};
+    use std::collections::HashMap;
+
+    use crate::{NEWLINE, PowerShellSession, PsValue, Scope, Variables};
 
     #[test]
     fn simple() {
@@ -209,6 +269,24 @@ mod tests {
         assert_eq!(script_res.errors().len(), 0);
     }
 
+    #[test]
+    fn test_args() {
+        let mut p = PowerShellSession::new();
+        let input = r#"function Invoke-Decoded { $args[0] + $args.Count }
+Invoke-Decoded "a" "b" "c""#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(s.result(), PsValue::String("a3".into()));
+
+        let input = r#"function f($first, [switch]$upper) { "$first|" + ($args -join ",") }
+f -upper 1 2 3"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(s.result(), PsValue::String("1|2,3".into()));
+
+        let input = r#"function f($first) { $args.Count }; f -first 1"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(s.result(), PsValue::Int(0));
+    }
+
     #[test]
     fn test_script_block_default_args() {
         let mut p = PowerShellSession::new();
@@ -256,4 +334,49 @@ mod tests {
         assert_eq!(s.deobfuscated(), "30".to_string());
         assert_eq!(s.result().to_string(), "30".to_string());
     }
+
+    #[test]
+    fn test_script_block_create_and_invoke() {
+        let mut p = PowerShellSession::new();
+        let input = r#"$code = '$x = "a" + ' + "'b'; `$x"
+$sb = [scriptblock]::Create($code)
+$r = $sb.Invoke()
+[ScriptBlock]::Create('param($a, $b) $a * $b').InvokeReturnAsIs(6, 7)"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(s.result().to_string(), "42".to_string());
+        assert_eq!(
+            s.deobfuscated(),
+            [
+                r#"$code = "$x = "a" + 'b'; $x""#,
+                r#"$sb = {$x = "a" + 'b'; $x}"#,
+                r#"$x = "ab""#,
+                r#"$r = "ab""#,
+                "42",
+            ]
+            .join(NEWLINE)
+        );
+        assert_eq!(s.errors().len(), 0);
+    }
+
+    #[test]
+    fn test_script_block_source() {
+        let mut p = PowerShellSession::new();
+        let s = p.parse_input("{ 1+1 }").unwrap();
+        assert_eq!(s.result().script_block_source(), Some("1+1 "));
+
+        let s = p.parse_input("$sb = {param($x) $x * 2}; $sb").unwrap();
+        assert_eq!(s.result().script_block_source(), Some("param($x) $x * 2"));
+
+        // the block keeps its parameters when it's passed to another session
+        let variables = Variables::from_map(
+            Scope::Global,
+            HashMap::from([("sb".to_string(), s.result())]),
+        );
+        let mut p = PowerShellSession::new().with_variables(variables);
+        let s = p.parse_input("& $global:sb 21").unwrap();
+        assert_eq!(s.result(), PsValue::Int(42));
+
+        let s = p.parse_input("'{ 1+1 }'").unwrap();
+        assert_eq!(s.result().script_block_source(), None);
+    }
 }
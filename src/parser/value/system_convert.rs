@@ -1,4 +1,4 @@
-use super::{MethodError, MethodResult, PsString, RuntimeObject, StaticFnCallType, Val};
+use super::{MethodError, MethodResult, PsString, RuntimeObject, StaticFnCallType, Val, ValError};
 use crate::parser::value::runtime_object::RuntimeResult;
 
 #[derive(Debug, Clone)]
@@ -8,6 +8,12 @@ impl RuntimeObject for Convert {
     fn static_method(&self, name: &str) -> RuntimeResult<StaticFnCallType> {
         match name.to_ascii_lowercase().as_str() {
             "frombase64string" => Ok(from_base_64_string),
+            "tobase64string" => Ok(to_base_64_string),
+            "fromhexstring" => Ok(from_hex_string),
+            "tohexstring" => Ok(to_hex_string),
+            "toint32" => Ok(to_int32),
+            "toint64" => Ok(to_int64),
+            "tostring" => Ok(to_string),
             _ => Err(MethodError::MethodNotFound(name.to_string()).into()),
         }
     }
@@ -32,3 +38,262 @@ fn from_base_64_string(args: Vec<Val>) -> MethodResult<Val> {
 
     Ok(Val::Array(x.iter().map(|b| Val::Char(*b as u32)).collect()))
 }
+
+fn to_base_64_string(args: Vec<Val>) -> MethodResult<Val> {
+    use base64::prelude::*;
+
+    let bytes = bytes_from_args("ToBase64String", args)?;
+    Ok(Val::String(BASE64_STANDARD.encode(bytes).into()))
+}
+
+fn from_hex_string(args: Vec<Val>) -> MethodResult<Val> {
+    if args.len() != 1 {
+        //something wrong
+        return Err(MethodError::new_incorrect_args("FromHexString", args));
+    }
+
+    let Val::String(PsString(s)) = &args[0] else {
+        return Err(MethodError::new_incorrect_args("FromHexString", args));
+    };
+
+    if s.len() % 2 != 0 {
+        return Err(MethodError::RuntimeError(
+            "The input is not a valid hex string as its length is not a multiple of 2.".to_string(),
+        ));
+    }
+
+    if !s.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(MethodError::RuntimeError(
+            "The input is not a valid hex string as it contains a non-hex character.".to_string(),
+        ));
+    }
+
+    let x = (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16))
+        .collect::<Result<Vec<u8>, _>>()
+        .map_err(|e| MethodError::RuntimeError(e.to_string()))?;
+
+    Ok(Val::Array(x.iter().map(|b| Val::Char(*b as u32)).collect()))
+}
+
+fn to_hex_string(args: Vec<Val>) -> MethodResult<Val> {
+    let bytes = bytes_from_args("ToHexString", args)?;
+    let s = bytes
+        .iter()
+        .map(|b| format!("{:02X}", b))
+        .collect::<String>();
+    Ok(Val::String(s.into()))
+}
+
+fn to_int32(args: Vec<Val>) -> MethodResult<Val> {
+    to_int("ToInt32", args, 32)
+}
+
+fn to_int64(args: Vec<Val>) -> MethodResult<Val> {
+    to_int("ToInt64", args, 64)
+}
+
+// ToInt32(value) or ToInt32(string, base). In bases other than 10 the digits
+// are the two's complement representation, so "ffffffff" is -1
+fn to_int(fn_name: &str, args: Vec<Val>, bits: u32) -> MethodResult<Val> {
+    let (value, base) = match args.as_slice() {
+        [value] => return Ok(Val::Int(value.cast_to_int()?)),
+        [Val::String(PsString(s)), base] => (s.trim(), base.cast_to_int()?),
+        _ => return Err(MethodError::new_incorrect_args(fn_name, args)),
+    };
+    let invalid = || ValError::InvalidCast(format!("\"{value}\""), format!("Int{bits}"));
+
+    let res = match base {
+        10 if bits == 32 => value.parse::<i32>().map_err(|_| invalid())? as i64,
+        10 => value.parse::<i64>().map_err(|_| invalid())?,
+        2 | 8 | 16 => {
+            let digits = match base {
+                16 => value
+                    .strip_prefix("0x")
+                    .or_else(|| value.strip_prefix("0X"))
+                    .unwrap_or(value),
+                _ => value,
+            };
+            // sign is not allowed, from_str_radix would accept "+"
+            if digits.starts_with('+') {
+                Err(invalid())?
+            }
+            let unsigned = u64::from_str_radix(digits, base as u32).map_err(|_| invalid())?;
+            match bits {
+                32 => u32::try_from(unsigned).map_err(|_| invalid())? as i32 as i64,
+                _ => unsigned as i64,
+            }
+        }
+        _ => Err(ValError::ArgumentOutOfRange(fn_name.to_string(), base))?,
+    };
+    Ok(Val::Int(res))
+}
+
+// ToString(value) or ToString(number, base). Negative numbers are formatted
+// in two's complement, like Int32 if the number fits, otherwise like Int64
+fn to_string(args: Vec<Val>) -> MethodResult<Val> {
+    let (value, base) = match args.as_slice() {
+        [value] => return Ok(Val::String(value.cast_to_string().into())),
+        [value @ (Val::Int(_) | Val::Char(_)), base] => (value.cast_to_int()?, base.cast_to_int()?),
+        _ => return Err(MethodError::new_incorrect_args("ToString", args)),
+    };
+
+    let unsigned = match i32::try_from(value) {
+        Ok(value) => value as u32 as u64,
+        Err(_) => value as u64,
+    };
+    let res = match base {
+        2 => format!("{unsigned:b}"),
+        8 => format!("{unsigned:o}"),
+        10 => value.to_string(),
+        16 => format!("{unsigned:x}"),
+        _ => Err(ValError::ArgumentOutOfRange("ToString".to_string(), base))?,
+    };
+    Ok(Val::String(res.into()))
+}
+
+// byte[] argument can be passed as a single array or, because argument list is
+// flattened, as a list of bytes
+pub(super) fn bytes_from_args(fn_name: &str, args: Vec<Val>) -> MethodResult<Vec<u8>> {
+    let values = match args.as_slice() {
+        [Val::Array(vec)] => vec,
+        [] => return Err(MethodError::new_incorrect_args(fn_name, args)),
+        _ => &args,
+    };
+
+    values
+        .iter()
+        .map(|v| match v {
+            Val::Char(c) => u8::try_from(*c).ok(),
+            Val::Int(i) => u8::try_from(*i).ok(),
+            _ => None,
+        })
+        .collect::<Option<Vec<u8>>>()
+        .ok_or_else(|| MethodError::new_incorrect_args(fn_name, args.clone()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{PowerShellSession, PsValue};
+
+    #[test]
+    fn to_base64_string() {
+        let mut p = PowerShellSession::new();
+        let input = r#"[System.Convert]::ToBase64String([byte[]](104, 105))"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(s.result(), PsValue::String("aGk=".to_string()));
+
+        let input = r#"
+$bytes = [System.Convert]::FromBase64String("aGVsbG8=")
+[System.Convert]::ToBase64String($bytes)"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(s.result(), PsValue::String("aGVsbG8=".to_string()));
+
+        let input = r#"[System.Convert]::ToBase64String(104, 1050)"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(
+            s.errors()[0].to_string(),
+            "MethodError: Incorrect arguments \"[\"Int(104)\", \"Int(1050)\"]\" for method \
+             \"ToBase64String\""
+        );
+    }
+
+    #[test]
+    fn hex_string() {
+        let mut p = PowerShellSession::new();
+        let input = r#"[System.Convert]::ToHexString([byte[]](1, 171, 255))"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(s.result(), PsValue::String("01ABFF".to_string()));
+
+        let input = r#"
+$bytes = [System.Convert]::FromHexString("68656c6C6F")
+[System.Convert]::ToHexString($bytes)"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(s.result(), PsValue::String("68656C6C6F".to_string()));
+
+        let input = r#"[System.Convert]::FromHexString("abc")"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(
+            s.errors()[0].to_string(),
+            "MethodError: RuntimeError: The input is not a valid hex string as its length is not \
+             a multiple of 2."
+        );
+
+        let input = r#"[System.Convert]::FromHexString("+1")"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(
+            s.errors()[0].to_string(),
+            "MethodError: RuntimeError: The input is not a valid hex string as it contains a \
+             non-hex character."
+        );
+    }
+
+    #[test]
+    fn to_int_with_base() {
+        let mut p = PowerShellSession::new();
+        let input = r#"[Convert]::ToInt32('1010', 2) + [Convert]::ToInt32('17', 8) + [Convert]::ToInt32('0xff', 16)"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(s.result(), PsValue::Int(10 + 15 + 255));
+
+        let input = r#"[Convert]::ToInt32('ffffffff', 16), [Convert]::ToInt64('ffffffff', 16), [Convert]::ToInt32(' -12 ', 10)"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(
+            s.result(),
+            PsValue::Array(vec![
+                PsValue::Int(-1),
+                PsValue::Int(0xffffffff),
+                PsValue::Int(-12)
+            ])
+        );
+
+        let input =
+            r#"$c = "1001000","1101001" | % { [char]([Convert]::ToInt32($_, 2)) }; -join $c"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(s.result(), PsValue::String("Hi".to_string()));
+
+        let s = p.parse_input(r#"[Convert]::ToInt32('12', 3)"#).unwrap();
+        assert_eq!(
+            s.errors()[0].to_string(),
+            "MethodError: RuntimeError: Specified argument was out of the range of valid values. \
+             Operation \"ToInt32\", Argument: 3"
+        );
+
+        let s = p.parse_input(r#"[Convert]::ToInt32('102', 2)"#).unwrap();
+        assert_eq!(
+            s.errors()[0].to_string(),
+            "MethodError: RuntimeError: Failed to convert value \"102\" to type Int32"
+        );
+
+        let s = p
+            .parse_input(r#"[Convert]::ToInt32('100000000', 16)"#)
+            .unwrap();
+        assert_eq!(
+            s.errors()[0].to_string(),
+            "MethodError: RuntimeError: Failed to convert value \"100000000\" to type Int32"
+        );
+    }
+
+    #[test]
+    fn to_string_with_base() {
+        let mut p = PowerShellSession::new();
+        let input = r#"[Convert]::ToString(255, 16), [Convert]::ToString(10, 2), [Convert]::ToString(8, 8), [Convert]::ToString(-1, 16), [Convert]::ToString(-5, 10)"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(
+            s.result(),
+            PsValue::Array(
+                ["ff", "1010", "10", "ffffffff", "-5"]
+                    .into_iter()
+                    .map(|s| PsValue::String(s.to_string()))
+                    .collect()
+            )
+        );
+
+        let s = p.parse_input(r#"[Convert]::ToString(10, 3)"#).unwrap();
+        assert_eq!(
+            s.errors()[0].to_string(),
+            "MethodError: RuntimeError: Specified argument was out of the range of valid values. \
+             Operation \"ToString\", Argument: 3"
+        );
+    }
+}
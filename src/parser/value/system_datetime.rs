@@ -0,0 +1,545 @@
+use std::fmt::Write;
+
+use chrono::{
+    Datelike, Local, Months, NaiveDate, NaiveDateTime, TimeDelta, TimeZone, Timelike,
+    format::{Item, StrftimeItems},
+};
+
+use super::{
+    MethodError, MethodResult, RuntimeObject, Val, ValError, ValResult, ValType,
+    runtime_object::{MethodCallType, RuntimeError, RuntimeResult},
+    system_timespan::{TICKS_PER_SECOND, TimeSpan},
+};
+
+// ticks of [datetime]::MaxValue
+const MAX_TICKS: i64 = 3_155_378_975_999_999_999;
+
+// [datetime] type, gives access to the static members
+#[derive(Debug, Clone)]
+pub(crate) struct DateTimeType {}
+
+impl DateTimeType {
+    pub(crate) const TYPE_NAME: &'static str = "system.datetime";
+
+    // static members, the ones like Now are computed from the given local time,
+    // so the session can provide its own current time
+    pub(crate) fn static_member(name: &str, now: NaiveDateTime) -> RuntimeResult<Val> {
+        let date_time = match name.to_ascii_lowercase().as_str() {
+            "now" => now,
+            "utcnow" => Local
+                .from_local_datetime(&now)
+                .earliest()
+                .map_or(now, |local| local.naive_utc()),
+            "today" => now.date().into(),
+            "minvalue" => DateTime::epoch(),
+            "maxvalue" => NaiveDate::from_ymd_opt(9999, 12, 31)
+                .and_then(|d| d.and_hms_nano_opt(23, 59, 59, 999_999_900))
+                .unwrap_or_default(),
+            _ => Err(RuntimeError::MemberNotFound(name.to_string()))?,
+        };
+        Ok(DateTime(date_time).into())
+    }
+}
+
+impl RuntimeObject for DateTimeType {
+    fn readonly_static_member(&self, name: &str) -> RuntimeResult<Val> {
+        Self::static_member(name, Local::now().naive_local())
+    }
+
+    fn name(&self) -> String {
+        "DateTime".to_string()
+    }
+
+    fn type_definition(&self) -> RuntimeResult<ValType> {
+        Ok(ValType::RuntimeType(Self::TYPE_NAME.to_string()))
+    }
+}
+
+// Instance of System.DateTime. Time zone is not kept, it's always a local time
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct DateTime(pub(crate) NaiveDateTime);
+
+impl From<DateTime> for Val {
+    fn from(value: DateTime) -> Self {
+        Val::RuntimeObject(Box::new(value))
+    }
+}
+
+impl DateTime {
+    const PARSE_FORMATS: [&'static str; 6] = [
+        "%Y-%m-%dT%H:%M:%S%.f",
+        "%Y-%m-%d %H:%M:%S%.f",
+        "%m/%d/%Y %H:%M:%S",
+        "%m/%d/%Y %I:%M:%S %p",
+        "%m/%d/%Y %H:%M",
+        "%Y/%m/%d %H:%M:%S",
+    ];
+    const PARSE_DATE_FORMATS: [&'static str; 3] = ["%Y-%m-%d", "%m/%d/%Y", "%Y/%m/%d"];
+
+    pub(crate) fn parse(s: &str) -> ValResult<Self> {
+        let s = s.trim().trim_end_matches('Z');
+        let date_time = Self::PARSE_FORMATS
+            .iter()
+            .find_map(|f| NaiveDateTime::parse_from_str(s, f).ok())
+            .or_else(|| {
+                Self::PARSE_DATE_FORMATS
+                    .iter()
+                    .find_map(|f| NaiveDate::parse_from_str(s, f).ok())
+                    .map(NaiveDateTime::from)
+            });
+
+        date_time
+            .map(DateTime)
+            .ok_or_else(|| ValError::InvalidCast(format!("\"{s}\""), "DateTime".to_string()))
+    }
+
+    pub(crate) fn try_from_val(val: &Val) -> ValResult<Self> {
+        match val {
+            Val::String(s) => Self::parse(&s.0),
+            Val::ScriptText(s) => Self::parse(s),
+            Val::Int(ticks) => Self::from_ticks(*ticks),
+            // other DateTime object
+            Val::RuntimeObject(rt) => match rt.readonly_member("ticks") {
+                Ok(Val::Int(ticks)) => Self::from_ticks(ticks),
+                _ => Err(ValError::InvalidCast(rt.name(), "DateTime".to_string())),
+            },
+            Val::NonDisplayed(val) => Self::try_from_val(val),
+            _ => Err(ValError::InvalidCast(
+                val.ttype().to_string(),
+                "DateTime".to_string(),
+            )),
+        }
+    }
+
+    fn epoch() -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(1, 1, 1).unwrap_or_default().into()
+    }
+
+    fn from_ticks(ticks: i64) -> ValResult<Self> {
+        if !(0..=MAX_TICKS).contains(&ticks) {
+            return Err(ValError::ArgumentOutOfRange("ticks".to_string(), ticks));
+        }
+        let delta = TimeDelta::seconds(ticks.div_euclid(TICKS_PER_SECOND))
+            + TimeDelta::nanoseconds(ticks.rem_euclid(TICKS_PER_SECOND) * 100);
+        Ok(DateTime(Self::epoch() + delta))
+    }
+
+    fn ticks(&self) -> i64 {
+        let delta = self.0 - Self::epoch();
+        delta.num_seconds() * TICKS_PER_SECOND + delta.subsec_nanos() as i64 / 100
+    }
+
+    // [datetime] + [timespan] and [datetime] - [timespan]
+    fn add_span(&self, op: &str, ticks: Option<i64>) -> ValResult<Val> {
+        ticks
+            .and_then(|ticks| self.ticks().checked_add(ticks))
+            .and_then(|ticks| Self::from_ticks(ticks).ok())
+            .map(Val::from)
+            .ok_or_else(|| ValError::ArgumentOutOfRange(op.to_string(), ticks.unwrap_or_default()))
+    }
+
+    fn add(&self, fn_name: &str, args: Vec<Val>, unit_ms: f64) -> MethodResult<Val> {
+        let [value] = args.as_slice() else {
+            return Err(MethodError::new_incorrect_args(fn_name, args));
+        };
+        // .NET rounds the value to milliseconds
+        let ms = (value.cast_to_float()? * unit_ms).round();
+        Some(ms)
+            .filter(|ms| ms.is_finite())
+            .and_then(|ms| TimeDelta::try_milliseconds(ms as i64))
+            .and_then(|delta| self.0.checked_add_signed(delta))
+            .and_then(Self::in_range)
+            .map(Val::from)
+            .ok_or_else(|| Self::out_of_range(fn_name))
+    }
+
+    fn add_months(&self, fn_name: &str, args: Vec<Val>, multiplier: i64) -> MethodResult<Val> {
+        let [value] = args.as_slice() else {
+            return Err(MethodError::new_incorrect_args(fn_name, args));
+        };
+        let months = value
+            .cast_to_int()?
+            .checked_mul(multiplier)
+            .ok_or_else(|| Self::out_of_range(fn_name))?;
+        let count = u32::try_from(months.unsigned_abs())
+            .map(Months::new)
+            .map_err(|_| Self::out_of_range(fn_name))?;
+        let res = if months >= 0 {
+            self.0.checked_add_months(count)
+        } else {
+            self.0.checked_sub_months(count)
+        };
+        res.and_then(Self::in_range)
+            .map(Val::from)
+            .ok_or_else(|| Self::out_of_range(fn_name))
+    }
+
+    // DateTime values are between the years 1 and 9999
+    fn in_range(date_time: NaiveDateTime) -> Option<Self> {
+        (1..=9999)
+            .contains(&date_time.year())
+            .then_some(DateTime(date_time))
+    }
+
+    fn out_of_range(fn_name: &str) -> MethodError {
+        MethodError::RuntimeError(format!(
+            "The added or subtracted value results in an un-representable DateTime. ({fn_name})"
+        ))
+    }
+
+    fn to_string(self, args: Vec<Val>) -> MethodResult<Val> {
+        let s = match args.as_slice() {
+            [] => self.name(),
+            [format] => self.format(&format.cast_to_string())?,
+            _ => return Err(MethodError::new_incorrect_args("ToString", args)),
+        };
+        Ok(Val::String(s.into()))
+    }
+
+    fn day_name(&self, abbreviated: bool) -> String {
+        let name = match self.0.weekday() {
+            chrono::Weekday::Mon => "Monday",
+            chrono::Weekday::Tue => "Tuesday",
+            chrono::Weekday::Wed => "Wednesday",
+            chrono::Weekday::Thu => "Thursday",
+            chrono::Weekday::Fri => "Friday",
+            chrono::Weekday::Sat => "Saturday",
+            chrono::Weekday::Sun => "Sunday",
+        };
+        if abbreviated { &name[..3] } else { name }.to_string()
+    }
+
+    fn month_name(&self, abbreviated: bool) -> String {
+        const MONTHS: [&str; 12] = [
+            "January",
+            "February",
+            "March",
+            "April",
+            "May",
+            "June",
+            "July",
+            "August",
+            "September",
+            "October",
+            "November",
+            "December",
+        ];
+        let name = MONTHS[self.0.month0() as usize];
+        if abbreviated { &name[..3] } else { name }.to_string()
+    }
+
+    // formats the date with .NET format string (en-US culture), as in
+    // Get-Date -Format or $date.ToString(format)
+    pub(crate) fn format(&self, format: &str) -> MethodResult<String> {
+        let format_error =
+            || MethodError::Exception("Input string was not in a correct format.".to_string());
+
+        let custom_format = match format {
+            "" => return Ok(self.name()),
+            "d" => "M/d/yyyy",
+            "D" => "dddd, MMMM d, yyyy",
+            "f" => "dddd, MMMM d, yyyy h:mm tt",
+            "F" | "U" => "dddd, MMMM d, yyyy h:mm:ss tt",
+            "g" => "M/d/yyyy h:mm tt",
+            "G" => "M/d/yyyy h:mm:ss tt",
+            "m" | "M" => "MMMM d",
+            "o" | "O" => "yyyy'-'MM'-'dd'T'HH':'mm':'ss'.'fffffff",
+            "r" | "R" => "ddd, dd MMM yyyy HH':'mm':'ss 'GMT'",
+            "s" => "yyyy'-'MM'-'dd'T'HH':'mm':'ss",
+            "t" => "h:mm tt",
+            "T" => "h:mm:ss tt",
+            "u" => "yyyy'-'MM'-'dd HH':'mm':'ss'Z'",
+            "y" | "Y" => "MMMM yyyy",
+            _ if format.chars().count() == 1 => return Err(format_error()),
+            _ => format,
+        };
+
+        let dt = &self.0;
+        let hour12 = match dt.hour() % 12 {
+            0 => 12,
+            h => h,
+        };
+        let mut res = String::new();
+        let mut chars = custom_format.chars().peekable();
+        while let Some(c) = chars.next() {
+            let mut count = 1;
+            if c.is_ascii_alphabetic() {
+                while chars.next_if_eq(&c).is_some() {
+                    count += 1;
+                }
+            }
+
+            match c {
+                'y' => match count {
+                    1 => res.push_str(&(dt.year() % 100).to_string()),
+                    2 => res.push_str(&format!("{:02}", dt.year() % 100)),
+                    _ => res.push_str(&format!("{:0count$}", dt.year())),
+                },
+                'M' => match count {
+                    1 => res.push_str(&dt.month().to_string()),
+                    2 => res.push_str(&format!("{:02}", dt.month())),
+                    3 => res.push_str(&self.month_name(true)),
+                    _ => res.push_str(&self.month_name(false)),
+                },
+                'd' => match count {
+                    1 => res.push_str(&dt.day().to_string()),
+                    2 => res.push_str(&format!("{:02}", dt.day())),
+                    3 => res.push_str(&self.day_name(true)),
+                    _ => res.push_str(&self.day_name(false)),
+                },
+                'h' if count == 1 => res.push_str(&hour12.to_string()),
+                'h' => res.push_str(&format!("{:02}", hour12)),
+                'H' if count == 1 => res.push_str(&dt.hour().to_string()),
+                'H' => res.push_str(&format!("{:02}", dt.hour())),
+                'm' if count == 1 => res.push_str(&dt.minute().to_string()),
+                'm' => res.push_str(&format!("{:02}", dt.minute())),
+                's' if count == 1 => res.push_str(&dt.second().to_string()),
+                's' => res.push_str(&format!("{:02}", dt.second())),
+                'f' | 'F' => {
+                    if count > 7 {
+                        return Err(format_error());
+                    }
+                    let fraction = format!("{:07}", dt.nanosecond() / 100);
+                    let fraction = &fraction[..count];
+                    if c == 'f' {
+                        res.push_str(fraction);
+                    } else {
+                        res.push_str(fraction.trim_end_matches('0'));
+                    }
+                }
+                't' => {
+                    let designator = if dt.hour() < 12 { "AM" } else { "PM" };
+                    res.push_str(if count == 1 {
+                        &designator[..1]
+                    } else {
+                        designator
+                    });
+                }
+                'g' => res.push_str("A.D."),
+                'z' => {
+                    let offset = Local
+                        .offset_from_local_datetime(dt)
+                        .single()
+                        .map(|o| o.local_minus_utc())
+                        .unwrap_or_default();
+                    let sign = if offset < 0 { '-' } else { '+' };
+                    let (hours, minutes) = (offset.abs() / 3600, offset.abs() % 3600 / 60);
+                    match count {
+                        1 => res.push_str(&format!("{sign}{hours}")),
+                        2 => res.push_str(&format!("{sign}{hours:02}")),
+                        _ => res.push_str(&format!("{sign}{hours:02}:{minutes:02}")),
+                    }
+                }
+                // kind of date is unspecified, so no time zone information
+                'K' => {}
+                '\'' | '"' => {
+                    for literal in chars.by_ref() {
+                        if literal == c {
+                            break;
+                        }
+                        res.push(literal);
+                    }
+                }
+                '\\' => res.push(chars.next().ok_or_else(format_error)?),
+                // "%d" means single custom specifier, not the standard format
+                '%' => {}
+                c => {
+                    for _ in 0..count {
+                        res.push(c);
+                    }
+                }
+            }
+        }
+
+        Ok(res)
+    }
+
+    // formats the date with the unix-like format, as in Get-Date -UFormat
+    pub(crate) fn uformat(&self, format: &str) -> MethodResult<String> {
+        let items = StrftimeItems::new(format).collect::<Vec<_>>();
+        if items.contains(&Item::Error) {
+            return Err(MethodError::Exception(format!(
+                "Invalid UFormat \"{format}\""
+            )));
+        }
+
+        let local = Local
+            .from_local_datetime(&self.0)
+            .earliest()
+            .unwrap_or_else(|| Local.from_utc_datetime(&self.0));
+        let mut res = String::new();
+        write!(res, "{}", local.format_with_items(items.into_iter()))
+            .map_err(|_| MethodError::Exception(format!("Invalid UFormat \"{format}\"")))?;
+        Ok(res)
+    }
+}
+
+impl RuntimeObject for DateTime {
+    fn readonly_member(&self, name: &str) -> RuntimeResult<Val> {
+        let dt = &self.0;
+        Ok(match name.to_ascii_lowercase().as_str() {
+            "year" => Val::Int(dt.year() as i64),
+            "month" => Val::Int(dt.month() as i64),
+            "day" => Val::Int(dt.day() as i64),
+            "hour" => Val::Int(dt.hour() as i64),
+            "minute" => Val::Int(dt.minute() as i64),
+            "second" => Val::Int(dt.second() as i64),
+            "millisecond" => Val::Int((dt.nanosecond() / 1_000_000) as i64),
+            "dayofweek" => Val::String(self.day_name(false).into()),
+            "dayofyear" => Val::Int(dt.ordinal() as i64),
+            "ticks" => Val::Int(self.ticks()),
+            "date" => DateTime(dt.date().into()).into(),
+            _ => Err(RuntimeError::MemberNotFound(name.to_string()))?,
+        })
+    }
+
+    fn method(&self, name: &str) -> RuntimeResult<MethodCallType> {
+        const MS_IN_SECOND: f64 = 1000.;
+        const MS_IN_MINUTE: f64 = 60. * MS_IN_SECOND;
+        const MS_IN_HOUR: f64 = 60. * MS_IN_MINUTE;
+        const MS_IN_DAY: f64 = 24. * MS_IN_HOUR;
+
+        let dt = *self;
+        Ok(match name.to_ascii_lowercase().as_str() {
+            "tostring" => Box::new(move |_, args| dt.to_string(args)),
+            "addmilliseconds" => Box::new(move |_, args| dt.add("AddMilliseconds", args, 1.)),
+            "addseconds" => Box::new(move |_, args| dt.add("AddSeconds", args, MS_IN_SECOND)),
+            "addminutes" => Box::new(move |_, args| dt.add("AddMinutes", args, MS_IN_MINUTE)),
+            "addhours" => Box::new(move |_, args| dt.add("AddHours", args, MS_IN_HOUR)),
+            "adddays" => Box::new(move |_, args| dt.add("AddDays", args, MS_IN_DAY)),
+            "addmonths" => Box::new(move |_, args| dt.add_months("AddMonths", args, 1)),
+            "addyears" => Box::new(move |_, args| dt.add_months("AddYears", args, 12)),
+            _ => Err(MethodError::MethodNotFound(name.to_string()))?,
+        })
+    }
+
+    fn op_addition(&self, other: &Val) -> Option<ValResult<Val>> {
+        Some(TimeSpan::try_from_val(other).and_then(|span| self.add_span("+", Some(span.0))))
+    }
+
+    // difference of dates is a timespan
+    fn op_subtraction(&self, other: &Val) -> Option<ValResult<Val>> {
+        if other.type_definition().ok() == Some(self.type_definition().ok()?) {
+            let other = DateTime::try_from_val(other);
+            return Some(other.map(|other| TimeSpan(self.ticks() - other.ticks()).into()));
+        }
+        Some(
+            TimeSpan::try_from_val(other).and_then(|span| self.add_span("-", span.0.checked_neg())),
+        )
+    }
+
+    // the same as conversion to string in powershell, culture invariant
+    fn name(&self) -> String {
+        self.0.format("%m/%d/%Y %H:%M:%S").to_string()
+    }
+
+    fn type_definition(&self) -> RuntimeResult<ValType> {
+        Ok(ValType::RuntimeType(DateTimeType::TYPE_NAME.to_string()))
+    }
+
+    fn cast_to_script(&self) -> String {
+        format!("[datetime]\"{}\"", self.0.format("%Y-%m-%dT%H:%M:%S%.f"))
+    }
+
+    fn clone_object(&self) -> Val {
+        (*self).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{PowerShellSession, PsValue};
+
+    #[test]
+    fn date_time() {
+        let mut p = PowerShellSession::new();
+        let input = r#"$d = [datetime]"2024-02-28 13:05:09"; $d.AddDays(2).ToString("yyyy-MM-dd")"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(s.result(), PsValue::String("2024-03-01".to_string()));
+
+        let input = r#"$d = [datetime]"2024-02-28 13:05:09"; "$d""#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(
+            s.result(),
+            PsValue::String("02/28/2024 13:05:09".to_string())
+        );
+
+        let input = r#"$d = [datetime]"2024-02-28 13:05:09"; $d.Year, $d.Month, $d.Day, $d.Hour, $d.DayOfWeek"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(
+            s.output(),
+            ["2024", "2", "28", "13", "Wednesday"].join(crate::NEWLINE)
+        );
+
+        let input = r#"([datetime]"2024-01-31").AddMonths(1).AddYears(-1).ToString("d")"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(s.result(), PsValue::String("2/28/2023".to_string()));
+    }
+
+    #[test]
+    fn out_of_range() {
+        let mut p = PowerShellSession::new();
+        let input = r#"$d = [datetime]"2024-02-28"
+$d.AddYears(9223372036854775807)
+$d.AddMonths(4294967296)
+$d.AddYears(8000)
+$m = 0 - [double]::MaxValue; $d.AddDays($m)
+$d.AddMilliseconds([double]::NaN)
+[datetime]3155378976000000000
+[datetime]-1
+$d.AddYears(-2023).Year"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(s.result(), PsValue::Int(1));
+        let errors = s.errors().iter().map(|e| e.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            errors,
+            [
+                "AddYears",
+                "AddMonths",
+                "AddYears",
+                "AddDays",
+                "AddMilliseconds"
+            ]
+            .map(|name| format!(
+                "MethodError: RuntimeError: The added or subtracted value results in an \
+                 un-representable DateTime. ({name})"
+            ))
+            .into_iter()
+            .chain([
+                "ValError: Specified argument was out of the range of valid values. Operation \
+                 \"ticks\", Argument: 3155378976000000000"
+                    .to_string(),
+                "ValError: Specified argument was out of the range of valid values. Operation \
+                 \"ticks\", Argument: -1"
+                    .to_string(),
+            ])
+            .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn format() {
+        let mut p = PowerShellSession::new();
+        for (format, expected) in [
+            ("yyyy-MM-dd HH:mm:ss.fff", "2024-02-08 15:05:09.120"),
+            (
+                "dddd, MMMM d, yyyy h:mm tt",
+                "Thursday, February 8, 2024 3:05 PM",
+            ),
+            ("ddd dd MMM yy", "Thu 08 Feb 24"),
+            ("'Year' yyyy", "Year 2024"),
+            ("%d", "8"),
+            ("FFF", "12"),
+            ("s", "2024-02-08T15:05:09"),
+            ("G", "2/8/2024 3:05:09 PM"),
+        ] {
+            let input = format!(r#"([datetime]"2024-02-08T15:05:09.12").ToString("{format}")"#);
+            let s = p.parse_input(&input).unwrap();
+            assert_eq!(
+                s.result(),
+                PsValue::String(expected.to_string()),
+                "{format}"
+            );
+        }
+    }
+}
@@ -1,6 +1,7 @@
 use super::{
     MethodError, MethodResult, RuntimeObject, Val,
-    runtime_object::{MethodCallType, RuntimeError, RuntimeResult},
+    runtime_object::{MethodCallType, RuntimeError, RuntimeResult, StaticFnCallType},
+    system_convert::bytes_from_args,
 };
 
 #[derive(Debug, Clone)]
@@ -9,63 +10,340 @@ pub(crate) struct Encoding {}
 impl RuntimeObject for Encoding {
     fn readonly_static_member(&self, name: &str) -> RuntimeResult<Val> {
         log::debug!("get_static_member called with name: {}", name);
+        let encoding = match name.to_ascii_lowercase().as_str() {
+            "unicode" => TextEncoding::Unicode,
+            "bigendianunicode" => TextEncoding::BigEndianUnicode,
+            "utf8" => TextEncoding::Utf8,
+            "utf7" => TextEncoding::Utf7,
+            "ascii" => TextEncoding::Ascii,
+            _ => Err(RuntimeError::MemberNotFound(name.to_string()))?,
+        };
+        Ok(Val::RuntimeObject(Box::new(encoding)))
+    }
+
+    fn static_method(&self, name: &str) -> RuntimeResult<StaticFnCallType> {
         match name.to_ascii_lowercase().as_str() {
-            "unicode" => Ok(Val::RuntimeObject(Box::new(UnicodeEncoding {}))),
-            _ => Err(RuntimeError::MemberNotFound(name.to_string())),
+            "convert" => Ok(convert),
+            _ => Err(MethodError::MethodNotFound(name.to_string()).into()),
         }
     }
 }
 
-#[derive(Debug, Clone)]
-pub(crate) struct UnicodeEncoding {}
+// [Text.Encoding]::Convert(src, dst, bytes) decodes the bytes with the source
+// encoding and encodes the text with the destination one
+fn convert(args: Vec<Val>) -> MethodResult<Val> {
+    let [src, dst, bytes] = args.as_slice() else {
+        return Err(MethodError::new_incorrect_args("Convert", args));
+    };
+    let (Some(src), Some(dst)) = (TextEncoding::from_val(src), TextEncoding::from_val(dst)) else {
+        return Err(MethodError::new_incorrect_args("Convert", args));
+    };
+
+    let text = src.get_string(vec![bytes.clone()])?;
+    dst.get_bytes(vec![text])
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum TextEncoding {
+    Unicode,
+    BigEndianUnicode,
+    Utf8,
+    Utf7,
+    Ascii,
+}
 
-impl RuntimeObject for UnicodeEncoding {
+impl RuntimeObject for TextEncoding {
     fn method(&self, name: &str) -> RuntimeResult<MethodCallType> {
+        let encoding = *self;
         match name.to_ascii_lowercase().as_str() {
-            "getstring" => Ok(Box::new(get_string)),
+            "getstring" => Ok(Box::new(move |_, args| encoding.get_string(args))),
+            "getbytes" => Ok(Box::new(move |_, args| encoding.get_bytes(args))),
             _ => Err(MethodError::MethodNotFound(name.to_string()).into()),
         }
     }
+
+    fn name(&self) -> String {
+        match self {
+            TextEncoding::Unicode => "UnicodeEncoding",
+            TextEncoding::BigEndianUnicode => "BigEndianUnicodeEncoding",
+            TextEncoding::Utf8 => "UTF8Encoding",
+            TextEncoding::Utf7 => "UTF7Encoding",
+            TextEncoding::Ascii => "ASCIIEncoding",
+        }
+        .to_string()
+    }
 }
 
-fn get_string(_: &Val, args: Vec<Val>) -> MethodResult<Val> {
-    if args.len() != 1 {
-        //something wrong
-        return Err(MethodError::new_incorrect_args("getstring", args));
+impl TextEncoding {
+    const ALL: [TextEncoding; 5] = [
+        TextEncoding::Unicode,
+        TextEncoding::BigEndianUnicode,
+        TextEncoding::Utf8,
+        TextEncoding::Utf7,
+        TextEncoding::Ascii,
+    ];
+
+    // encodings are passed as runtime objects, so they are recognized by name
+    fn from_val(val: &Val) -> Option<TextEncoding> {
+        let Val::RuntimeObject(rt) = val else {
+            return None;
+        };
+        let name = rt.name();
+        Self::ALL
+            .into_iter()
+            .find(|encoding| encoding.name() == name)
     }
 
-    let arg = args[0].clone();
-    let Val::Array(box_vec) = arg else {
-        return Err(MethodError::new_incorrect_args("getstring", args));
-    };
+    fn get_string(&self, args: Vec<Val>) -> MethodResult<Val> {
+        let buf = bytes_from_args("GetString", args)?;
 
-    let v = box_vec
-        .iter()
-        .map(|v| {
-            let Val::Char(c) = v else {
-                return Err(MethodError::new_incorrect_args("getstring", args.clone()));
-            };
-            Ok(*c as u8)
-        })
-        .collect::<Result<Vec<u8>, _>>()?;
-
-    Ok(Val::String(string_from_vec(v).into()))
+        let s = match self {
+            TextEncoding::Unicode => string_from_utf16(&buf, u16::from_le_bytes),
+            TextEncoding::BigEndianUnicode => string_from_utf16(&buf, u16::from_be_bytes),
+            TextEncoding::Utf8 => String::from_utf8_lossy(&buf).to_string(),
+            TextEncoding::Utf7 => utf7_decode(&buf),
+            TextEncoding::Ascii => buf
+                .iter()
+                .map(|b| if b.is_ascii() { *b as char } else { '?' })
+                .collect(),
+        };
+
+        Ok(Val::String(s.into()))
+    }
+
+    fn get_bytes(&self, args: Vec<Val>) -> MethodResult<Val> {
+        let [arg @ (Val::String(_) | Val::Char(_))] = args.as_slice() else {
+            return Err(MethodError::new_incorrect_args("GetBytes", args));
+        };
+        let s = arg.cast_to_string();
+
+        let buf = match self {
+            TextEncoding::Unicode => s.encode_utf16().flat_map(u16::to_le_bytes).collect(),
+            TextEncoding::BigEndianUnicode => s.encode_utf16().flat_map(u16::to_be_bytes).collect(),
+            TextEncoding::Utf8 => s.into_bytes(),
+            TextEncoding::Utf7 => utf7_encode(&s),
+            TextEncoding::Ascii => s
+                .chars()
+                .map(|c| if c.is_ascii() { c as u8 } else { b'?' })
+                .collect::<Vec<u8>>(),
+        };
+
+        Ok(Val::Array(
+            buf.into_iter().map(|b| Val::Char(b as u32)).collect(),
+        ))
+    }
 }
 
-fn string_from_vec(mut buf: Vec<u8>) -> String {
-    let u16_buffer = unsafe { buf.align_to_mut::<u16>().1 };
+fn string_from_utf16(buf: &[u8], from_bytes: fn([u8; 2]) -> u16) -> String {
+    let u16_buffer = buf
+        .chunks_exact(2)
+        .map(|pair| from_bytes([pair[0], pair[1]]))
+        .collect::<Vec<u16>>();
 
-    let mut ends_with_null = false;
+    let mut res_string = String::from_utf16_lossy(&u16_buffer);
     if let Some(c) = u16_buffer.last()
         && *c == 0
     {
-        ends_with_null = true;
-    }
-
-    let mut res_string = String::from_utf16_lossy(u16_buffer);
-    if ends_with_null {
         res_string.pop();
     }
 
     res_string
 }
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn base64_value(b: u8) -> Option<u32> {
+    BASE64_ALPHABET
+        .iter()
+        .position(|c| *c == b)
+        .map(|pos| pos as u32)
+}
+
+// UTF-7 (RFC 2152): ascii characters are written directly, everything else is
+// written as modified base64 of UTF-16BE, enclosed between '+' and '-'
+fn utf7_decode(buf: &[u8]) -> String {
+    let mut units = Vec::new();
+    let mut i = 0;
+    while i < buf.len() {
+        if buf[i] != b'+' {
+            units.push(buf[i] as u16);
+            i += 1;
+            continue;
+        }
+
+        i += 1;
+        if buf.get(i) == Some(&b'-') {
+            units.push(b'+' as u16);
+            i += 1;
+            continue;
+        }
+
+        let mut bits = 0u32;
+        let mut bits_len = 0;
+        while let Some(value) = buf.get(i).and_then(|b| base64_value(*b)) {
+            bits = (bits << 6) | value;
+            bits_len += 6;
+            if bits_len >= 16 {
+                bits_len -= 16;
+                units.push((bits >> bits_len) as u16);
+                bits &= (1 << bits_len) - 1;
+            }
+            i += 1;
+        }
+
+        if buf.get(i) == Some(&b'-') {
+            i += 1;
+        }
+    }
+
+    String::from_utf16_lossy(&units)
+}
+
+fn utf7_encode(s: &str) -> Vec<u8> {
+    fn is_direct(c: char) -> bool {
+        c.is_ascii_alphanumeric() || "'(),-./:? \t\r\n".contains(c)
+    }
+
+    fn flush(res: &mut Vec<u8>, bits: &mut u32, bits_len: &mut u32) {
+        if *bits_len > 0 {
+            res.push(BASE64_ALPHABET[((*bits << (6 - *bits_len)) & 0x3f) as usize]);
+        }
+        *bits = 0;
+        *bits_len = 0;
+        res.push(b'-');
+    }
+
+    let mut res = Vec::new();
+    let mut in_base64 = false;
+    let mut bits = 0u32;
+    let mut bits_len = 0;
+    for c in s.chars() {
+        if is_direct(c) {
+            if in_base64 {
+                flush(&mut res, &mut bits, &mut bits_len);
+                in_base64 = false;
+            }
+            res.push(c as u8);
+            continue;
+        }
+
+        if c == '+' && !in_base64 {
+            res.extend_from_slice(b"+-");
+            continue;
+        }
+
+        if !in_base64 {
+            res.push(b'+');
+            in_base64 = true;
+        }
+
+        let mut units = [0u16; 2];
+        for unit in c.encode_utf16(&mut units) {
+            bits = (bits << 16) | *unit as u32;
+            bits_len += 16;
+            while bits_len >= 6 {
+                bits_len -= 6;
+                res.push(BASE64_ALPHABET[((bits >> bits_len) & 0x3f) as usize]);
+            }
+            bits &= (1 << bits_len) - 1;
+        }
+    }
+
+    if in_base64 {
+        flush(&mut res, &mut bits, &mut bits_len);
+    }
+
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{PowerShellSession, PsValue};
+
+    #[test]
+    fn get_string() {
+        let mut p = PowerShellSession::new();
+        let input = r#"
+$b64 = "aABlAGwAbABvAA=="
+[System.Text.Encoding]::Unicode.GetString([System.Convert]::FromBase64String($b64))"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(s.result(), PsValue::String("hello".to_string()));
+
+        let input =
+            r#"[Text.Encoding]::UTF8.GetString([Convert]::FromBase64String("xbzDs8WCdw=="))"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(s.result(), PsValue::String("żółw".to_string()));
+
+        let input = r#"[Text.Encoding]::ASCII.GetString([byte[]](104, 105, 200))"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(s.result(), PsValue::String("hi?".to_string()));
+
+        let input = r#"[Text.Encoding]::BigEndianUnicode.GetString([byte[]](0, 104, 0, 105))"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(s.result(), PsValue::String("hi".to_string()));
+
+        let input =
+            r#"[Text.Encoding]::UTF7.GetString([Text.Encoding]::ASCII.GetBytes("1 +- 2 +AOQ-!"))"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(s.result(), PsValue::String("1 + 2 ä!".to_string()));
+    }
+
+    #[test]
+    fn get_bytes() {
+        let mut p = PowerShellSession::new();
+        let input = r#"[Text.Encoding]::Unicode.GetBytes("hi")"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(
+            s.result(),
+            PsValue::Array(vec![
+                PsValue::Char(104),
+                PsValue::Char(0),
+                PsValue::Char(105),
+                PsValue::Char(0)
+            ])
+        );
+
+        for encoding in ["Unicode", "BigEndianUnicode", "UTF8", "UTF7"] {
+            let input = format!(
+                r#"$e = [Text.Encoding]::{encoding}; $e.GetString($e.GetBytes("a+b=żółw!"))"#
+            );
+            let s = p.parse_input(&input).unwrap();
+            assert_eq!(s.result(), PsValue::String("a+b=żółw!".to_string()));
+        }
+
+        let input = r#"[Convert]::ToBase64String([Text.Encoding]::UTF7.GetBytes("a+b=ä"))"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(s.result(), PsValue::String("YSstYitBRDBBNUEt".to_string()));
+
+        let input = r#"[Text.Encoding]::UTF8.GetBytes(1, 2)"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(
+            s.errors()[0].to_string(),
+            "MethodError: Incorrect arguments \"[\"Int(1)\", \"Int(2)\"]\" for method \"GetBytes\""
+        );
+    }
+
+    #[test]
+    fn convert() {
+        let mut p = PowerShellSession::new();
+        let input = r#"$bytes = [Text.Encoding]::UTF8.GetBytes("hi")
+[Text.Encoding]::Convert([Text.Encoding]::UTF8, [Text.Encoding]::ASCII, $bytes)"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(
+            s.result(),
+            PsValue::Array(vec![PsValue::Char(104), PsValue::Char(105)])
+        );
+
+        let input = r#"[Text.Encoding]::Convert([Text.Encoding]::UTF8, [Text.Encoding]::Unicode, [Text.Encoding]::UTF8.GetBytes("ż"))"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(
+            s.result(),
+            PsValue::Array(vec![PsValue::Char(124), PsValue::Char(1)])
+        );
+
+        let input = r#"[Text.Encoding]::Convert([Text.Encoding]::UTF8, 1, @(104))"#;
+        let s = p.parse_input(input).unwrap();
+        assert!(s.errors()[0].to_string().contains("\"Convert\""));
+    }
+}
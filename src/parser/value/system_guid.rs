@@ -0,0 +1,233 @@
+use super::{
+    Member, MethodError, MethodResult, RuntimeObject, Val, ValError, ValResult, ValType,
+    runtime_object::{MethodCallType, RuntimeError, RuntimeResult, StaticFnCallType},
+};
+
+// [guid] type, gives access to the static members. NewGuid() uses the random
+// generator of the session, so it's evaluated by the parser
+#[derive(Debug, Clone)]
+pub(crate) struct GuidType {}
+
+impl GuidType {
+    pub(crate) const TYPE_NAME: &'static str = "system.guid";
+}
+
+impl RuntimeObject for GuidType {
+    fn readonly_static_member(&self, name: &str) -> RuntimeResult<Val> {
+        match name.to_ascii_lowercase().as_str() {
+            "empty" => Ok(Guid(0).into()),
+            _ => Err(RuntimeError::MemberNotFound(name.to_string())),
+        }
+    }
+
+    fn static_method(&self, name: &str) -> RuntimeResult<StaticFnCallType> {
+        match name.to_ascii_lowercase().as_str() {
+            "parse" => Ok(|args| {
+                let [input] = args.as_slice() else {
+                    return Err(MethodError::new_incorrect_args("Parse", args));
+                };
+                Ok(Guid::try_from_val(input)?.into())
+            }),
+            _ => Err(MethodError::MethodNotFound(name.to_string()).into()),
+        }
+    }
+
+    fn name(&self) -> String {
+        "Guid".to_string()
+    }
+
+    fn type_definition(&self) -> RuntimeResult<ValType> {
+        Ok(ValType::RuntimeType(Self::TYPE_NAME.to_string()))
+    }
+}
+
+// Instance of System.Guid, the 32 hex digits in the order they are written
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Guid(u128);
+
+impl From<Guid> for Val {
+    fn from(value: Guid) -> Self {
+        Val::RuntimeObject(Box::new(value))
+    }
+}
+
+impl Guid {
+    // version 4 guid, the version and variant bits are set like in .NET
+    pub(crate) fn from_random(high: u64, low: u64) -> Self {
+        let mut value = ((high as u128) << 64) | low as u128;
+        value = (value & !(0xf << 76)) | (0x4 << 76);
+        value = (value & !(0x3 << 62)) | (0x2 << 62);
+        Guid(value)
+    }
+
+    // accepts the "N", "D", "B" and "P" formats
+    pub(crate) fn parse(s: &str) -> ValResult<Self> {
+        let invalid = || ValError::InvalidCast(format!("\"{s}\""), "Guid".to_string());
+        let trimmed = s.trim();
+        let inner = match (trimmed.chars().next(), trimmed.chars().last()) {
+            (Some('{'), Some('}')) | (Some('('), Some(')')) => &trimmed[1..trimmed.len() - 1],
+            _ => trimmed,
+        };
+
+        let digits = match inner.len() {
+            32 => inner.to_string(),
+            36 if [8, 13, 18, 23].iter().all(|&i| inner.as_bytes()[i] == b'-') => {
+                inner.replace('-', "")
+            }
+            _ => return Err(invalid()),
+        };
+        if digits.len() != 32 || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(invalid());
+        }
+        u128::from_str_radix(&digits, 16)
+            .map(Guid)
+            .map_err(|_| invalid())
+    }
+
+    pub(crate) fn try_from_val(val: &Val) -> ValResult<Self> {
+        match val {
+            Val::String(s) => Self::parse(&s.0),
+            Val::ScriptText(s) => Self::parse(s),
+            // other Guid object
+            Val::RuntimeObject(rt) => match rt.readonly_member("guid") {
+                Ok(Val::String(s)) => Self::parse(&s.0),
+                _ => Err(ValError::InvalidCast(rt.name(), "Guid".to_string())),
+            },
+            Val::NonDisplayed(val) => Self::try_from_val(val),
+            _ => Err(ValError::InvalidCast(
+                val.ttype().to_string(),
+                "Guid".to_string(),
+            )),
+        }
+    }
+
+    fn format(&self, format: &str) -> MethodResult<String> {
+        let hex = format!("{:032x}", self.0);
+        let dashed = format!(
+            "{}-{}-{}-{}-{}",
+            &hex[..8],
+            &hex[8..12],
+            &hex[12..16],
+            &hex[16..20],
+            &hex[20..]
+        );
+        Ok(match format {
+            "" | "D" | "d" => dashed,
+            "N" | "n" => hex,
+            "B" | "b" => format!("{{{dashed}}}"),
+            "P" | "p" => format!("({dashed})"),
+            _ => Err(MethodError::Exception(format!(
+                "Format string can be only \"D\", \"d\", \"N\", \"n\", \"P\", \"p\", \"B\", \
+                 \"b\", \"X\" or \"x\", not \"{format}\""
+            )))?,
+        })
+    }
+
+    fn to_string(self, args: Vec<Val>) -> MethodResult<Val> {
+        let format = match args.as_slice() {
+            [] => String::new(),
+            [format] => format.cast_to_string(),
+            _ => return Err(MethodError::new_incorrect_args("ToString", args)),
+        };
+        Ok(Val::String(self.format(&format)?.into()))
+    }
+}
+
+impl RuntimeObject for Guid {
+    fn readonly_member(&self, name: &str) -> RuntimeResult<Val> {
+        match name.to_ascii_lowercase().as_str() {
+            "guid" => Ok(Val::String(self.name().into())),
+            _ => Err(RuntimeError::MemberNotFound(name.to_string())),
+        }
+    }
+
+    fn method(&self, name: &str) -> RuntimeResult<MethodCallType> {
+        let guid = *self;
+        Ok(match name.to_ascii_lowercase().as_str() {
+            "tostring" => Box::new(move |_, args| guid.to_string(args)),
+            _ => Err(MethodError::MethodNotFound(name.to_string()))?,
+        })
+    }
+
+    fn members(&self) -> Vec<Member> {
+        vec![
+            Member::method(
+                "ToString",
+                "string ToString(), string ToString(string format)",
+            ),
+            Member::property("Guid", "string Guid {get;}"),
+        ]
+    }
+
+    fn name(&self) -> String {
+        self.format("D").unwrap_or_default()
+    }
+
+    fn type_definition(&self) -> RuntimeResult<ValType> {
+        Ok(ValType::RuntimeType(GuidType::TYPE_NAME.to_string()))
+    }
+
+    fn cast_to_script(&self) -> String {
+        format!("[guid]\"{}\"", self.name())
+    }
+
+    fn clone_object(&self) -> Val {
+        (*self).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{NEWLINE, PowerShellSession, PsValue};
+
+    #[test]
+    fn new_guid() {
+        let script = "[guid]::NewGuid().ToString()";
+        let first = PowerShellSession::new()
+            .with_random_seed(7)
+            .safe_eval(script);
+        let second = PowerShellSession::new()
+            .with_random_seed(7)
+            .safe_eval(script);
+        assert_eq!(first, second);
+
+        let guid = first.unwrap();
+        assert_eq!(guid.len(), 36);
+        assert_eq!(&guid[14..15], "4");
+        assert!(matches!(&guid[19..20], "8" | "9" | "a" | "b"));
+    }
+
+    #[test]
+    fn cast_guid() {
+        let mut p = PowerShellSession::new();
+        let input = r#"$g = [guid]"{6F9619FF-8B86-D011-B42D-00C04FC964FF}"
+$g.Guid
+"Global\$($g.ToString("N"))"
+$g -eq [guid]"6f9619ff8b86d011b42d00c04fc964ff"
+[guid]::Empty"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(
+            s.output(),
+            [
+                "6f9619ff-8b86-d011-b42d-00c04fc964ff",
+                "Global\\6f9619ff8b86d011b42d00c04fc964ff",
+                "True",
+                "00000000-0000-0000-0000-000000000000",
+            ]
+            .join(NEWLINE)
+        );
+
+        let s = p.parse_input(r#"[guid]"not-a-guid""#).unwrap();
+        assert_eq!(
+            s.errors()[0].to_string(),
+            "ValError: Failed to convert value \"not-a-guid\" to type Guid"
+        );
+        let s = p
+            .parse_input(r#"[guid]::Parse("(6f9619ff-8b86-d011-b42d-00c04fc964ff)")"#)
+            .unwrap();
+        assert_eq!(
+            s.result(),
+            PsValue::String("6f9619ff-8b86-d011-b42d-00c04fc964ff".into())
+        );
+    }
+}
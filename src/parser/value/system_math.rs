@@ -0,0 +1,286 @@
+use super::{MethodError, MethodResult, RuntimeObject, StaticFnCallType, Val, ValType};
+use crate::parser::value::runtime_object::{RuntimeError, RuntimeResult};
+
+// .NET rounds to at most 15 fractional digits
+const MAX_ROUND_DIGITS: i64 = 15;
+
+#[derive(Debug, Clone)]
+pub(crate) struct Math {}
+
+impl RuntimeObject for Math {
+    fn static_method(&self, name: &str) -> RuntimeResult<StaticFnCallType> {
+        match name.to_ascii_lowercase().as_str() {
+            "pow" => Ok(pow),
+            "sqrt" => Ok(sqrt),
+            "abs" => Ok(abs),
+            "floor" => Ok(floor),
+            "ceiling" => Ok(ceiling),
+            "min" => Ok(min),
+            "max" => Ok(max),
+            "round" => Ok(round),
+            _ => Err(MethodError::MethodNotFound(name.to_string()).into()),
+        }
+    }
+}
+
+fn float_args<const N: usize>(fn_name: &str, args: &[Val]) -> MethodResult<[f64; N]> {
+    let Ok(args_array) = <&[Val; N]>::try_from(args) else {
+        return Err(MethodError::new_incorrect_args(fn_name, args.to_vec()));
+    };
+
+    let mut res = [0.; N];
+    for (i, arg) in args_array.iter().enumerate() {
+        res[i] = arg.cast_to_float()?;
+    }
+    Ok(res)
+}
+
+fn pow(args: Vec<Val>) -> MethodResult<Val> {
+    let [x, y] = float_args("Pow", &args)?;
+    Ok(Val::Float(x.powf(y)))
+}
+
+fn sqrt(args: Vec<Val>) -> MethodResult<Val> {
+    let [x] = float_args("Sqrt", &args)?;
+    Ok(Val::Float(x.sqrt()))
+}
+
+fn floor(args: Vec<Val>) -> MethodResult<Val> {
+    let [x] = float_args("Floor", &args)?;
+    Ok(Val::Float(x.floor()))
+}
+
+fn ceiling(args: Vec<Val>) -> MethodResult<Val> {
+    let [x] = float_args("Ceiling", &args)?;
+    Ok(Val::Float(x.ceil()))
+}
+
+// Abs, Min and Max are overloaded, integer arguments give integer result
+fn abs(args: Vec<Val>) -> MethodResult<Val> {
+    match args.as_slice() {
+        [Val::Int(i)] => Ok(Val::Int(i.abs())),
+        _ => {
+            let [x] = float_args("Abs", &args)?;
+            Ok(Val::Float(x.abs()))
+        }
+    }
+}
+
+fn min(args: Vec<Val>) -> MethodResult<Val> {
+    match args.as_slice() {
+        [Val::Int(a), Val::Int(b)] => Ok(Val::Int(*a.min(b))),
+        _ => {
+            let [a, b] = float_args("Min", &args)?;
+            Ok(Val::Float(a.min(b)))
+        }
+    }
+}
+
+fn max(args: Vec<Val>) -> MethodResult<Val> {
+    match args.as_slice() {
+        [Val::Int(a), Val::Int(b)] => Ok(Val::Int(*a.max(b))),
+        _ => {
+            let [a, b] = float_args("Max", &args)?;
+            Ok(Val::Float(a.max(b)))
+        }
+    }
+}
+
+// Round(x), Round(x, digits), Round(x, mode) and Round(x, digits, mode). Like
+// in .NET, the midpoint is rounded to the even number by default
+fn round(args: Vec<Val>) -> MethodResult<Val> {
+    let incorrect_args = || MethodError::new_incorrect_args("Round", args.clone());
+    let (x, digits, mode) = match args.as_slice() {
+        [x] => (x, None, None),
+        [x, mode @ Val::String(_)] => (x, None, Some(mode)),
+        [x, digits] => (x, Some(digits), None),
+        [x, digits, mode] => (x, Some(digits), Some(mode)),
+        _ => return Err(incorrect_args()),
+    };
+
+    let x = x.cast_to_float()?;
+    let digits = digits
+        .map(Val::cast_to_int)
+        .transpose()?
+        .unwrap_or_default();
+    if !(0..=MAX_ROUND_DIGITS).contains(&digits) {
+        return Err(incorrect_args());
+    }
+    let mode = match mode {
+        Some(mode) => MidpointRounding::from_val(mode).ok_or_else(incorrect_args)?,
+        None => MidpointRounding::ToEven,
+    };
+
+    // the decimal point is moved in the decimal representation, so 2.345 is
+    // scaled to 234.5, not to 234.50000000000003
+    let scale = 10f64.powi(digits as i32);
+    let scaled = format!("{x}e{digits}").parse().unwrap_or(x * scale);
+    let rounded = match mode {
+        MidpointRounding::ToEven => Val::round_bankers(scaled),
+        MidpointRounding::AwayFromZero => scaled.round(),
+        MidpointRounding::ToZero => scaled.trunc(),
+        MidpointRounding::ToNegativeInfinity => scaled.floor(),
+        MidpointRounding::ToPositiveInfinity => scaled.ceil(),
+    };
+    Ok(Val::Float(rounded / scale))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum MidpointRounding {
+    ToEven,
+    AwayFromZero,
+    ToZero,
+    ToNegativeInfinity,
+    ToPositiveInfinity,
+}
+
+impl MidpointRounding {
+    const ALL: [MidpointRounding; 5] = [
+        MidpointRounding::ToEven,
+        MidpointRounding::AwayFromZero,
+        MidpointRounding::ToZero,
+        MidpointRounding::ToNegativeInfinity,
+        MidpointRounding::ToPositiveInfinity,
+    ];
+
+    // enum value is given by its name, or by its number
+    fn from_val(val: &Val) -> Option<Self> {
+        match val {
+            Val::Int(i) => Self::ALL.get(usize::try_from(*i).ok()?).copied(),
+            _ => {
+                let name = val.cast_to_string();
+                Self::ALL
+                    .into_iter()
+                    .find(|mode| format!("{mode:?}").eq_ignore_ascii_case(&name))
+            }
+        }
+    }
+}
+
+// [MidpointRounding] type, its members are the names of rounding modes
+impl RuntimeObject for MidpointRounding {
+    fn readonly_static_member(&self, name: &str) -> RuntimeResult<Val> {
+        let Some(mode) = Self::from_val(&Val::String(name.into())) else {
+            return Err(RuntimeError::MemberNotFound(name.to_string()));
+        };
+        Ok(Val::String(format!("{mode:?}").into()))
+    }
+
+    fn name(&self) -> String {
+        "System.MidpointRounding".to_string()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Type {}
+
+impl Type {
+    pub(crate) const TYPE_NAME: &'static str = "system.type";
+}
+
+impl RuntimeObject for Type {
+    fn static_method(&self, name: &str) -> RuntimeResult<StaticFnCallType> {
+        match name.to_ascii_lowercase().as_str() {
+            "gettype" => Ok(get_type),
+            _ => Err(MethodError::MethodNotFound(name.to_string()).into()),
+        }
+    }
+
+    fn type_definition(&self) -> RuntimeResult<ValType> {
+        Ok(ValType::RuntimeType(Self::TYPE_NAME.to_string()))
+    }
+}
+
+// Type.GetType returns null when the type is unknown
+fn get_type(args: Vec<Val>) -> MethodResult<Val> {
+    let [Val::String(name)] = args.as_slice() else {
+        return Err(MethodError::new_incorrect_args("GetType", args));
+    };
+
+    Ok(ValType::runtime(&name.0).unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{PowerShellSession, PsValue};
+
+    #[test]
+    fn math() {
+        let mut p = PowerShellSession::new();
+        let s = p.parse_input("[System.Math]::Pow(2, 10)").unwrap();
+        assert_eq!(s.result(), PsValue::Float(1024.));
+
+        let s = p.parse_input("[math]::Sqrt(16), [math]::Abs(-2)").unwrap();
+        assert_eq!(
+            s.result(),
+            PsValue::Array(vec![PsValue::Float(4.), PsValue::Int(2)])
+        );
+
+        let s = p
+            .parse_input("[math]::Floor(2.5), [math]::Ceiling(2.5), [math]::Max(2, 3)")
+            .unwrap();
+        assert_eq!(
+            s.result(),
+            PsValue::Array(vec![
+                PsValue::Float(2.),
+                PsValue::Float(3.),
+                PsValue::Int(3)
+            ])
+        );
+
+        let s = p.parse_input("[math]::Pow(2)").unwrap();
+        assert_eq!(
+            s.errors()[0].to_string(),
+            "MethodError: Incorrect arguments \"[\"Int(2)\"]\" for method \"Pow\""
+        );
+    }
+
+    #[test]
+    fn round() {
+        let mut p = PowerShellSession::new();
+        let s = p
+            .parse_input("[Math]::Round(2.5), [Math]::Round(3.5), [Math]::Round(-2.5)")
+            .unwrap();
+        assert_eq!(
+            s.result(),
+            PsValue::Array(vec![
+                PsValue::Float(2.),
+                PsValue::Float(4.),
+                PsValue::Float(-2.)
+            ])
+        );
+
+        let input = r#"[Math]::Round(2.345, 2)
+[Math]::Round(2.345, 2, [MidpointRounding]::AwayFromZero)
+[Math]::Round(2.5, [System.MidpointRounding]::AwayFromZero)
+[Math]::Round(-2.567, 1, "ToZero")"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(
+            s.output(),
+            ["2.34", "2.35", "3", "-2.5"].join(crate::NEWLINE)
+        );
+
+        let s = p.parse_input("[MidpointRounding]::awayfromzero").unwrap();
+        assert_eq!(s.result(), PsValue::String("AwayFromZero".into()));
+
+        let s = p.parse_input("[Math]::Round(2.5, 16)").unwrap();
+        assert_eq!(s.errors().len(), 1);
+    }
+
+    #[test]
+    fn type_get_type() {
+        let mut p = PowerShellSession::new();
+        let input = r#"$m = [type]::GetType("System.Math"); $m::Pow(2, 3)"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(s.result(), PsValue::Float(8.));
+
+        let input =
+            r#"[System.Type]::GetType("System.Convert")::ToBase64String([byte[]](104, 105))"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(s.result(), PsValue::String("aGk=".to_string()));
+
+        let input = r#"$null -eq [type]::GetType("System.IO.Unknown")"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(s.result(), PsValue::Bool(true));
+    }
+}
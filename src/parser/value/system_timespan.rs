@@ -0,0 +1,415 @@
+use std::cmp::Ordering;
+
+use super::{
+    Member, MethodError, MethodResult, RuntimeObject, Val, ValError, ValResult, ValType,
+    runtime_object::{MethodCallType, RuntimeError, RuntimeResult, StaticFnCallType},
+};
+
+pub(crate) const TICKS_PER_MILLISECOND: i64 = 10_000;
+pub(crate) const TICKS_PER_SECOND: i64 = 1000 * TICKS_PER_MILLISECOND;
+const TICKS_PER_MINUTE: i64 = 60 * TICKS_PER_SECOND;
+const TICKS_PER_HOUR: i64 = 60 * TICKS_PER_MINUTE;
+const TICKS_PER_DAY: i64 = 24 * TICKS_PER_HOUR;
+
+// [timespan] type, gives access to the static members
+#[derive(Debug, Clone)]
+pub(crate) struct TimeSpanType {}
+
+impl TimeSpanType {
+    pub(crate) const TYPE_NAME: &'static str = "system.timespan";
+}
+
+impl RuntimeObject for TimeSpanType {
+    fn static_method(&self, name: &str) -> RuntimeResult<StaticFnCallType> {
+        Ok(match name.to_ascii_lowercase().as_str() {
+            "new" => |args| {
+                let components = args
+                    .iter()
+                    .map(|arg| Ok(arg.cast_to_int()?))
+                    .collect::<MethodResult<Vec<_>>>()?;
+                let ticks = match components.as_slice() {
+                    [ticks] => Some(*ticks),
+                    [hours, minutes, seconds] => {
+                        TimeSpan::ticks_from(0, *hours, *minutes, *seconds, 0)
+                    }
+                    [days, hours, minutes, seconds] => {
+                        TimeSpan::ticks_from(*days, *hours, *minutes, *seconds, 0)
+                    }
+                    [days, hours, minutes, seconds, milliseconds] => {
+                        TimeSpan::ticks_from(*days, *hours, *minutes, *seconds, *milliseconds)
+                    }
+                    _ => return Err(MethodError::new_incorrect_args("new", args)),
+                };
+                let ticks = ticks.ok_or_else(|| TimeSpan::too_long("new"))?;
+                Ok(TimeSpan(ticks).into())
+            },
+            "parse" => |args| {
+                let [input] = args.as_slice() else {
+                    return Err(MethodError::new_incorrect_args("Parse", args));
+                };
+                Ok(TimeSpan::parse(&input.cast_to_string())?.into())
+            },
+            "fromdays" => |args| TimeSpan::from_units("FromDays", args, TICKS_PER_DAY),
+            "fromhours" => |args| TimeSpan::from_units("FromHours", args, TICKS_PER_HOUR),
+            "fromminutes" => |args| TimeSpan::from_units("FromMinutes", args, TICKS_PER_MINUTE),
+            "fromseconds" => |args| TimeSpan::from_units("FromSeconds", args, TICKS_PER_SECOND),
+            "frommilliseconds" => {
+                |args| TimeSpan::from_units("FromMilliseconds", args, TICKS_PER_MILLISECOND)
+            }
+            "fromticks" => |args| TimeSpan::from_units("FromTicks", args, 1),
+            _ => Err(MethodError::MethodNotFound(name.to_string()))?,
+        })
+    }
+
+    fn readonly_static_member(&self, name: &str) -> RuntimeResult<Val> {
+        let ticks = match name.to_ascii_lowercase().as_str() {
+            "zero" => 0,
+            "maxvalue" => i64::MAX,
+            "minvalue" => i64::MIN,
+            _ => Err(RuntimeError::MemberNotFound(name.to_string()))?,
+        };
+        Ok(TimeSpan(ticks).into())
+    }
+
+    fn name(&self) -> String {
+        "TimeSpan".to_string()
+    }
+
+    fn type_definition(&self) -> RuntimeResult<ValType> {
+        Ok(ValType::RuntimeType(Self::TYPE_NAME.to_string()))
+    }
+}
+
+// Instance of System.TimeSpan, kept as the number of 100ns ticks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) struct TimeSpan(pub(crate) i64);
+
+impl From<TimeSpan> for Val {
+    fn from(value: TimeSpan) -> Self {
+        Val::RuntimeObject(Box::new(value))
+    }
+}
+
+impl TimeSpan {
+    fn ticks_from(days: i64, hours: i64, minutes: i64, seconds: i64, ms: i64) -> Option<i64> {
+        [
+            (days, TICKS_PER_DAY),
+            (hours, TICKS_PER_HOUR),
+            (minutes, TICKS_PER_MINUTE),
+            (seconds, TICKS_PER_SECOND),
+            (ms, TICKS_PER_MILLISECOND),
+        ]
+        .iter()
+        .try_fold(0i64, |ticks, (value, unit)| {
+            ticks.checked_add(value.checked_mul(*unit)?)
+        })
+    }
+
+    fn too_long(fn_name: &str) -> MethodError {
+        MethodError::Exception(format!(
+            "TimeSpan overflowed because the duration is too long. ({fn_name})"
+        ))
+    }
+
+    // like in .NET, the value is rounded to milliseconds, except for ticks
+    fn from_units(fn_name: &str, args: Vec<Val>, unit_ticks: i64) -> MethodResult<Val> {
+        let [value] = args.as_slice() else {
+            return Err(MethodError::new_incorrect_args(fn_name, args));
+        };
+        let ticks = if unit_ticks == 1 {
+            value.cast_to_int()? as f64
+        } else {
+            let ms = value.cast_to_float()? * (unit_ticks / TICKS_PER_MILLISECOND) as f64;
+            ms.round() * TICKS_PER_MILLISECOND as f64
+        };
+        if !ticks.is_finite() || ticks >= i64::MAX as f64 || ticks < i64::MIN as f64 {
+            return Err(Self::too_long(fn_name));
+        }
+        Ok(TimeSpan(ticks as i64).into())
+    }
+
+    // "[-]d" or "[-][d.]hh:mm[:ss[.fffffff]]"
+    pub(crate) fn parse(s: &str) -> ValResult<Self> {
+        let invalid = || ValError::InvalidCast(format!("\"{s}\""), "TimeSpan".to_string());
+        let number = |c: &str| c.parse::<i64>().map_err(|_| invalid());
+        let trimmed = s.trim();
+        let (negative, trimmed) = match trimmed.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, trimmed),
+        };
+
+        let parts = trimmed.split(':').collect::<Vec<_>>();
+        let ticks = match parts.as_slice() {
+            [days] => Self::ticks_from(number(days)?, 0, 0, 0, 0),
+            [hours, minutes, rest @ ..] if rest.len() <= 1 => {
+                let (days, hours) = match hours.split_once('.') {
+                    Some((days, hours)) => (number(days)?, number(hours)?),
+                    None => (0, number(hours)?),
+                };
+                let minutes = number(minutes)?;
+                let (seconds, fraction) = match rest.first().map(|s| s.split_once('.')) {
+                    Some(Some((seconds, fraction))) => (number(seconds)?, fraction),
+                    Some(None) => (number(rest[0])?, ""),
+                    None => (0, ""),
+                };
+                if hours > 23 || minutes > 59 || seconds > 59 || fraction.len() > 7 {
+                    Err(invalid())?
+                }
+                let fraction_ticks = match fraction {
+                    "" => 0,
+                    _ => number(&format!("{fraction:0<7}"))?,
+                };
+                Self::ticks_from(days, hours, minutes, seconds, 0)
+                    .and_then(|ticks| ticks.checked_add(fraction_ticks))
+            }
+            _ => Err(invalid())?,
+        };
+        let ticks = ticks.ok_or_else(invalid)?;
+        Ok(TimeSpan(if negative { -ticks } else { ticks }))
+    }
+
+    pub(crate) fn try_from_val(val: &Val) -> ValResult<Self> {
+        match val {
+            Val::String(s) => Self::parse(&s.0),
+            Val::ScriptText(s) => Self::parse(s),
+            Val::Int(ticks) => Ok(TimeSpan(*ticks)),
+            // other TimeSpan object
+            Val::RuntimeObject(rt)
+                if rt.type_definition().ok()
+                    == Some(ValType::RuntimeType(TimeSpanType::TYPE_NAME.to_string())) =>
+            {
+                match rt.readonly_member("ticks") {
+                    Ok(Val::Int(ticks)) => Ok(TimeSpan(ticks)),
+                    _ => Err(ValError::InvalidCast(
+                        rt.cast_to_string(),
+                        "TimeSpan".to_string(),
+                    )),
+                }
+            }
+            Val::NonDisplayed(val) => Self::try_from_val(val),
+            _ => Err(ValError::InvalidCast(
+                val.cast_to_string(),
+                "TimeSpan".to_string(),
+            )),
+        }
+    }
+
+    // days, hours, minutes, seconds and ticks of the second
+    fn components(&self) -> (i64, i64, i64, i64, i64) {
+        let ticks = self.0;
+        (
+            ticks / TICKS_PER_DAY,
+            ticks % TICKS_PER_DAY / TICKS_PER_HOUR,
+            ticks % TICKS_PER_HOUR / TICKS_PER_MINUTE,
+            ticks % TICKS_PER_MINUTE / TICKS_PER_SECOND,
+            ticks % TICKS_PER_SECOND,
+        )
+    }
+
+    fn total(&self, unit_ticks: i64) -> Val {
+        Val::Float(self.0 as f64 / unit_ticks as f64)
+    }
+
+    fn checked_add(&self, other: &Val, op: &str, negate: bool) -> ValResult<Self> {
+        let other = Self::try_from_val(other)?.0;
+        let other = if negate {
+            other.checked_neg()
+        } else {
+            Some(other)
+        };
+        other
+            .and_then(|other| self.0.checked_add(other))
+            .map(TimeSpan)
+            .ok_or_else(|| ValError::ArgumentOutOfRange(op.to_string(), self.0))
+    }
+}
+
+impl RuntimeObject for TimeSpan {
+    fn readonly_member(&self, name: &str) -> RuntimeResult<Val> {
+        let (days, hours, minutes, seconds, ticks) = self.components();
+        Ok(match name.to_ascii_lowercase().as_str() {
+            "days" => Val::Int(days),
+            "hours" => Val::Int(hours),
+            "minutes" => Val::Int(minutes),
+            "seconds" => Val::Int(seconds),
+            "milliseconds" => Val::Int(ticks / TICKS_PER_MILLISECOND),
+            "ticks" => Val::Int(self.0),
+            "totaldays" => self.total(TICKS_PER_DAY),
+            "totalhours" => self.total(TICKS_PER_HOUR),
+            "totalminutes" => self.total(TICKS_PER_MINUTE),
+            "totalseconds" => self.total(TICKS_PER_SECOND),
+            "totalmilliseconds" => self.total(TICKS_PER_MILLISECOND),
+            _ => Err(RuntimeError::MemberNotFound(name.to_string()))?,
+        })
+    }
+
+    fn method(&self, name: &str) -> RuntimeResult<MethodCallType> {
+        let span = *self;
+        Ok(match name.to_ascii_lowercase().as_str() {
+            "tostring" => Box::new(move |_, args| {
+                if !args.is_empty() {
+                    return Err(MethodError::new_incorrect_args("ToString", args));
+                }
+                Ok(Val::String(span.cast_to_string().into()))
+            }),
+            "add" | "subtract" => {
+                let (fn_name, negate) = match name.to_ascii_lowercase().as_str() {
+                    "add" => ("Add", false),
+                    _ => ("Subtract", true),
+                };
+                Box::new(move |_, args| {
+                    let [other] = args.as_slice() else {
+                        return Err(MethodError::new_incorrect_args(fn_name, args));
+                    };
+                    Ok(span.checked_add(other, fn_name, negate)?.into())
+                })
+            }
+            "negate" => Box::new(move |_, _| {
+                Ok(TimeSpan(
+                    span.0
+                        .checked_neg()
+                        .ok_or_else(|| Self::too_long("Negate"))?,
+                )
+                .into())
+            }),
+            "duration" => Box::new(move |_, _| {
+                Ok(TimeSpan(
+                    span.0
+                        .checked_abs()
+                        .ok_or_else(|| Self::too_long("Duration"))?,
+                )
+                .into())
+            }),
+            _ => Err(MethodError::MethodNotFound(name.to_string()))?,
+        })
+    }
+
+    fn members(&self) -> Vec<Member> {
+        vec![
+            Member::method("Add", "timespan Add(timespan ts)"),
+            Member::method("Duration", "timespan Duration()"),
+            Member::method("Negate", "timespan Negate()"),
+            Member::method("Subtract", "timespan Subtract(timespan ts)"),
+            Member::method("ToString", "string ToString()"),
+            Member::property("Days", "int Days {get;}"),
+            Member::property("Hours", "int Hours {get;}"),
+            Member::property("Milliseconds", "int Milliseconds {get;}"),
+            Member::property("Minutes", "int Minutes {get;}"),
+            Member::property("Seconds", "int Seconds {get;}"),
+            Member::property("Ticks", "long Ticks {get;}"),
+            Member::property("TotalDays", "double TotalDays {get;}"),
+            Member::property("TotalHours", "double TotalHours {get;}"),
+            Member::property("TotalMilliseconds", "double TotalMilliseconds {get;}"),
+            Member::property("TotalMinutes", "double TotalMinutes {get;}"),
+            Member::property("TotalSeconds", "double TotalSeconds {get;}"),
+        ]
+    }
+
+    fn compare(&self, other: &Val) -> Option<ValResult<Ordering>> {
+        Some(TimeSpan::try_from_val(other).map(|other| self.cmp(&other)))
+    }
+
+    fn op_addition(&self, other: &Val) -> Option<ValResult<Val>> {
+        Some(self.checked_add(other, "+", false).map(Val::from))
+    }
+
+    fn op_subtraction(&self, other: &Val) -> Option<ValResult<Val>> {
+        Some(self.checked_add(other, "-", true).map(Val::from))
+    }
+
+    fn name(&self) -> String {
+        "System.TimeSpan".to_string()
+    }
+
+    // "[-][d.]hh:mm:ss[.fffffff]", like the constant format of .NET
+    fn cast_to_string(&self) -> String {
+        let (days, hours, minutes, seconds, ticks) = self.components();
+        let sign = if self.0 < 0 { "-" } else { "" };
+        let mut s = sign.to_string();
+        if days != 0 {
+            s += &format!("{}.", days.unsigned_abs());
+        }
+        s += &format!(
+            "{:02}:{:02}:{:02}",
+            hours.unsigned_abs(),
+            minutes.unsigned_abs(),
+            seconds.unsigned_abs()
+        );
+        if ticks != 0 {
+            s += &format!(".{:07}", ticks.unsigned_abs());
+        }
+        s
+    }
+
+    fn type_definition(&self) -> RuntimeResult<ValType> {
+        Ok(ValType::RuntimeType(TimeSpanType::TYPE_NAME.to_string()))
+    }
+
+    fn cast_to_script(&self) -> String {
+        format!("[timespan]\"{}\"", self.cast_to_string())
+    }
+
+    fn clone_object(&self) -> Val {
+        (*self).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{NEWLINE, PowerShellSession};
+
+    #[test]
+    fn date_time_arithmetic() {
+        let mut p = PowerShellSession::new();
+        let input = r#"$d = [datetime]"2024-02-28 13:05:09"
+$t = [timespan]"1.02:03:04.5"
+($d + $t).ToString("yyyy-MM-dd HH:mm:ss.fff")
+$d - [timespan]::FromMinutes(90)
+$diff = [datetime]"2024-03-01" - $d
+$diff
+$diff.TotalHours
+$t + [timespan]::new(1, 2, 3)
+$t.Days, $t.Hours, $t.Milliseconds
+[timespan]"00:30" -lt [timespan]::FromHours(1)"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(
+            s.output(),
+            [
+                "2024-02-29 15:08:13.500",
+                "02/28/2024 11:35:09",
+                "1.10:54:51",
+                "34.9141666666667",
+                "1.03:05:07.5000000",
+                "1",
+                "2",
+                "500",
+                "True",
+            ]
+            .join(NEWLINE)
+        );
+        assert_eq!(s.errors().len(), 0);
+        assert_eq!(
+            s.deobfuscated().lines().nth(4),
+            Some(r#"$diff = [timespan]"1.10:54:51""#)
+        );
+
+        let s = p
+            .parse_input("[datetime]::MaxValue + [timespan]::FromDays(1)")
+            .unwrap();
+        assert_eq!(
+            s.errors()[0].to_string(),
+            "ValError: Specified argument was out of the range of valid values. Operation \"+\", \
+             Argument: 864000000000"
+        );
+
+        let s = p
+            .parse_input(r#"$t = [timespan]"01:00"; $t; $t / 2"#)
+            .unwrap();
+        assert_eq!(s.output(), "01:00:00");
+        assert_eq!(
+            s.errors()[0].to_string(),
+            "ValError: Operation \"/\" is not defined for types \"RuntimeType(\"System.TimeSpan\")\" \
+             op \"Int\""
+        );
+    }
+}
@@ -0,0 +1,251 @@
+use std::cmp::Ordering;
+
+use super::{
+    Member, MethodError, MethodResult, RuntimeObject, Val, ValError, ValResult, ValType,
+    runtime_object::{MethodCallType, RuntimeError, RuntimeResult, StaticFnCallType},
+};
+
+// [version] type, gives access to the static members
+#[derive(Debug, Clone)]
+pub(crate) struct VersionType {}
+
+impl VersionType {
+    pub(crate) const TYPE_NAME: &'static str = "system.version";
+}
+
+impl RuntimeObject for VersionType {
+    fn static_method(&self, name: &str) -> RuntimeResult<StaticFnCallType> {
+        match name.to_ascii_lowercase().as_str() {
+            "new" => Ok(|args| {
+                if let [input] = args.as_slice() {
+                    return Ok(Version::try_from_val(input)?.into());
+                }
+                let components = args
+                    .iter()
+                    .map(|arg| Ok(arg.cast_to_int()?))
+                    .collect::<MethodResult<Vec<_>>>()?;
+                let version = match components.as_slice() {
+                    [major, minor] => Version::new(*major, *minor, -1, -1),
+                    [major, minor, build] => Version::new(*major, *minor, *build, -1),
+                    [major, minor, build, revision] => {
+                        Version::new(*major, *minor, *build, *revision)
+                    }
+                    _ => return Err(MethodError::new_incorrect_args("new", args)),
+                };
+                Ok(version.into())
+            }),
+            "parse" => Ok(|args| {
+                let [input] = args.as_slice() else {
+                    return Err(MethodError::new_incorrect_args("Parse", args));
+                };
+                Ok(Version::try_from_val(input)?.into())
+            }),
+            _ => Err(MethodError::MethodNotFound(name.to_string()).into()),
+        }
+    }
+
+    fn name(&self) -> String {
+        "Version".to_string()
+    }
+
+    fn type_definition(&self) -> RuntimeResult<ValType> {
+        Ok(ValType::RuntimeType(Self::TYPE_NAME.to_string()))
+    }
+}
+
+// Instance of System.Version. Like in .NET, undefined build and revision are
+// -1, so they are lower than 0 when versions are compared
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) struct Version {
+    major: i64,
+    minor: i64,
+    build: i64,
+    revision: i64,
+}
+
+impl From<Version> for Val {
+    fn from(value: Version) -> Self {
+        Val::RuntimeObject(Box::new(value))
+    }
+}
+
+impl Version {
+    pub(crate) fn new(major: i64, minor: i64, build: i64, revision: i64) -> Self {
+        Self {
+            major,
+            minor,
+            build,
+            revision,
+        }
+    }
+
+    // "major.minor[.build[.revision]]"
+    pub(crate) fn parse(s: &str) -> ValResult<Self> {
+        let invalid = || ValError::InvalidCast(format!("\"{s}\""), "Version".to_string());
+        let components = s
+            .trim()
+            .split('.')
+            .map(|c| c.parse::<u32>().map(i64::from).map_err(|_| invalid()))
+            .collect::<ValResult<Vec<_>>>()?;
+        Ok(match components.as_slice() {
+            [major, minor] => Self::new(*major, *minor, -1, -1),
+            [major, minor, build] => Self::new(*major, *minor, *build, -1),
+            [major, minor, build, revision] => Self::new(*major, *minor, *build, *revision),
+            _ => Err(invalid())?,
+        })
+    }
+
+    pub(crate) fn try_from_val(val: &Val) -> ValResult<Self> {
+        match val {
+            Val::String(s) => Self::parse(&s.0),
+            Val::ScriptText(s) => Self::parse(s),
+            Val::Int(_) | Val::Float(_) => Self::parse(&val.cast_to_string()),
+            // other Version object
+            Val::RuntimeObject(rt)
+                if rt.type_definition().ok()
+                    == Some(ValType::RuntimeType(VersionType::TYPE_NAME.to_string())) =>
+            {
+                Self::parse(&rt.name())
+            }
+            Val::NonDisplayed(val) => Self::try_from_val(val),
+            _ => Err(ValError::InvalidCast(
+                val.ttype().to_string(),
+                "Version".to_string(),
+            )),
+        }
+    }
+
+    // first field_count components, joined with dots
+    fn format(&self, field_count: usize) -> MethodResult<String> {
+        let components = [self.major, self.minor, self.build, self.revision];
+        let defined = components.iter().take_while(|c| **c >= 0).count();
+        if field_count > defined {
+            return Err(MethodError::Exception(format!(
+                "The argument must be between 0 and {defined}."
+            )));
+        }
+        Ok(components[..field_count]
+            .iter()
+            .map(i64::to_string)
+            .collect::<Vec<_>>()
+            .join("."))
+    }
+}
+
+impl RuntimeObject for Version {
+    fn readonly_member(&self, name: &str) -> RuntimeResult<Val> {
+        match name.to_ascii_lowercase().as_str() {
+            "major" => Ok(Val::Int(self.major)),
+            "minor" => Ok(Val::Int(self.minor)),
+            "build" => Ok(Val::Int(self.build)),
+            "revision" => Ok(Val::Int(self.revision)),
+            _ => Err(RuntimeError::MemberNotFound(name.to_string())),
+        }
+    }
+
+    fn method(&self, name: &str) -> RuntimeResult<MethodCallType> {
+        let version = *self;
+        Ok(match name.to_ascii_lowercase().as_str() {
+            "tostring" => Box::new(move |_, args| {
+                let s = match args.as_slice() {
+                    [] => version.name(),
+                    [field_count] => version.format(field_count.cast_to_int()?.max(0) as usize)?,
+                    _ => return Err(MethodError::new_incorrect_args("ToString", args)),
+                };
+                Ok(Val::String(s.into()))
+            }),
+            "compareto" => Box::new(move |_, args| {
+                let [other] = args.as_slice() else {
+                    return Err(MethodError::new_incorrect_args("CompareTo", args));
+                };
+                let ordering = match other {
+                    Val::Null => Ordering::Greater,
+                    other => version.cmp(&Version::try_from_val(other)?),
+                };
+                Ok(Val::Int(ordering as i64))
+            }),
+            _ => Err(MethodError::MethodNotFound(name.to_string()))?,
+        })
+    }
+
+    fn members(&self) -> Vec<Member> {
+        vec![
+            Member::method("CompareTo", "int CompareTo(version value)"),
+            Member::method(
+                "ToString",
+                "string ToString(), string ToString(int fieldCount)",
+            ),
+            Member::property("Build", "int Build {get;}"),
+            Member::property("Major", "int Major {get;}"),
+            Member::property("Minor", "int Minor {get;}"),
+            Member::property("Revision", "int Revision {get;}"),
+        ]
+    }
+
+    fn compare(&self, other: &Val) -> Option<ValResult<Ordering>> {
+        Some(Version::try_from_val(other).map(|other| self.cmp(&other)))
+    }
+
+    fn name(&self) -> String {
+        let defined = [self.build, self.revision]
+            .iter()
+            .take_while(|c| **c >= 0)
+            .count();
+        self.format(2 + defined).unwrap_or_default()
+    }
+
+    fn type_definition(&self) -> RuntimeResult<ValType> {
+        Ok(ValType::RuntimeType(VersionType::TYPE_NAME.to_string()))
+    }
+
+    fn cast_to_script(&self) -> String {
+        format!("[version]\"{}\"", self.name())
+    }
+
+    fn clone_object(&self) -> Val {
+        (*self).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{NEWLINE, PowerShellSession, PsValue};
+
+    #[test]
+    fn compare_versions() {
+        let mut p = PowerShellSession::new();
+        let input = r#"$v = [version]"1.10.3"
+"$($v.Major) $($v.Minor) $($v.Build) $($v.Revision)"
+$v -gt [version]"1.9.12.4"
+$v -lt "1.10"
+$v -ge "1.10.3"
+[version]"2.0" -eq [version]::new(2, 0)
+[version]::new("1.2.3").Build
+$v.ToString(2)
+$PSVersionTable.PSVersion -ge [version]"5.0""#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(
+            s.output(),
+            [
+                "1 10 3 -1",
+                "True",
+                "False",
+                "True",
+                "True",
+                "3",
+                "1.10",
+                "True"
+            ]
+            .join(NEWLINE)
+        );
+        assert_eq!(s.errors().len(), 0);
+
+        let s = p.parse_input(r#"[version]"1.2.x""#).unwrap();
+        assert_eq!(
+            s.errors()[0].to_string(),
+            "ValError: Failed to convert value \"1.2.x\" to type Version"
+        );
+        let s = p.parse_input(r#"[version]"3.1" | % { "$_" }"#).unwrap();
+        assert_eq!(s.result(), PsValue::String("3.1".into()));
+    }
+}
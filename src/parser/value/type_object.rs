@@ -0,0 +1,206 @@
+use super::{
+    Member, MethodCallType, MethodError, RuntimeError, RuntimeObject, StaticFnCallType, Val,
+    ValResult, ValType, runtime_object::RuntimeResult,
+};
+
+// full names of the supported runtime types, the types are looked up lowercase
+const RUNTIME_TYPE_NAMES: [&str; 15] = [
+    "System.Convert",
+    "System.Math",
+    "System.Type",
+    "System.MidpointRounding",
+    "System.DateTime",
+    "System.Guid",
+    "System.TimeSpan",
+    "System.Version",
+    "System.Management.Automation.PSCustomObject",
+    "System.Text.Encoding",
+    "System.Text.UnicodeEncoding",
+    "System.Text.BigEndianUnicodeEncoding",
+    "System.Text.UTF8Encoding",
+    "System.Text.UTF7Encoding",
+    "System.Text.ASCIIEncoding",
+];
+
+// Type object, like [math] or [type]"System.Math". It gives access to the
+// static members of the type and, like System.Type, reports the type name
+#[derive(Debug)]
+pub(crate) struct TypeObject {
+    // name the type was resolved from, e.g. "math"
+    type_name: String,
+    full_name: String,
+    inner: Box<dyn RuntimeObject>,
+}
+
+impl TypeObject {
+    pub(crate) fn new(type_name: &str, ttype: &ValType, inner: Box<dyn RuntimeObject>) -> Self {
+        Self {
+            type_name: type_name.to_string(),
+            full_name: Self::full_name(type_name, ttype),
+            inner,
+        }
+    }
+
+    fn full_name(type_name: &str, ttype: &ValType) -> String {
+        let mut name = type_name.to_ascii_lowercase();
+        name.retain(|c| !c.is_whitespace());
+        if let Some(element_name) = name.strip_suffix("[]") {
+            let element = match ttype {
+                ValType::Array(Some(element)) => element.as_ref().clone(),
+                _ => ValType::Null,
+            };
+            return format!("{}[]", Self::full_name(element_name, &element));
+        }
+
+        let name = name.strip_prefix("system.").unwrap_or(&name);
+        let full_name = match (ttype, name) {
+            (ValType::RuntimeType(name), _) => {
+                return RUNTIME_TYPE_NAMES
+                    .iter()
+                    .find(|full_name| full_name.eq_ignore_ascii_case(name))
+                    .map(|full_name| full_name.to_string())
+                    .unwrap_or_else(|| name.clone());
+            }
+            (_, "byte") => "Byte",
+            (_, "sbyte") => "SByte",
+            (_, "int16") => "Int16",
+            (_, "uint16") => "UInt16",
+            (_, "uint32") => "UInt32",
+            (_, "long" | "int64") => "Int64",
+            (_, "decimal") => "Decimal",
+            (_, "float" | "single") => "Single",
+            (ValType::Bool, _) => "Boolean",
+            (ValType::Int, _) => "Int32",
+            (ValType::Float, _) => "Double",
+            (ValType::Char, _) => "Char",
+            (ValType::String, _) => "String",
+            (ValType::Array(_), _) => "Array",
+            (ValType::HashTable, _) => "Collections.Hashtable",
+            (ValType::ScriptBlock, _) => "Management.Automation.ScriptBlock",
+            (ValType::Switch, _) => "Management.Automation.SwitchParameter",
+            _ => "Object",
+        };
+        format!("System.{full_name}")
+    }
+
+    // [type] cast of a type name or of another type object
+    pub(crate) fn try_from_val(val: &Val) -> ValResult<Val> {
+        match val {
+            Val::RuntimeObject(rt) => match rt.readonly_member("fullname") {
+                Ok(full_name) => ValType::runtime(&full_name.cast_to_string()),
+                Err(_) => ValType::runtime(&rt.name()),
+            },
+            Val::NonDisplayed(val) => Self::try_from_val(val),
+            _ => ValType::runtime(&val.cast_to_string()),
+        }
+    }
+}
+
+impl RuntimeObject for TypeObject {
+    fn method(&self, name: &str) -> RuntimeResult<MethodCallType> {
+        match name.to_ascii_lowercase().as_str() {
+            "tostring" => {
+                let full_name = self.full_name.clone();
+                Ok(Box::new(move |_, args| {
+                    if !args.is_empty() {
+                        return Err(MethodError::new_incorrect_args("ToString", args));
+                    }
+                    Ok(Val::String(full_name.clone().into()))
+                }))
+            }
+            _ => self.inner.method(name),
+        }
+    }
+
+    fn static_method(&self, name: &str) -> RuntimeResult<StaticFnCallType> {
+        self.inner.static_method(name)
+    }
+
+    fn member(&mut self, name: &str) -> RuntimeResult<&mut Val> {
+        self.inner.member(name)
+    }
+
+    fn readonly_member(&self, name: &str) -> RuntimeResult<Val> {
+        match name.to_ascii_lowercase().as_str() {
+            "fullname" => Ok(Val::String(self.full_name.clone().into())),
+            "name" => {
+                let name = self.full_name.rsplit('.').next().unwrap_or_default();
+                Ok(Val::String(name.into()))
+            }
+            _ => self
+                .inner
+                .readonly_member(name)
+                .map_err(|_| RuntimeError::MemberNotFound(name.to_string())),
+        }
+    }
+
+    fn readonly_static_member(&self, name: &str) -> RuntimeResult<Val> {
+        self.inner.readonly_static_member(name)
+    }
+
+    fn members(&self) -> Vec<Member> {
+        let mut members = vec![
+            Member::method("ToString", "string ToString()"),
+            Member::property("FullName", "string FullName {get;}"),
+            Member::property("Name", "string Name {get;}"),
+        ];
+        members.extend(self.inner.members());
+        members
+    }
+
+    fn name(&self) -> String {
+        self.inner.name()
+    }
+
+    fn type_definition(&self) -> RuntimeResult<ValType> {
+        self.inner.type_definition()
+    }
+
+    fn clone_object(&self) -> Val {
+        ValType::runtime(&self.type_name).unwrap_or_default()
+    }
+
+    fn cast_to_script(&self) -> String {
+        self.inner.cast_to_script()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{NEWLINE, PowerShellSession};
+
+    #[test]
+    fn type_names() {
+        let mut p = PowerShellSession::new();
+        let input = r#"$t = [type]("System."+"Math")
+$t::Round(2.6)
+$t.ToString()
+[long].FullName
+[int[]].Name
+[text.utf8encoding].ToString()
+$n = -join [char[]](67,111,110,118,101,114,116)
+([type]$n)::ToInt32("12")
+([type][guid]).Name"#;
+        let s = p.parse_input(input).unwrap();
+        assert_eq!(
+            s.output(),
+            [
+                "3",
+                "System.Math",
+                "System.Int64",
+                "Int32[]",
+                "System.Text.UTF8Encoding",
+                "12",
+                "Guid",
+            ]
+            .join(NEWLINE)
+        );
+        assert_eq!(s.errors().len(), 0);
+
+        let s = p.parse_input(r#"[type]"System.IO.File""#).unwrap();
+        assert_eq!(
+            s.errors()[0].to_string(),
+            "ValError: Unknown type \"system.io.file\""
+        );
+    }
+}
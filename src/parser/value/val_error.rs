@@ -13,6 +13,12 @@ pub enum ValError {
     #[error("Failed to convert value {0} to type {1}")]
     InvalidCast(String, String),
 
+    #[error(
+        "Failed to convert value \"{0}\" to type {1}. Numbers are parsed culture-invariant, \
+         \",\" separator is not allowed"
+    )]
+    NonInvariantNumber(String, String),
+
     #[error("Unknown type \"{0}\"")]
     UnknownType(String),
 
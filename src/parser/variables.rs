@@ -8,9 +8,14 @@ use function::FunctionMap;
 use phf::phf_map;
 pub(super) use scopes::SessionScope;
 use thiserror_no_std::Error;
-pub(super) use variable::{Scope, VarName};
-
-use crate::parser::{Val, value::ScriptBlock};
+pub use variable::Scope;
+pub(super) use variable::VarName;
+
+use crate::parser::{
+    PsValue, Val,
+    command::{JSON_DEPTH, parse_json},
+    value::{ScriptBlock, Version},
+};
 #[derive(Error, Debug, PartialEq, Clone)]
 pub enum VariableError {
     #[error("Variable \"{0}\" is not defined")]
@@ -76,6 +81,21 @@ impl Variables {
         );
     }
 
+    // $PSVersionTable of the simulated PowerShell version, editions before 6.0
+    // are Windows PowerShell
+    pub(crate) fn set_ps_version_table(&mut self, major: i64, minor: i64) {
+        let version = Version::new(major, minor, -1, -1);
+        let edition = if major < 6 { "Desktop" } else { "Core" };
+        let table = HashMap::from([
+            ("psversion".to_string(), version.into()),
+            ("psedition".to_string(), Val::String(edition.into())),
+        ]);
+        let _ = self.set(
+            &VarName::new_with_scope(Scope::Special, "psversiontable".into()),
+            Val::HashTable(table),
+        );
+    }
+
     pub fn set_status(&mut self, b: bool) {
         let _ = self.set(
             &VarName::new_with_scope(Scope::Special, "$?".into()),
@@ -345,6 +365,81 @@ impl Variables {
         Ok(variables)
     }
 
+    /// Creates a new Variables instance with global variables loaded from a
+    /// JSON object.
+    ///
+    /// Every property of the object becomes a global variable. Values are
+    /// converted the same way as by `ConvertFrom-Json`: nested objects become
+    /// hashtables and arrays become arrays. Keys are lowercased, as variable
+    /// and hashtable keys are case-insensitive.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the text is not valid JSON, or if the document is
+    /// not an object.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ps_parser::{PowerShellSession, Variables};
+    ///
+    /// let json = r#"{"Name": "radek", "Servers": [{"Host": "srv1", "Port": 8080}]}"#;
+    /// let variables = Variables::from_json_string(json).unwrap();
+    /// let mut session = PowerShellSession::new().with_variables(variables);
+    ///
+    /// let result = session.safe_eval("$servers[0].host + ':' + $global:Servers[0].Port").unwrap();
+    /// assert_eq!(result, "srv1:8080");
+    /// ```
+    pub fn from_json_string(json_string: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let Val::HashTable(map) =
+            parse_json(json_string, JSON_DEPTH).map_err(|err| err.to_string())?
+        else {
+            return Err("JSON document has to be an object".into());
+        };
+        let mut variables = Self::new();
+        variables.global_scope.extend(map);
+        Ok(variables)
+    }
+
+    /// Create a new Variables instance with global variables loaded from a
+    /// JSON file
+    pub fn from_json_file(path: &std::path::Path) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::from_json_string(&std::fs::read_to_string(path)?)
+    }
+
+    /// Creates a new Variables instance with the values of the map inserted
+    /// into the given scope.
+    ///
+    /// Variable names are case-insensitive, so the keys are lowercased.
+    /// Values of the script and local scopes are cleared before each script
+    /// unless the variables persist, so the global scope is the usual choice
+    /// for seeding a session.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::collections::HashMap;
+    /// use ps_parser::{PowerShellSession, PsValue, Scope, Variables};
+    ///
+    /// let map = HashMap::from([
+    ///     ("UserName".to_string(), PsValue::String("radek".into())),
+    ///     ("retries".to_string(), PsValue::Int(3)),
+    /// ]);
+    /// let variables = Variables::from_map(Scope::Global, map);
+    /// let mut session = PowerShellSession::new().with_variables(variables);
+    ///
+    /// let result = session.safe_eval("\"$username $($global:RETRIES + 1)\"").unwrap();
+    /// assert_eq!(result, "radek 4");
+    /// ```
+    pub fn from_map(scope: Scope, map: HashMap<String, PsValue>) -> Self {
+        let mut variables = Self::new();
+        variables.map_from_scope(&scope).extend(
+            map.into_iter()
+                .map(|(key, value)| (key.to_ascii_lowercase(), value.into())),
+        );
+        variables
+    }
+
     fn const_map_from_scope(&self, scope: &Scope) -> &VariableMap {
         match scope {
             Scope::Global => &self.global_scope,
@@ -419,6 +514,26 @@ impl Variables {
         self.set(&var_name, val)
     }
 
+    /// Removes the variable from the specified scope, or from the innermost
+    /// scope that defines it. Returns the removed value.
+    pub(crate) fn remove(&mut self, var_name: &VarName) -> VariableResult<Option<Val>> {
+        let name = var_name.name.to_ascii_lowercase();
+        if let Some(scope) = &var_name.scope {
+            return Ok(self.map_from_scope(scope).remove(&name));
+        }
+        if Self::PREDEFINED_VARIABLES.contains_key(name.as_str()) {
+            return Err(VariableError::ReadOnly(name));
+        }
+
+        let map = self
+            .scope_sessions_stack
+            .iter_mut()
+            .rev()
+            .chain([&mut self.script_scope, &mut self.global_scope])
+            .find(|map| map.contains_key(&name));
+        Ok(map.and_then(|map| map.remove(&name)))
+    }
+
     fn find_mut_variable_in_scopes(
         &mut self,
         var_name: &VarName,
@@ -529,8 +644,10 @@ impl Variables {
 
 #[cfg(test)]
 mod tests {
-    use super::Variables;
-    use crate::{PowerShellSession, PsValue};
+    use std::collections::HashMap;
+
+    use super::{Scope, Variables};
+    use crate::{NEWLINE, PowerShellSession, PsValue};
 
     #[test]
     fn test_builtin_variables() {
@@ -659,6 +776,23 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_scoped_variables_in_function() {
+        let mut p = PowerShellSession::new();
+        let input = r#"
+function Set-Values { $global:x = 5; $script:y = 6; $z = 7 }
+Set-Values
+& { function g { $global:x += 1 }; g }
+$x; $y; $z"#;
+        let script_res = p.parse_input(input).unwrap();
+        assert_eq!(script_res.output(), ["6", "6"].join(NEWLINE));
+        assert_eq!(
+            script_res.errors().last().unwrap().to_string(),
+            "VariableError: Variable \"z\" is not defined"
+        );
+        assert_eq!(p.session_variables().get("x").unwrap(), &PsValue::Int(6));
+    }
+
     #[test]
     fn test_env_special_cases() {
         let v = Variables::env();
@@ -699,6 +833,31 @@ mod tests {
         assert_eq!(p.safe_eval(input).unwrap().as_str(), "False");
     }
 
+    #[test]
+    fn ps_version_table() {
+        let input = r#"
+if ($PSVersionTable.PSVersion.Major -ge 6) { "core" } else { "desktop" }
+$PSVersionTable["PSEdition"]
+"$($PSVersionTable.PSVersion.Major).$($PSVersionTable.PSVersion.Minor)"
+"#;
+        let s = PowerShellSession::new().parse_input(input).unwrap();
+        assert_eq!(s.output(), ["desktop", "Desktop", "5.1"].join(NEWLINE));
+
+        let s = PowerShellSession::new()
+            .with_ps_version(7, 2)
+            .with_variables(Variables::force_eval())
+            .parse_input(input)
+            .unwrap();
+        assert_eq!(s.output(), ["core", "Core", "7.2"].join(NEWLINE));
+
+        let input = r#"$PSVersionTable.PSVersion -lt [version]"7.2.1""#;
+        let s = PowerShellSession::new()
+            .with_ps_version(7, 2)
+            .parse_input(input)
+            .unwrap();
+        assert_eq!(s.output(), "True");
+    }
+
     #[test]
     fn test_from_ini() {
         let input = r#"[global]
@@ -773,4 +932,92 @@ local_var = "local_value"
             "\"local_value\""
         );
     }
+
+    #[test]
+    fn test_from_map() {
+        let map = HashMap::from([
+            ("Name".to_string(), PsValue::String("radek".into())),
+            ("AGE".to_string(), PsValue::Int(30)),
+            (
+                "Tags".to_string(),
+                PsValue::Array(vec![PsValue::Bool(true), PsValue::Float(5.9)]),
+            ),
+        ]);
+        let mut p =
+            PowerShellSession::new().with_variables(Variables::from_map(Scope::Global, map));
+        assert_eq!(
+            p.parse_input(r#" $global:name "#).unwrap().result(),
+            PsValue::String("radek".into())
+        );
+        assert_eq!(
+            p.parse_input(r#" $age + 1 "#).unwrap().result(),
+            PsValue::Int(31)
+        );
+        assert_eq!(p.safe_eval(r#" $TAGS[1] "#).unwrap().as_str(), "5.9");
+
+        let map = HashMap::from([("Path".to_string(), PsValue::String("C:\\Windows".into()))]);
+        let mut p = PowerShellSession::new().with_variables(Variables::from_map(Scope::Env, map));
+        assert_eq!(
+            p.safe_eval(r#" $env:PATH "#).unwrap().as_str(),
+            "C:\\Windows"
+        );
+        assert_eq!(p.safe_eval(r#" $path "#).unwrap().as_str(), "");
+    }
+
+    #[test]
+    fn test_from_json_string() {
+        let input = r#"{
+    "Name": "radek",
+    "Age": 30,
+    "Height": 5.9,
+    "IsAdmin": true,
+    "Empty": null,
+    "Servers": [{ "Host": "srv1", "Ports": [80, 443] }],
+    "Limits": { "Memory": { "Max": 1024 } }
+}"#;
+        let mut p =
+            PowerShellSession::new().with_variables(Variables::from_json_string(input).unwrap());
+        assert_eq!(
+            p.parse_input(r#" $global:name "#).unwrap().result(),
+            PsValue::String("radek".into())
+        );
+        assert_eq!(
+            p.parse_input(r#" $AGE "#).unwrap().result(),
+            PsValue::Int(30)
+        );
+        assert_eq!(
+            p.parse_input(r#" $height "#).unwrap().result(),
+            PsValue::Float(5.9)
+        );
+        assert_eq!(p.safe_eval(r#" $isadmin "#).unwrap().as_str(), "True");
+        assert_eq!(
+            p.parse_input(r#" $null -eq $empty "#).unwrap().result(),
+            PsValue::Bool(true)
+        );
+        assert_eq!(
+            p.safe_eval(r#" $servers[0].Host + ':' + $servers[0].ports[1] "#)
+                .unwrap()
+                .as_str(),
+            "srv1:443"
+        );
+        assert_eq!(
+            p.parse_input(r#" $limits.memory.Max "#).unwrap().result(),
+            PsValue::Int(1024)
+        );
+
+        assert_eq!(
+            Variables::from_json_string("[1, 2]")
+                .err()
+                .unwrap()
+                .to_string(),
+            "JSON document has to be an object"
+        );
+        assert_eq!(
+            Variables::from_json_string(r#"{"a": }"#)
+                .err()
+                .unwrap()
+                .to_string(),
+            "Conversion from JSON failed. Invalid JSON primitive at position 6"
+        );
+    }
 }
@@ -7,13 +7,22 @@ pub(super) type FunctionMap = HashMap<String, ScriptBlock>;
 
 impl Variables {
     pub(crate) fn get_function(&mut self, name: &str) -> Option<CallablePredType> {
-        if let Some(fun) = self.script_functions.get(name).cloned() {
-            self.get_function_from_script_block(fun)
-        } else if let Some(fun) = self.global_functions.get(name).cloned() {
-            self.get_function_from_script_block(fun)
-        } else {
-            None
-        }
+        let fun = self.function_script_block(name)?.clone();
+        self.get_function_from_script_block(fun)
+    }
+
+    // functions of the script shadow the global ones
+    pub(crate) fn function_script_block(&self, name: &str) -> Option<&ScriptBlock> {
+        self.script_functions
+            .get(name)
+            .or_else(|| self.global_functions.get(name))
+    }
+
+    // functions defined by the script and the global ones, by name
+    pub(crate) fn functions(&self) -> Vec<(String, ScriptBlock)> {
+        let mut functions = self.global_functions.clone();
+        functions.extend(self.script_functions.clone());
+        functions.into_iter().collect()
     }
 
     pub(crate) fn get_function_from_script_block(
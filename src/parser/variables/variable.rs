@@ -34,7 +34,7 @@ impl std::fmt::Display for VarName {
 }
 
 #[derive(Debug, Eq, Hash, PartialEq, Clone)]
-pub(crate) enum Scope {
+pub enum Scope {
     Special,
     Global,
     Script,